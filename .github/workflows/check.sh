@@ -9,9 +9,10 @@ cargo fmt --check || (
 )
 
 cargo clippy -- -Dwarnings
+cargo clippy --no-default-features --features inference -- -Dwarnings
 
 install -d tests/data/
 pushd tests/data/
 wget https://github.com/open-spaced-repetition/fsrs-optimizer-burn/files/12394182/collection.anki21.zip
 unzip *.zip
-SKIP_TRAINING=1 cargo test --release
+SKIP_TRAINING=1 cargo test --release --features serde
@@ -16,10 +16,14 @@ use itertools::Itertools;
 pub(crate) fn calc_mem(inf: &FSRS, past_reviews: usize) -> MemoryState {
     let review = FSRSReview {
         rating: 3,
-        delta_t: 21,
+        delta_t: 21.0,
+        duration_ms: None,
     };
     let reviews = repeat(review.clone()).take(past_reviews + 1).collect_vec();
-    inf.memory_state(FSRSItem { reviews })
+    inf.memory_state(FSRSItem {
+        reviews,
+        group: None,
+    })
 }
 
 pub(crate) fn next_states(inf: &FSRS) -> NextStates {
@@ -29,7 +33,8 @@ pub(crate) fn next_states(inf: &FSRS) -> NextStates {
             difficulty: 7.005062,
         }),
         0.9,
-        21,
+        21.0,
+        36500,
     )
 }
 
@@ -0,0 +1,242 @@
+//! Reading and writing items as Arrow record batches, Arrow IPC files and Parquet files.
+//!
+//! Items are stored in long format, with one row per review:
+//!
+//! | column        | type    |                                                    |
+//! |---------------|---------|----------------------------------------------------|
+//! | `item`        | uint64  | the position of the item the review belongs to     |
+//! | `rating`      | uint32  | 1-4                                                |
+//! | `delta_t`     | float32 | days since the previous review, as in [FSRSReview] |
+//! | `duration_ms` | uint32  | [FSRSReview::duration_ms], or null                 |
+//! | `group`       | uint64  | the item's [FSRSItem::group], or null              |
+//!
+//! Rows are in review order, so a file can be read with pandas or polars and grouped by `item`.
+
+use crate::dataset::{FSRSItem, FSRSReview};
+use crate::error::{FSRSError, Result};
+use arrow::array::{Array, ArrayRef, Float32Array, UInt32Array, UInt64Array};
+use arrow::datatypes::{DataType, Field, Schema, SchemaRef};
+use arrow::ipc::reader::FileReader;
+use arrow::ipc::writer::FileWriter;
+use arrow::record_batch::RecordBatch;
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ArrowWriter;
+use parquet::file::reader::ChunkReader;
+use std::io::{Read, Seek, Write};
+use std::sync::Arc;
+
+fn schema() -> SchemaRef {
+    Arc::new(Schema::new(vec![
+        Field::new("item", DataType::UInt64, false),
+        Field::new("rating", DataType::UInt32, false),
+        Field::new("delta_t", DataType::Float32, false),
+        Field::new("duration_ms", DataType::UInt32, true),
+        Field::new("group", DataType::UInt64, true),
+    ]))
+}
+
+fn serialization_error(err: impl ToString) -> FSRSError {
+    FSRSError::Serialization {
+        message: err.to_string(),
+    }
+}
+
+/// Encode items as a single record batch, with one row per review.
+pub fn items_to_record_batch(items: &[FSRSItem]) -> Result<RecordBatch> {
+    let reviews = || items.iter().flat_map(|item| item.reviews.iter());
+    let item_ids: UInt64Array = items
+        .iter()
+        .enumerate()
+        .flat_map(|(index, item)| std::iter::repeat(index as u64).take(item.reviews.len()))
+        .collect::<Vec<_>>()
+        .into();
+    let ratings: UInt32Array = reviews().map(|r| r.rating).collect::<Vec<_>>().into();
+    let delta_ts: Float32Array = reviews().map(|r| r.delta_t).collect::<Vec<_>>().into();
+    let durations: UInt32Array = reviews().map(|r| r.duration_ms).collect::<Vec<_>>().into();
+    let groups: UInt64Array = items
+        .iter()
+        .flat_map(|item| std::iter::repeat(item.group).take(item.reviews.len()))
+        .collect::<Vec<_>>()
+        .into();
+    RecordBatch::try_new(
+        schema(),
+        vec![
+            Arc::new(item_ids) as ArrayRef,
+            Arc::new(ratings),
+            Arc::new(delta_ts),
+            Arc::new(durations),
+            Arc::new(groups),
+        ],
+    )
+    .map_err(serialization_error)
+}
+
+fn column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<&'a T> {
+    let column = batch
+        .column_by_name(name)
+        .ok_or_else(|| serialization_error(format!("missing column {name}")))?;
+    if column.null_count() > 0 {
+        return Err(serialization_error(format!("column {name} has nulls")));
+    }
+    column
+        .as_any()
+        .downcast_ref()
+        .ok_or_else(|| serialization_error(format!("column {name} has the wrong type")))
+}
+
+/// A nullable column that older files or other tools may leave out.
+fn optional_column<'a, T: 'static>(batch: &'a RecordBatch, name: &str) -> Result<Option<&'a T>> {
+    batch
+        .column_by_name(name)
+        .map(|column| {
+            column
+                .as_any()
+                .downcast_ref()
+                .ok_or_else(|| serialization_error(format!("column {name} has the wrong type")))
+        })
+        .transpose()
+}
+
+fn optional_value<T: arrow::datatypes::ArrowPrimitiveType>(
+    column: Option<&arrow::array::PrimitiveArray<T>>,
+    row: usize,
+) -> Option<T::Native> {
+    column
+        .filter(|column| column.is_valid(row))
+        .map(|column| column.value(row))
+}
+
+/// Decode items from record batches in the format of [items_to_record_batch]. Consecutive rows
+/// with the same `item` form one item, which may span batches. The `group` column is optional,
+/// and is read from each item's first row, and the `duration_ms` column is optional.
+pub fn items_from_record_batches(
+    batches: impl IntoIterator<Item = RecordBatch>,
+) -> Result<Vec<FSRSItem>> {
+    let mut items: Vec<FSRSItem> = vec![];
+    let mut current_id = None;
+    for batch in batches {
+        let item_ids: &UInt64Array = column(&batch, "item")?;
+        let ratings: &UInt32Array = column(&batch, "rating")?;
+        let delta_ts: &Float32Array = column(&batch, "delta_t")?;
+        let durations: Option<&UInt32Array> = optional_column(&batch, "duration_ms")?;
+        let groups: Option<&UInt64Array> = optional_column(&batch, "group")?;
+        for row in 0..batch.num_rows() {
+            let review = FSRSReview {
+                rating: ratings.value(row),
+                delta_t: delta_ts.value(row),
+                duration_ms: optional_value(durations, row),
+            };
+            let id = item_ids.value(row);
+            match items.last_mut() {
+                Some(item) if current_id == Some(id) => item.reviews.push(review),
+                _ => items.push(FSRSItem {
+                    reviews: vec![review],
+                    group: optional_value(groups, row),
+                }),
+            }
+            current_id = Some(id);
+        }
+    }
+    Ok(items)
+}
+
+/// Write items to an Arrow IPC file, which pandas and polars read with `read_feather` and
+/// `read_ipc`.
+pub fn write_ipc(items: &[FSRSItem], writer: impl Write) -> Result<()> {
+    let batch = items_to_record_batch(items)?;
+    let mut writer = FileWriter::try_new(writer, &batch.schema()).map_err(serialization_error)?;
+    writer.write(&batch).map_err(serialization_error)?;
+    writer.finish().map_err(serialization_error)
+}
+
+/// Read items from an Arrow IPC file written by [write_ipc].
+pub fn read_ipc(reader: impl Read + Seek) -> Result<Vec<FSRSItem>> {
+    let batches = FileReader::try_new(reader, None)
+        .map_err(serialization_error)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(serialization_error)?;
+    items_from_record_batches(batches)
+}
+
+/// Write items to a Parquet file.
+pub fn write_parquet(items: &[FSRSItem], writer: impl Write + Send) -> Result<()> {
+    let batch = items_to_record_batch(items)?;
+    let mut writer =
+        ArrowWriter::try_new(writer, batch.schema(), None).map_err(serialization_error)?;
+    writer.write(&batch).map_err(serialization_error)?;
+    writer.close().map_err(serialization_error)?;
+    Ok(())
+}
+
+/// Read items from a Parquet file, such as a [std::fs::File], written by [write_parquet] or by
+/// pandas or polars with the same columns.
+pub fn read_parquet(reader: impl ChunkReader + 'static) -> Result<Vec<FSRSItem>> {
+    let batches = ParquetRecordBatchReaderBuilder::try_new(reader)
+        .map_err(serialization_error)?
+        .build()
+        .map_err(serialization_error)?
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(serialization_error)?;
+    items_from_record_batches(batches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    fn items() -> Vec<FSRSItem> {
+        let review = |rating, delta_t| FSRSReview {
+            rating,
+            delta_t,
+            duration_ms: (rating != 4).then_some(rating * 1000),
+        };
+        vec![
+            FSRSItem {
+                reviews: vec![review(3, 0.0), review(3, 1.0)],
+                group: None,
+            },
+            FSRSItem {
+                reviews: vec![review(3, 0.0), review(3, 1.0), review(1, 3.5)],
+                group: None,
+            },
+            FSRSItem {
+                reviews: vec![review(4, 0.0), review(2, 0.0)],
+                group: Some(7),
+            },
+        ]
+    }
+
+    #[test]
+    fn round_trip() -> Result<()> {
+        let items = items();
+
+        let batch = items_to_record_batch(&items)?;
+        assert_eq!(batch.num_rows(), 7);
+        assert_eq!(
+            items_from_record_batches([batch.slice(0, 3), batch.slice(3, 4)])?,
+            items
+        );
+
+        let mut ipc = vec![];
+        write_ipc(&items, &mut ipc)?;
+        assert_eq!(read_ipc(Cursor::new(ipc))?, items);
+
+        let path = std::env::temp_dir().join(format!("fsrs-{}.parquet", std::process::id()));
+        write_parquet(&items, std::fs::File::create(&path).unwrap())?;
+        let read = read_parquet(std::fs::File::open(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read?, items);
+
+        let minimal = batch.project(&[0, 1, 2]).unwrap();
+        assert!(items_from_record_batches([minimal])?.iter().all(|item| {
+            item.group.is_none() && item.reviews.iter().all(|r| r.duration_ms.is_none())
+        }));
+        let missing = batch.project(&[0, 1]).unwrap();
+        assert!(matches!(
+            items_from_record_batches([missing]),
+            Err(FSRSError::Serialization { .. })
+        ));
+        Ok(())
+    }
+}
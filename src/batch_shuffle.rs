@@ -2,6 +2,24 @@ use burn::data::dataset::Dataset;
 use rand::{prelude::SliceRandom, rngs::StdRng, SeedableRng};
 use std::marker::PhantomData;
 
+/// How training items are ordered into batches. Items are split into batches in the order
+/// provided, which is usually grouped by card or by date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BatchShuffle {
+    /// Batches are used in the order provided.
+    None,
+    /// Items stay in their batches, and the order of the batches is shuffled.
+    #[default]
+    Batches,
+    /// Items are dealt out so that each batch has a similar spread of review counts, and the
+    /// order of the batches is shuffled. The final, partial batch is always used last.
+    Stratified,
+    /// Items with similar review counts are batched together, and the order of the batches is
+    /// shuffled. Batches are padded to their longest item, so this saves much of the work on
+    /// collections with a few very long histories.
+    Bucketed,
+}
+
 pub(crate) struct BatchShuffledDataset<D, I> {
     dataset: D,
     indices: Vec<usize>,
@@ -46,6 +64,91 @@ where
         let mut rng = StdRng::seed_from_u64(seed);
         Self::new(dataset, batch_size, &mut rng)
     }
+
+    /// Orders the dataset according to `shuffle`. `len` gives the length that
+    /// [BatchShuffle::Stratified] spreads across batches.
+    pub fn with_strategy(
+        dataset: D,
+        batch_size: usize,
+        seed: u64,
+        shuffle: BatchShuffle,
+        len: impl Fn(&I) -> usize,
+    ) -> Self {
+        let indices = match shuffle {
+            BatchShuffle::None => (0..dataset.len()).collect(),
+            BatchShuffle::Batches => return Self::with_seed(dataset, batch_size, seed),
+            BatchShuffle::Stratified | BatchShuffle::Bucketed => {
+                let mut rng = StdRng::seed_from_u64(seed);
+                let lens: Vec<_> = (0..dataset.len())
+                    .map(|i| dataset.get(i).map_or(0, |item| len(&item)))
+                    .collect();
+                if shuffle == BatchShuffle::Stratified {
+                    stratified_indices(&lens, batch_size, &mut rng)
+                } else {
+                    bucketed_indices(&lens, batch_size, &mut rng)
+                }
+            }
+        };
+        Self {
+            dataset,
+            indices,
+            input: PhantomData,
+        }
+    }
+}
+
+/// Indices sorted by length, with items of equal length in random order.
+fn sorted_by_len(lens: &[usize], rng: &mut StdRng) -> Vec<usize> {
+    let mut sorted: Vec<_> = (0..lens.len()).collect();
+    sorted.shuffle(rng);
+    sorted.sort_by_key(|&i| lens[i]);
+    sorted
+}
+
+/// Shuffle the order of the batches, keeping a final partial batch at the end so the data loader
+/// splits the items back into the same batches.
+fn shuffle_batches(
+    mut batches: Vec<Vec<usize>>,
+    batch_size: usize,
+    rng: &mut StdRng,
+) -> Vec<usize> {
+    let partial = match batches.last() {
+        Some(last) if last.len() < batch_size => batches.pop(),
+        _ => None,
+    };
+    batches.shuffle(rng);
+    batches.into_iter().chain(partial).flatten().collect()
+}
+
+/// Deal items out to the batches in order of length, so each full batch gets items from across
+/// the range of lengths.
+fn stratified_indices(lens: &[usize], batch_size: usize, rng: &mut StdRng) -> Vec<usize> {
+    let len = lens.len();
+    let num_batches = (len + batch_size - 1) / batch_size;
+    let sorted = sorted_by_len(lens, rng);
+
+    let capacity = |batch: usize| batch_size.min(len - batch * batch_size);
+    let mut batches: Vec<Vec<usize>> = (0..num_batches)
+        .map(|batch| Vec::with_capacity(capacity(batch)))
+        .collect();
+    let mut batch = 0;
+    for index in sorted {
+        while batches[batch].len() == capacity(batch) {
+            batch = (batch + 1) % num_batches;
+        }
+        batches[batch].push(index);
+        batch = (batch + 1) % num_batches;
+    }
+    shuffle_batches(batches, batch_size, rng)
+}
+
+/// Split items into batches in order of length, so each batch needs little padding.
+fn bucketed_indices(lens: &[usize], batch_size: usize, rng: &mut StdRng) -> Vec<usize> {
+    let batches = sorted_by_len(lens, rng)
+        .chunks(batch_size)
+        .map(<[usize]>::to_vec)
+        .collect();
+    shuffle_batches(batches, batch_size, rng)
 }
 
 impl<D, I> Dataset<I> for BatchShuffledDataset<D, I>
@@ -69,11 +172,11 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::dataset::FSRSDataset;
     use crate::{convertor_tests::anki21_sample_file_converted_to_fsrs, FSRSItem, FSRSReview};
 
     #[test]
     fn batch_shuffle() {
-        use crate::dataset::FSRSDataset;
         let dataset = FSRSDataset::from(anki21_sample_file_converted_to_fsrs());
         let batch_size = 10;
         let seed = 42;
@@ -87,241 +190,301 @@ mod tests {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 8,
+                            delta_t: 8.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 21,
+                            delta_t: 21.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 1,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 7,
+                            delta_t: 7.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 19,
+                            delta_t: 19.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 1,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 8,
+                            delta_t: 8.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 19,
+                            delta_t: 19.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 5,
+                            delta_t: 5.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 11,
+                            delta_t: 11.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 6,
+                            delta_t: 6.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 17,
+                            delta_t: 17.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 8,
+                            delta_t: 8.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 20,
+                            delta_t: 20.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 4,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 8,
+                            delta_t: 8.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 4,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 5,
+                            delta_t: 5.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 4,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 5,
+                            delta_t: 5.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
                 FSRSItem {
                     reviews: vec![
                         FSRSReview {
                             rating: 4,
-                            delta_t: 0,
+                            delta_t: 0.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 1,
+                            delta_t: 1.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 3,
+                            delta_t: 3.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 9,
+                            delta_t: 9.0,
+                            duration_ms: None,
                         },
                         FSRSReview {
                             rating: 3,
-                            delta_t: 19,
+                            delta_t: 19.0,
+                            duration_ms: None,
                         }
                     ],
+                    group: None,
                 },
             ]
         );
@@ -329,7 +492,6 @@ mod tests {
 
     #[test]
     fn item_shuffle() {
-        use crate::dataset::FSRSDataset;
         use burn::data::dataset::transform::ShuffledDataset;
         let dataset = FSRSDataset::from(anki21_sample_file_converted_to_fsrs());
         let seed = 42;
@@ -338,4 +500,36 @@ mod tests {
             dbg!(shuffled_dataset.get(i).unwrap());
         }
     }
+
+    #[test]
+    fn length_aware_shuffles() {
+        let lens: Vec<usize> = (0..25).map(|i| i % 5 + 2).collect();
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut indices = stratified_indices(&lens, 10, &mut rng);
+        indices.sort();
+        assert_eq!(indices, (0..25).collect::<Vec<_>>());
+
+        let indices = stratified_indices(&lens[..20], 10, &mut rng);
+        for batch in indices.chunks(10) {
+            let mut batch_lens: Vec<_> = batch.iter().map(|&i| lens[i]).collect();
+            batch_lens.sort();
+            assert_eq!(batch_lens, [2, 2, 3, 3, 4, 4, 5, 5, 6, 6]);
+        }
+
+        let indices = bucketed_indices(&lens[..20], 10, &mut rng);
+        for batch in indices.chunks(10) {
+            let batch_lens: Vec<_> = batch.iter().map(|&i| lens[i]).collect();
+            assert!(batch_lens == [2, 2, 2, 2, 3, 3, 3, 3, 4, 4] || batch_lens[0] >= 4);
+        }
+
+        let dataset = FSRSDataset::from(anki21_sample_file_converted_to_fsrs());
+        let unshuffled = BatchShuffledDataset::with_strategy(
+            dataset,
+            10,
+            42,
+            BatchShuffle::None,
+            |item: &FSRSItem| item.reviews.len(),
+        );
+        assert_eq!(unshuffled.indices[..3], [0, 1, 2]);
+    }
 }
@@ -0,0 +1,97 @@
+use crate::error::{FSRSError, Result};
+use crate::{FSRSItem, DEFAULT_WEIGHTS, FSRS};
+use std::ops::Range;
+
+/// The metrics obtained for a single collection by [run_benchmark].
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchmarkResult {
+    pub name: String,
+    /// The number of items that were evaluated, summed over all splits.
+    pub size: usize,
+    pub log_loss: f32,
+    pub rmse_bins: f32,
+}
+
+/// Train and evaluate on each of the provided collections, following the protocol of the
+/// fsrs-benchmark repository: each collection is split chronologically into `n_splits + 1`
+/// folds, and for every split the weights are trained on all preceding folds and evaluated on
+/// the following one. Metrics are averaged over splits, weighted by the number of items tested.
+///
+/// The items of each collection must be sorted by the time of their final review. Training
+/// uses fixed seeds, so results are reproducible.
+pub fn run_benchmark(
+    collections: Vec<(String, Vec<FSRSItem>)>,
+    n_splits: usize,
+) -> Result<Vec<BenchmarkResult>> {
+    collections
+        .into_iter()
+        .map(|(name, items)| benchmark_collection(name, items, n_splits))
+        .collect()
+}
+
+fn benchmark_collection(
+    name: String,
+    items: Vec<FSRSItem>,
+    n_splits: usize,
+) -> Result<BenchmarkResult> {
+    let mut size = 0;
+    let mut log_loss = 0.0;
+    let mut rmse_bins = 0.0;
+    for (train, test) in time_series_splits(items.len(), n_splits) {
+        let trainset = items[train].to_vec();
+        let testset = items[test].to_vec();
+        let weights = match FSRS::new(None)?.compute_weights(trainset, None) {
+            Ok(weights) => weights,
+            // early splits of small collections may not have enough data to train on
+            Err(FSRSError::NotEnoughData) => DEFAULT_WEIGHTS.to_vec(),
+            Err(err) => return Err(err),
+        };
+        let test_size = testset.len();
+        let evaluation = FSRS::new(Some(&weights))?.evaluate(testset, |_| true)?;
+        size += test_size;
+        log_loss += evaluation.log_loss * test_size as f32;
+        rmse_bins += evaluation.rmse_bins * test_size as f32;
+    }
+    if size == 0 {
+        return Err(FSRSError::NotEnoughData);
+    }
+    Ok(BenchmarkResult {
+        name,
+        size,
+        log_loss: log_loss / size as f32,
+        rmse_bins: rmse_bins / size as f32,
+    })
+}
+
+/// Returns (train, test) index ranges. The final test fold absorbs any remainder.
+fn time_series_splits(len: usize, n_splits: usize) -> Vec<(Range<usize>, Range<usize>)> {
+    let fold_size = len / (n_splits + 1);
+    if fold_size == 0 {
+        return vec![];
+    }
+    (1..=n_splits)
+        .map(|split| {
+            let train_end = fold_size * split;
+            let test_end = if split == n_splits {
+                len
+            } else {
+                train_end + fold_size
+            };
+            (0..train_end, train_end..test_end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits() {
+        assert_eq!(
+            time_series_splits(13, 3),
+            [(0..3, 3..6), (0..6, 6..9), (0..9, 9..13)]
+        );
+        assert!(time_series_splits(3, 5).is_empty());
+    }
+}
@@ -171,10 +171,14 @@ fn convert_to_fsrs_items(
                     .take(idx + 1)
                     .map(|r| FSRSReview {
                         rating: r.button_chosen as u32,
-                        delta_t: r.last_interval.max(0) as u32,
+                        delta_t: r.last_interval.max(0) as f32,
+                        duration_ms: None,
                     })
                     .collect();
-                FSRSItem { reviews }
+                FSRSItem {
+                    reviews,
+                    group: None,
+                }
             })
             .collect(),
     )
@@ -379,101 +383,126 @@ fn conversion_works() {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5
+                        delta_t: 5.0,
+                        duration_ms: None
                     }
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5
+                        delta_t: 5.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 10
+                        delta_t: 10.0,
+                        duration_ms: None
                     }
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5
+                        delta_t: 5.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 10
+                        delta_t: 10.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 22
+                        delta_t: 22.0,
+                        duration_ms: None
                     }
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5
+                        delta_t: 5.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 10
+                        delta_t: 10.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 22
+                        delta_t: 22.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 2,
-                        delta_t: 56
+                        delta_t: 56.0,
+                        duration_ms: None
                     }
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5
+                        delta_t: 5.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 10
+                        delta_t: 10.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 22
+                        delta_t: 22.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 2,
-                        delta_t: 56
+                        delta_t: 56.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 64
+                        delta_t: 64.0,
+                        duration_ms: None
                     }
                 ],
+                group: None,
             }
         ]
     );
@@ -502,13 +531,16 @@ fn ordering_of_inputs_should_not_change() {
             reviews: vec![
                 FSRSReview {
                     rating: 4,
-                    delta_t: 0
+                    delta_t: 0.0,
+                    duration_ms: None
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 3
+                    delta_t: 3.0,
+                    duration_ms: None
                 }
-            ]
+            ],
+            group: None
         }
     );
 }
@@ -539,13 +571,16 @@ fn delta_t_is_correct() -> Result<()> {
             reviews: vec![
                 FSRSReview {
                     rating: 3,
-                    delta_t: 0
+                    delta_t: 0.0,
+                    duration_ms: None
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 1
+                    delta_t: 1.0,
+                    duration_ms: None
                 }
-            ]
+            ],
+            group: None
         }])
     );
 
@@ -565,49 +600,61 @@ fn delta_t_is_correct() -> Result<()> {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 2
+                        delta_t: 2.0,
+                        duration_ms: None
                     }
-                ]
+                ],
+                group: None
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 2
+                        delta_t: 2.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 3
+                        delta_t: 3.0,
+                        duration_ms: None
                     }
-                ]
+                ],
+                group: None
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 3,
-                        delta_t: 0
+                        delta_t: 0.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 2
+                        delta_t: 2.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 3
+                        delta_t: 3.0,
+                        duration_ms: None
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5
+                        delta_t: 5.0,
+                        duration_ms: None
                     }
-                ]
+                ],
+                group: None
             }
         ])
     );
@@ -1,11 +1,21 @@
+#[cfg(feature = "training")]
 use std::collections::HashMap;
 
+use crate::error::{FSRSError, Result};
+#[cfg(feature = "training")]
 use burn::data::dataloader::batcher::Batcher;
+#[cfg(feature = "training")]
 use burn::{
     data::dataset::Dataset,
     tensor::{backend::Backend, Data, ElementConversion, Float, Int, Shape, Tensor},
 };
+use rand::{rngs::StdRng, seq::SliceRandom, SeedableRng};
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt::Write;
+use std::hash::Hash;
+#[cfg(feature = "training")]
+use std::sync::Arc;
 
 /// Stores a list of reviews for a card, in chronological order. Each FSRSItem corresponds
 /// to a single review, but contains the previous reviews of the card as well, after the
@@ -13,18 +23,382 @@ use serde::{Deserialize, Serialize};
 /// When used during review, the last item should include the correct delta_t, but
 /// the provided rating is ignored as all four ratings are returned by .next_states()
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct FSRSItem {
     pub reviews: Vec<FSRSReview>,
+    /// An opaque id chosen by the caller, such as a deck or preset id, for training and
+    /// evaluating groups of items separately. See [group_items].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<u64>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct FSRSReview {
     /// 1-4
     pub rating: u32,
-    /// The number of days that passed
-    pub delta_t: u32,
+    /// The number of days that passed, which may be fractional for reviews made earlier or later
+    /// in the day. A `delta_t` under 1 marks a review on the same day as the previous one.
+    pub delta_t: f32,
+    /// How long the user took to answer, if known. Only used for estimating the costs the
+    /// simulator needs, with [crate::SimulatorConfig::estimate_costs_from_items].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u32>,
 }
 
+impl FSRSItem {
+    /// Build items from a card's timestamped reviews.
+    pub fn builder() -> FSRSItemBuilder {
+        FSRSItemBuilder::default()
+    }
+}
+
+/// Builds items from a card's reviews, given as `(timestamp, rating)` pairs in any order.
+/// Timestamps are Unix times in seconds, and are converted to the local day of each review, using
+/// [FSRSItemBuilder::utc_offset] and [FSRSItemBuilder::next_day_starts_at], so `delta_t` counts
+/// the days the user saw pass.
+#[derive(Debug, Clone, Default)]
+pub struct FSRSItemBuilder {
+    reviews: Vec<(i64, u32, Option<u32>)>,
+    utc_offset: i32,
+    next_day_starts_at: u32,
+    group: Option<u64>,
+}
+
+impl FSRSItemBuilder {
+    pub fn review(mut self, timestamp: i64, rating: u32) -> Self {
+        self.reviews.push((timestamp, rating, None));
+        self
+    }
+
+    /// A review that took the user `duration_ms` to answer.
+    pub fn timed_review(mut self, timestamp: i64, rating: u32, duration_ms: u32) -> Self {
+        self.reviews.push((timestamp, rating, Some(duration_ms)));
+        self
+    }
+
+    pub fn reviews(mut self, reviews: impl IntoIterator<Item = (i64, u32)>) -> Self {
+        self.reviews.extend(
+            reviews
+                .into_iter()
+                .map(|(timestamp, rating)| (timestamp, rating, None)),
+        );
+        self
+    }
+
+    /// The user's offset from UTC in seconds, such as 3600 for UTC+1.
+    pub fn utc_offset(mut self, seconds: i32) -> Self {
+        self.utc_offset = seconds;
+        self
+    }
+
+    /// The hour, from 0 to 23, at which a new day begins, so late-night reviews count towards
+    /// the previous day.
+    pub fn next_day_starts_at(mut self, hour: u32) -> Self {
+        self.next_day_starts_at = hour;
+        self
+    }
+
+    /// The [FSRSItem::group] of the built items.
+    pub fn group(mut self, group: u64) -> Self {
+        self.group = Some(group);
+        self
+    }
+
+    /// The card's full history, as passed to [crate::FSRS::memory_state].
+    pub fn build_history(self) -> Result<FSRSItem> {
+        if !(0..24).contains(&self.next_day_starts_at) {
+            return Err(FSRSError::InvalidInput {
+                field: "next_day_starts_at",
+            });
+        }
+        if self.reviews.is_empty() {
+            return Err(FSRSError::InvalidInput { field: "reviews" });
+        }
+        if self
+            .reviews
+            .iter()
+            .any(|(_, rating, _)| !(1..=4).contains(rating))
+        {
+            return Err(FSRSError::InvalidInput { field: "rating" });
+        }
+        let mut reviews = self.reviews;
+        reviews.sort_by_key(|&(timestamp, _, _)| timestamp);
+        let rollover = self.utc_offset as i64 - self.next_day_starts_at as i64 * 3600;
+        let day = |timestamp: i64| (timestamp + rollover).div_euclid(86400);
+        let mut previous_day = day(reviews[0].0);
+        Ok(FSRSItem {
+            reviews: reviews
+                .into_iter()
+                .map(|(timestamp, rating, duration_ms)| {
+                    let day = day(timestamp);
+                    let delta_t = (day - previous_day) as f32;
+                    previous_day = day;
+                    FSRSReview {
+                        rating,
+                        delta_t,
+                        duration_ms,
+                    }
+                })
+                .collect(),
+            group: self.group,
+        })
+    }
+
+    /// One item for each review after the first, holding the reviews up to it, as training
+    /// expects.
+    pub fn build(self) -> Result<Vec<FSRSItem>> {
+        let history = self.build_history()?;
+        Ok((2..=history.reviews.len())
+            .map(|len| FSRSItem {
+                reviews: history.reviews[..len].to_vec(),
+                group: history.group,
+            })
+            .collect())
+    }
+}
+
+/// Maps the grades used by an app onto FSRS ratings (1 Again, 2 Hard, 3 Good, 4 Easy), so apps
+/// with fewer buttons can record their own grades. Grades are indices into the map: with
+/// [RatingMap::pass_fail], grade 0 is a fail, recorded as Again, and grade 1 is a pass, recorded
+/// as Good.
+///
+/// Convert review histories with [RatingMap::remap] before training or scheduling, and pick the
+/// scheduled state for a grade with [crate::NextStates::for_grade].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RatingMap {
+    ratings: Vec<u32>,
+}
+
+impl RatingMap {
+    /// `ratings[grade]` is the rating each grade is recorded as. Fails if any is outside 1-4.
+    pub fn new(ratings: Vec<u32>) -> Result<Self> {
+        if ratings.iter().any(|rating| !(1..=4).contains(rating)) {
+            return Err(FSRSError::InvalidInput { field: "ratings" });
+        }
+        Ok(Self { ratings })
+    }
+
+    /// Fail (0) is recorded as Again, and pass (1) as Good.
+    pub fn pass_fail() -> Self {
+        Self {
+            ratings: vec![1, 3],
+        }
+    }
+
+    /// The rating a grade is recorded as, or None if the grade isn't mapped.
+    pub fn rating(&self, grade: u32) -> Option<u32> {
+        self.ratings.get(grade as usize).copied()
+    }
+
+    /// Convert items whose reviews hold grades into items holding ratings. Fails with
+    /// [FSRSError::InvalidItem] on the first item with an unmapped grade.
+    pub fn remap(&self, items: Vec<FSRSItem>) -> Result<Vec<FSRSItem>> {
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut item)| {
+                for review in &mut item.reviews {
+                    review.rating = self.rating(review.rating).ok_or(FSRSError::InvalidItem {
+                        index,
+                        reason: "grade not in rating map",
+                    })?;
+                }
+                Ok(item)
+            })
+            .collect()
+    }
+}
+
+/// An overview of a set of items, as returned by [summarize]. Each item is counted once, as the
+/// optimizer sees it, so a card with many reviews contributes many items.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatasetSummary {
+    pub items: usize,
+    /// Items by the rating of their first review, from Again to Easy. Invalid ratings are not
+    /// counted.
+    pub first_rating_counts: [usize; 4],
+    /// The number of items with each number of reviews.
+    pub review_counts: BTreeMap<usize, usize>,
+    /// The most days covered by a single item's reviews.
+    pub span_days: f32,
+    /// The fraction of items whose last review was rated Again, out of those with more than one
+    /// review.
+    pub lapse_rate: f32,
+    /// The mean `delta_t` of the last review, out of items with more than one review.
+    pub average_interval: f32,
+}
+
+/// Summarize the items the optimizer would be given, so unusual data can be noticed before
+/// training on it.
+pub fn summarize(items: &[FSRSItem]) -> DatasetSummary {
+    let mut summary = DatasetSummary {
+        items: items.len(),
+        ..Default::default()
+    };
+    let mut reviewed = 0;
+    let mut lapses = 0;
+    let mut total_interval = 0.0;
+    for item in items {
+        *summary.review_counts.entry(item.reviews.len()).or_default() += 1;
+        if let Some(count) = item.reviews.first().and_then(|r| {
+            summary
+                .first_rating_counts
+                .get_mut((r.rating as usize).wrapping_sub(1))
+        }) {
+            *count += 1;
+        }
+        let span = item.reviews.iter().map(|r| r.delta_t).sum();
+        summary.span_days = summary.span_days.max(span);
+        if let [_, .., last] = item.reviews.as_slice() {
+            reviewed += 1;
+            lapses += usize::from(last.rating == 1);
+            total_interval += last.delta_t as f64;
+        }
+    }
+    if reviewed > 0 {
+        summary.lapse_rate = lapses as f32 / reviewed as f32;
+        summary.average_interval = (total_interval / reviewed as f64) as f32;
+    }
+    summary
+}
+
+/// The version written by [to_jsonl]. It changes only if the meaning of a line changes, so
+/// files written by earlier versions of this crate can still be read.
+pub const JSONL_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct JsonlLine<'a> {
+    version: u32,
+    reviews: &'a [FSRSReview],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct JsonlLineOwned {
+    version: u32,
+    reviews: Vec<FSRSReview>,
+    #[serde(default)]
+    group: Option<u64>,
+}
+
+/// Encode items as JSON Lines, one item per line, such as
+/// `{"version":1,"reviews":[{"rating":3,"delta_t":0.0},{"rating":3,"delta_t":1.0}]}`, with a
+/// `group` field for items that have one.
+/// This is a stable format for attaching datasets to bug reports and for test fixtures.
+pub fn to_jsonl(items: &[FSRSItem]) -> String {
+    let mut jsonl = String::new();
+    for item in items {
+        let line = JsonlLine {
+            version: JSONL_VERSION,
+            reviews: &item.reviews,
+            group: item.group,
+        };
+        jsonl += &serde_json::to_string(&line).expect("items are always serializable");
+        jsonl.push('\n');
+    }
+    jsonl
+}
+
+/// Decode items written by [to_jsonl]. Blank lines are skipped.
+pub fn from_jsonl(jsonl: &str) -> Result<Vec<FSRSItem>> {
+    jsonl
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| {
+            let error = |message: String| FSRSError::Serialization {
+                message: format!("line {}: {message}", index + 1),
+            };
+            let line: JsonlLineOwned =
+                serde_json::from_str(line).map_err(|err| error(err.to_string()))?;
+            if line.version > JSONL_VERSION {
+                return Err(error(format!("unsupported version {}", line.version)));
+            }
+            Ok(FSRSItem {
+                reviews: line.reviews,
+                group: line.group,
+            })
+        })
+        .collect()
+}
+
+/// Split items by their [FSRSItem::group], keeping their order within each group. Items without
+/// a group are returned under None.
+pub fn group_items(
+    items: impl IntoIterator<Item = FSRSItem>,
+) -> BTreeMap<Option<u64>, Vec<FSRSItem>> {
+    let mut groups: BTreeMap<_, Vec<_>> = BTreeMap::new();
+    for item in items {
+        groups.entry(item.group).or_default().push(item);
+    }
+    groups
+}
+
+/// Remove repeated items for the same card, such as from importing a review log twice or merging
+/// collections, which would otherwise weigh those cards more heavily in training. Items are given
+/// with a key identifying their card, such as a card id, as different cards often have identical
+/// histories. The first of each set of identical items for a card is kept, and the order is
+/// preserved.
+pub fn dedup_items<K: Hash + Eq>(items: impl IntoIterator<Item = (K, FSRSItem)>) -> Vec<FSRSItem> {
+    let mut seen = HashSet::new();
+    items
+        .into_iter()
+        .filter_map(|(card, item)| {
+            let reviews: Vec<_> = item
+                .reviews
+                .iter()
+                .map(|r| (r.rating, r.delta_t.to_bits()))
+                .collect();
+            seen.insert((card, item.group, reviews)).then_some(item)
+        })
+        .collect()
+}
+
+/// Export items for contributing to research, as CSV in the format of the FSRS benchmark
+/// dataset: `card_id,review_th,delta_t,rating`, with a `delta_t` of -1 for each card's first
+/// review.
+///
+/// Only ratings and intervals are exported. The items are expected to hold an item for each
+/// review of each card after the first, as training does, and each card's history is written
+/// once. Cards are shuffled with `seed` and numbered from 0, so neither card ids nor the order
+/// of the input can be recovered; `review_th` counts reviews in the shuffled order.
+pub fn to_benchmark_csv(items: &[FSRSItem], seed: u64) -> String {
+    let key = |reviews: &[FSRSReview]| -> Vec<_> {
+        reviews
+            .iter()
+            .map(|r| (r.rating, r.delta_t.to_bits()))
+            .collect()
+    };
+    // A card contributes an item for its full history and for each shorter prefix. Every item
+    // but the full history is extended by exactly one item with another review, so counting
+    // each item and uncounting its prefix leaves only the full histories.
+    let mut histories: BTreeMap<_, (&[FSRSReview], isize)> = BTreeMap::new();
+    for item in items.iter().filter(|item| !item.reviews.is_empty()) {
+        let reviews = item.reviews.as_slice();
+        histories.entry(key(reviews)).or_insert((reviews, 0)).1 += 1;
+        let prefix = &reviews[..reviews.len() - 1];
+        histories.entry(key(prefix)).or_insert((prefix, 0)).1 -= 1;
+    }
+    let mut cards: Vec<_> = histories
+        .into_values()
+        .flat_map(|(reviews, count)| std::iter::repeat(reviews).take(count.max(0) as usize))
+        .collect();
+    cards.shuffle(&mut StdRng::seed_from_u64(seed));
+    let mut csv = String::from("card_id,review_th,delta_t,rating\n");
+    let mut review_th = 0;
+    for (card_id, reviews) in cards.into_iter().enumerate() {
+        for (index, review) in reviews.iter().enumerate() {
+            review_th += 1;
+            let delta_t = if index == 0 { -1.0 } else { review.delta_t };
+            writeln!(csv, "{card_id},{review_th},{delta_t},{}", review.rating).unwrap();
+        }
+    }
+    csv
+}
+
+#[cfg(feature = "training")]
 impl FSRSItem {
     // The previous reviews done before the current one.
     pub(crate) fn history(&self) -> impl Iterator<Item = &FSRSReview> {
@@ -34,18 +408,109 @@ impl FSRSItem {
     pub(crate) fn current(&self) -> &FSRSReview {
         self.reviews.last().unwrap()
     }
+
+    /// Fails with [FSRSError::InvalidItem] if the item has fewer than two reviews, so there is
+    /// nothing to predict the last review from, a rating outside 1-4, or a negative or non-finite
+    /// `delta_t`, which would put reviews out of chronological order.
+    pub(crate) fn check(&self, index: usize) -> Result<()> {
+        if self.reviews.len() == 1 {
+            return Err(FSRSError::InvalidItem {
+                index,
+                reason: "only one review",
+            });
+        }
+        self.check_reviews(index)
+    }
+
+    /// Like [FSRSItem::check], but allows a single review, for finding a card's memory state.
+    pub(crate) fn check_reviews(&self, index: usize) -> Result<()> {
+        let reason = if self.reviews.is_empty() {
+            "no reviews"
+        } else if self.reviews.iter().any(|r| !(1..=4).contains(&r.rating)) {
+            "rating outside 1-4"
+        } else if self
+            .reviews
+            .iter()
+            .any(|r| !(r.delta_t.is_finite() && r.delta_t >= 0.0))
+        {
+            "delta_t negative or not finite"
+        } else {
+            return Ok(());
+        };
+        Err(FSRSError::InvalidItem { index, reason })
+    }
+
+    /// Keep only the last `len` reviews, so the first kept review is treated as the card's first.
+    pub(crate) fn keep_last(&self, len: usize) -> Self {
+        let skip = self.reviews.len().saturating_sub(len);
+        Self {
+            reviews: self.reviews[skip..].to_vec(),
+            group: self.group,
+        }
+    }
+
+    /// Drop reviews made on the same day as the previous one, keeping the first review of each
+    /// day. Returns None if the last review is dropped, as the item then repeats a shorter one.
+    pub(crate) fn collapse_same_day(mut self) -> Option<Self> {
+        if self.reviews.len() > 1 && is_same_day(self.current().delta_t) {
+            return None;
+        }
+        let mut first = true;
+        self.reviews.retain(|review| {
+            let keep = first || !is_same_day(review.delta_t);
+            first = false;
+            keep
+        });
+        Some(self)
+    }
+}
+
+/// Whether a review `delta_t` days after the previous one is on the same day. Fractional
+/// intervals under a day count, so a review a few hours later is treated like one with a
+/// `delta_t` of 0.
+pub(crate) fn is_same_day(delta_t: f32) -> bool {
+    delta_t < 1.0
+}
+
+/// What training does with reviews made on the same day as the previous review of a card, such
+/// as learning steps.
+#[cfg(feature = "training")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SameDayReviews {
+    /// Keep them when the short-term weights are being trained, as those model same-day
+    /// reviews, and otherwise collapse them.
+    #[default]
+    Auto,
+    Keep,
+    /// Keep only the first review of each day, as the long-term model can't use the others.
+    Collapse,
+}
+
+/// Check each item with [FSRSItem::check], before they are batched.
+#[cfg(feature = "training")]
+pub(crate) fn check_items(items: &[FSRSItem]) -> Result<()> {
+    items
+        .iter()
+        .enumerate()
+        .try_for_each(|(index, item)| item.check(index))
 }
 
+/// Batches items for the model. Items must have passed [FSRSItem::check], which the public
+/// entry points ensure before any batching, so invalid input is reported to the caller rather
+/// than panicking in a data loader thread.
+#[cfg(feature = "training")]
 pub(crate) struct FSRSBatcher<B: Backend> {
     device: B::Device,
 }
 
+#[cfg(feature = "training")]
 impl<B: Backend> FSRSBatcher<B> {
     pub fn new(device: B::Device) -> Self {
         Self { device }
     }
 }
 
+#[cfg(feature = "training")]
 #[derive(Debug, Clone)]
 pub(crate) struct FSRSBatch<B: Backend> {
     pub t_historys: Tensor<B, 2, Float>,
@@ -54,21 +519,21 @@ pub(crate) struct FSRSBatch<B: Backend> {
     pub labels: Tensor<B, 1, Int>,
 }
 
+#[cfg(feature = "training")]
 impl<B: Backend> Batcher<FSRSItem, FSRSBatch<B>> for FSRSBatcher<B> {
     fn batch(&self, items: Vec<FSRSItem>) -> FSRSBatch<B> {
         let pad_size = items
             .iter()
             .map(|x| x.reviews.len())
             .max()
-            .expect("FSRSItem is empty")
-            - 1;
+            .map_or(0, |len| len.saturating_sub(1));
 
         let (time_histories, rating_histories) = items
             .iter()
             .map(|item| {
                 let (mut delta_t, mut rating): (Vec<_>, Vec<_>) =
                     item.history().map(|r| (r.delta_t, r.rating)).unzip();
-                delta_t.resize(pad_size, 0);
+                delta_t.resize(pad_size, 0.0);
                 rating.resize(pad_size, 0);
                 let delta_t =
                     Tensor::from_data(Data::new(delta_t, Shape { dims: [pad_size] }).convert())
@@ -115,10 +580,12 @@ impl<B: Backend> Batcher<FSRSItem, FSRSBatch<B>> for FSRSBatcher<B> {
     }
 }
 
+#[cfg(feature = "training")]
 pub(crate) struct FSRSDataset {
-    items: Vec<FSRSItem>,
+    items: Arc<[FSRSItem]>,
 }
 
+#[cfg(feature = "training")]
 impl Dataset<FSRSItem> for FSRSDataset {
     fn len(&self) -> usize {
         self.items.len()
@@ -129,27 +596,98 @@ impl Dataset<FSRSItem> for FSRSDataset {
     }
 }
 
+#[cfg(feature = "training")]
 impl From<Vec<FSRSItem>> for FSRSDataset {
     fn from(items: Vec<FSRSItem>) -> Self {
+        Self {
+            items: items.into(),
+        }
+    }
+}
+
+/// Shares the items with the caller, so they can be evaluated after training without a copy.
+#[cfg(feature = "training")]
+impl From<Arc<[FSRSItem]>> for FSRSDataset {
+    fn from(items: Arc<[FSRSItem]>) -> Self {
         Self { items }
     }
 }
 
-pub fn filter_outlier(items: Vec<FSRSItem>) -> Vec<FSRSItem> {
+/// Controls how items with two reviews are filtered before pretraining. Items are grouped by their
+/// first rating, and then by the interval before their second review; the smallest interval
+/// groups are removed as outliers.
+#[cfg(feature = "training")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterConfig {
+    pub enabled: bool,
+    /// The most items that may be removed from each first rating, as a fraction of its items.
+    pub removal_fraction: f32,
+    /// First ratings with fewer items than this are kept whole, as there is too little data to
+    /// tell what is unusual.
+    pub min_group_size: usize,
+}
+
+#[cfg(feature = "training")]
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            removal_fraction: 0.05,
+            min_group_size: 0,
+        }
+    }
+}
+
+/// The result of [filter_outlier].
+#[cfg(feature = "training")]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FilterResult {
+    pub kept: Vec<FSRSItem>,
+    /// The groups that were removed as outliers.
+    pub removed: Vec<OutlierGroup>,
+}
+
+/// Items removed by [filter_outlier], which all share a first rating and the interval before
+/// their second review.
+#[cfg(feature = "training")]
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutlierGroup {
+    pub first_rating: u32,
+    pub delta_t: f32,
+    pub items: Vec<FSRSItem>,
+}
+
+#[cfg(feature = "training")]
+impl FilterResult {
+    /// The total number of items removed.
+    pub fn removed_count(&self) -> usize {
+        self.removed.iter().map(|group| group.items.len()).sum()
+    }
+}
+
+/// Remove outliers from the items with exactly two reviews, as is done before pretraining. Other
+/// items are always kept.
+#[cfg(feature = "training")]
+pub fn filter_outlier(items: Vec<FSRSItem>, config: &FilterConfig) -> FilterResult {
+    let (items, mut filtered_items): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .partition(|item| config.enabled && item.reviews.len() == 2);
+    let mut removed = vec![];
     let mut groups = HashMap::<u32, HashMap<u32, Vec<FSRSItem>>>::new();
 
     // 首先按照第一个 review 的 rating 和第二个 review 的 delta 进行分组
-    for item in items.iter() {
-        let (first_review, second_review) = (item.reviews.first().unwrap(), item.current());
+    for item in items.into_iter() {
+        let (first_review, second_review) = (&item.reviews[0], item.current());
         let rating_group = groups.entry(first_review.rating).or_default();
-        let delta_t_group = rating_group.entry(second_review.delta_t).or_default();
-        delta_t_group.push(item.clone());
+        // grouped by the bits of delta_t, which order the same as the values, as they're positive
+        let delta_t_group = rating_group
+            .entry(second_review.delta_t.to_bits())
+            .or_default();
+        delta_t_group.push(item);
     }
 
-    let mut filtered_items = vec![];
-
     // 对每个按 rating 分组的子组进一步处理
-    for (_rating, delta_t_groups) in groups.iter() {
+    for (&rating, delta_t_groups) in groups.iter() {
         let mut sub_groups = delta_t_groups.iter().collect::<Vec<_>>();
 
         // 按子组大小升序排序，大小相同的按 delta_t 降序排序
@@ -162,25 +700,72 @@ pub fn filter_outlier(items: Vec<FSRSItem>) -> Vec<FSRSItem> {
 
         // 计算总大小
         let total = sub_groups.iter().map(|(_, vec)| vec.len()).sum::<usize>();
+        let budget = if total < config.min_group_size {
+            0
+        } else {
+            (total as f64 * config.removal_fraction as f64) as usize
+        };
         let mut has_been_removed = 0;
 
-        for (_delta_t, sub_group) in sub_groups.iter().rev() {
-            if has_been_removed + sub_group.len() > total / 20 {
+        for (&delta_t, sub_group) in sub_groups.iter().rev() {
+            if has_been_removed + sub_group.len() > budget {
                 filtered_items.extend_from_slice(sub_group);
             } else {
                 has_been_removed += sub_group.len();
+                removed.push(OutlierGroup {
+                    first_rating: rating,
+                    delta_t: f32::from_bits(delta_t),
+                    items: sub_group.to_vec(),
+                });
             }
         }
     }
-    filtered_items
+    FilterResult {
+        kept: filtered_items,
+        removed,
+    }
 }
 
-pub fn split_data(items: Vec<FSRSItem>) -> (Vec<FSRSItem>, Vec<FSRSItem>) {
+#[cfg(feature = "training")]
+pub fn split_data(items: impl IntoIterator<Item = FSRSItem>) -> (Vec<FSRSItem>, Vec<FSRSItem>) {
     let (pretrainset, trainset) = items.into_iter().partition(|item| item.reviews.len() == 2);
-    (filter_outlier(pretrainset), trainset)
+    (
+        filter_outlier(pretrainset, &FilterConfig::default()).kept,
+        trainset,
+    )
+}
+
+/// Like [split_data], but fails on the first item that can't be trained on. Items are checked
+/// and split as they are consumed, without first being gathered into a Vec.
+#[cfg(feature = "training")]
+pub(crate) fn split_checked_data(
+    items: impl IntoIterator<Item = FSRSItem>,
+    filter: &FilterConfig,
+    collapse_same_day: bool,
+) -> Result<(Vec<FSRSItem>, Vec<FSRSItem>)> {
+    let mut pretrainset = vec![];
+    let mut trainset = vec![];
+    for (index, item) in items.into_iter().enumerate() {
+        item.check(index)?;
+        let item = if collapse_same_day {
+            let Some(item) = item.collapse_same_day() else {
+                continue;
+            };
+            item
+        } else {
+            item
+        };
+        // initial stability is fitted to second reviews on a later day
+        if item.reviews.len() == 2 && !is_same_day(item.current().delta_t) {
+            pretrainset.push(item);
+        } else {
+            trainset.push(item);
+        }
+    }
+    Ok((filter_outlier(pretrainset, filter).kept, trainset))
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "training"))]
 mod tests {
     use super::*;
     use crate::convertor_tests::anki21_sample_file_converted_to_fsrs;
@@ -196,13 +781,16 @@ mod tests {
                 reviews: vec![
                     FSRSReview {
                         rating: 1,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 4,
-                        delta_t: 2,
+                        delta_t: 2.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             }
         );
 
@@ -238,129 +826,161 @@ mod tests {
                 reviews: vec![
                     FSRSReview {
                         rating: 4,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5,
+                        delta_t: 5.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 4,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 5,
+                        delta_t: 5.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 11,
+                        delta_t: 11.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 4,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 2,
+                        delta_t: 2.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 4,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 2,
+                        delta_t: 2.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 6,
+                        delta_t: 6.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 4,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 2,
+                        delta_t: 2.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 6,
+                        delta_t: 6.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 16,
+                        delta_t: 16.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 4,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 2,
+                        delta_t: 2.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 6,
+                        delta_t: 6.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 16,
+                        delta_t: 16.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 39,
+                        delta_t: 39.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 1,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 1,
-                        delta_t: 1,
+                        delta_t: 1.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
             FSRSItem {
                 reviews: vec![
                     FSRSReview {
                         rating: 1,
-                        delta_t: 0,
+                        delta_t: 0.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 1,
-                        delta_t: 1,
+                        delta_t: 1.0,
+                        duration_ms: None,
                     },
                     FSRSReview {
                         rating: 3,
-                        delta_t: 1,
+                        delta_t: 1.0,
+                        duration_ms: None,
                     },
                 ],
+                group: None,
             },
         ];
         let batch = batcher.batch(items);
@@ -388,4 +1008,354 @@ mod tests {
         );
         assert_eq!(batch.labels.to_data(), Data::from([1, 1, 1, 1, 1, 1, 0, 1]));
     }
+
+    #[test]
+    fn split_data_from_iterator() {
+        let items = anki21_sample_file_converted_to_fsrs();
+        let (pretrainset, trainset) = split_data(items.clone());
+        let (pretrainset_iter, trainset_iter) = split_data(items.iter().cloned());
+        // filter_outlier doesn't preserve ordering
+        assert_eq!(pretrainset.len(), pretrainset_iter.len());
+        assert_eq!(trainset, trainset_iter);
+        assert!(trainset.iter().all(|item| item.reviews.len() > 2));
+    }
+
+    #[test]
+    fn invalid_items_are_reported() {
+        let review = |rating| FSRSReview {
+            rating,
+            delta_t: 1.0,
+            duration_ms: None,
+        };
+        let items = vec![
+            FSRSItem {
+                reviews: vec![review(3), review(3)],
+                group: None,
+            },
+            FSRSItem {
+                reviews: vec![review(3), review(5)],
+                group: None,
+            },
+            FSRSItem {
+                reviews: vec![],
+                group: None,
+            },
+        ];
+        assert!(matches!(
+            split_checked_data(items.clone(), &FilterConfig::default(), true),
+            Err(FSRSError::InvalidItem {
+                index: 1,
+                reason: "rating outside 1-4"
+            })
+        ));
+        assert!(matches!(
+            check_items(&items[2..]),
+            Err(FSRSError::InvalidItem { index: 0, .. })
+        ));
+        assert!(matches!(
+            FSRSItem {
+                reviews: vec![review(3)],
+                group: None
+            }
+            .check(0),
+            Err(FSRSError::InvalidItem {
+                reason: "only one review",
+                ..
+            })
+        ));
+        assert_eq!(
+            split_checked_data(items[..1].to_vec(), &FilterConfig::default(), true)
+                .unwrap()
+                .0
+                .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn summarize_items() {
+        let item = |reviews: &[(u32, f32)]| FSRSItem {
+            reviews: reviews
+                .iter()
+                .map(|&(rating, delta_t)| FSRSReview {
+                    rating,
+                    delta_t,
+                    duration_ms: None,
+                })
+                .collect(),
+            group: None,
+        };
+        let summary = summarize(&[
+            item(&[(3, 0.0), (1, 4.0)]),
+            item(&[(3, 0.0), (1, 4.0), (3, 1.0)]),
+            item(&[(1, 0.0), (3, 2.0)]),
+            item(&[(4, 0.0)]),
+        ]);
+        assert_eq!(summary.items, 4);
+        assert_eq!(summary.first_rating_counts, [1, 0, 2, 1]);
+        assert_eq!(
+            summary.review_counts.into_iter().collect::<Vec<_>>(),
+            [(1, 1), (2, 2), (3, 1)]
+        );
+        assert_eq!(summary.span_days, 5.0);
+        assert_eq!(summary.lapse_rate, 1.0 / 3.0);
+        assert_eq!(summary.average_interval, 7.0 / 3.0);
+        assert_eq!(summarize(&[]), DatasetSummary::default());
+    }
+
+    #[test]
+    fn filter_config() {
+        let items = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .filter(|item| item.reviews.len() == 2)
+            .collect::<Vec<_>>();
+        let filtered = |config| filter_outlier(items.clone(), &config).kept.len();
+        let default = filtered(FilterConfig::default());
+        assert!(default < items.len());
+        let result = filter_outlier(items.clone(), &FilterConfig::default());
+        assert_eq!(result.removed_count() + default, items.len());
+        for group in &result.removed {
+            assert!(group.items.iter().all(|item| {
+                item.reviews[0].rating == group.first_rating
+                    && item.current().delta_t == group.delta_t
+            }));
+        }
+        assert_eq!(
+            filtered(FilterConfig {
+                enabled: false,
+                ..Default::default()
+            }),
+            items.len()
+        );
+        assert_eq!(
+            filtered(FilterConfig {
+                min_group_size: usize::MAX,
+                ..Default::default()
+            }),
+            items.len()
+        );
+        assert!(
+            filtered(FilterConfig {
+                removal_fraction: 0.2,
+                ..Default::default()
+            }) < default
+        );
+    }
+
+    #[test]
+    fn same_day_reviews() {
+        let item = |delta_ts: &[f32]| FSRSItem {
+            reviews: delta_ts
+                .iter()
+                .map(|&delta_t| FSRSReview {
+                    rating: 3,
+                    delta_t,
+                    duration_ms: None,
+                })
+                .collect(),
+            group: None,
+        };
+        assert_eq!(
+            item(&[0.0, 0.0, 1.0, 0.0, 3.0]).collapse_same_day(),
+            Some(item(&[0.0, 1.0, 3.0]))
+        );
+        assert_eq!(item(&[0.0, 1.0, 0.0]).collapse_same_day(), None);
+        // a review a few hours later is on the same day too
+        assert!(is_same_day(0.3));
+        assert!(!is_same_day(1.0));
+        assert_eq!(
+            item(&[0.0, 0.3, 1.5]).collapse_same_day(),
+            Some(item(&[0.0, 1.5]))
+        );
+        let (pretrainset, trainset) =
+            split_checked_data([item(&[0.0, 0.3])], &FilterConfig::default(), false).unwrap();
+        assert!(pretrainset.is_empty());
+        assert_eq!(trainset, [item(&[0.0, 0.3])]);
+
+        let items = vec![item(&[0.0, 0.0]), item(&[0.0, 0.0, 2.0]), item(&[0.0, 2.0])];
+        let (pretrainset, trainset) =
+            split_checked_data(items.clone(), &FilterConfig::default(), false).unwrap();
+        assert_eq!(pretrainset, [item(&[0.0, 2.0])]);
+        assert_eq!(trainset, [item(&[0.0, 0.0]), item(&[0.0, 0.0, 2.0])]);
+        let (pretrainset, trainset) =
+            split_checked_data(items, &FilterConfig::default(), true).unwrap();
+        assert_eq!(pretrainset.len(), 2);
+        assert!(trainset.is_empty());
+    }
+
+    #[test]
+    fn rating_map() -> Result<()> {
+        let map = RatingMap::pass_fail();
+        let items = vec![FSRSItem {
+            reviews: vec![
+                FSRSReview {
+                    rating: 1,
+                    delta_t: 0.0,
+                    duration_ms: None,
+                },
+                FSRSReview {
+                    rating: 0,
+                    delta_t: 1.0,
+                    duration_ms: None,
+                },
+            ],
+            group: None,
+        }];
+        let remapped = map.remap(items.clone())?;
+        assert_eq!(
+            remapped[0]
+                .reviews
+                .iter()
+                .map(|r| r.rating)
+                .collect::<Vec<_>>(),
+            [3, 1]
+        );
+        assert!(RatingMap::new(vec![1, 5]).is_err());
+        assert!(matches!(
+            RatingMap::new(vec![1])?.remap(items),
+            Err(FSRSError::InvalidItem { index: 0, .. })
+        ));
+
+        let next_states = crate::FSRS::new(Some(&[]))?.next_states(None, 0.9, 0.0, 36500);
+        assert_eq!(next_states.for_grade(&map, 0), Some(&next_states.again));
+        assert_eq!(next_states.for_grade(&map, 1), Some(&next_states.good));
+        assert_eq!(next_states.for_grade(&map, 2), None);
+        Ok(())
+    }
+
+    #[test]
+    fn item_builder() -> Result<()> {
+        const DAY: i64 = 86400;
+        let start = 1_700_000_000 - 1_700_000_000 % DAY;
+        let builder = FSRSItem::builder()
+            .review(start + 5 * DAY + 10 * 3600, 3)
+            .timed_review(start + 10 * 3600, 1, 8000)
+            // 2am the next day, which counts as the same day with a 4am rollover
+            .review(start + DAY + 2 * 3600, 3)
+            .next_day_starts_at(4);
+        let history = builder.clone().build_history()?;
+        assert_eq!(
+            history
+                .reviews
+                .iter()
+                .map(|r| (r.rating, r.delta_t))
+                .collect::<Vec<_>>(),
+            [(1, 0.0), (3, 0.0), (3, 5.0)]
+        );
+        assert_eq!(history.reviews[0].duration_ms, Some(8000));
+        assert_eq!(history.reviews[1].duration_ms, None);
+        assert_eq!(builder.clone().build()?.len(), 2);
+        // in UTC-3, the 2am review falls on the evening of the first day
+        let shifted = builder.clone().next_day_starts_at(0).utc_offset(-3 * 3600);
+        assert_eq!(shifted.build_history()?.reviews[1].delta_t, 0.0);
+        assert_eq!(
+            builder
+                .clone()
+                .next_day_starts_at(0)
+                .build_history()?
+                .reviews[1]
+                .delta_t,
+            1.0
+        );
+        assert!(builder.clone().review(start, 5).build().is_err());
+        assert!(builder.next_day_starts_at(24).build().is_err());
+        assert!(FSRSItem::builder().build_history().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn jsonl() -> Result<()> {
+        let items = anki21_sample_file_converted_to_fsrs();
+        let jsonl = to_jsonl(&items[..100]);
+        assert_eq!(jsonl.lines().count(), 100);
+        assert_eq!(from_jsonl(&jsonl)?, items[..100]);
+        let mut timed = items[0].clone();
+        timed.reviews[0].duration_ms = Some(5000);
+        assert_eq!(from_jsonl(&to_jsonl(&[timed.clone()]))?, [timed]);
+        assert_eq!(
+            from_jsonl("{\"version\":1,\"reviews\":[{\"rating\":3,\"delta_t\":0.0}]}\n\n")?,
+            [FSRSItem {
+                reviews: vec![FSRSReview {
+                    rating: 3,
+                    delta_t: 0.0,
+                    duration_ms: None
+                }],
+                group: None
+            }]
+        );
+        assert!(matches!(
+            from_jsonl("{\"version\":2,\"reviews\":[]}"),
+            Err(FSRSError::Serialization { .. })
+        ));
+        assert!(matches!(
+            from_jsonl("{\"reviews\":[]}"),
+            Err(FSRSError::Serialization { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn grouped_items() -> Result<()> {
+        let items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| FSRSItem {
+                group: (index % 3 != 0).then_some(index as u64 % 3),
+                ..item
+            })
+            .collect();
+        let groups = group_items(items.clone());
+        assert_eq!(
+            groups.keys().collect::<Vec<_>>(),
+            [&None, &Some(1), &Some(2)]
+        );
+        assert_eq!(groups.values().map(Vec::len).sum::<usize>(), items.len());
+        assert_eq!(groups[&Some(1)][0], items[1]);
+        assert_eq!(from_jsonl(&to_jsonl(&items))?, items);
+        assert!(!to_jsonl(&items[..1]).contains("group"));
+        Ok(())
+    }
+
+    #[test]
+    fn dedup() {
+        let items = anki21_sample_file_converted_to_fsrs();
+        // each item is treated as a separate card
+        let keyed = |first_card| {
+            items[..50]
+                .iter()
+                .enumerate()
+                .map(move |(index, item)| (first_card + index, item.clone()))
+        };
+        // the same log imported twice, plus other cards with the same histories
+        let deduped = dedup_items(keyed(0).chain(keyed(0)).chain(keyed(1000)));
+        assert_eq!(deduped.len(), 100);
+        assert_eq!(deduped[..50], items[..50]);
+        assert_eq!(dedup_items(keyed(0)), items[..50]);
+    }
+
+    #[test]
+    fn benchmark_csv() {
+        let items = anki21_sample_file_converted_to_fsrs();
+        let csv = to_benchmark_csv(&items, 42);
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some("card_id,review_th,delta_t,rating"));
+        let rows: Vec<Vec<f32>> = lines
+            .map(|line| line.split(',').map(|v| v.parse().unwrap()).collect())
+            .collect();
+        // each card's history is written once, and each card has one item with two reviews
+        let cards = rows.iter().filter(|row| row[2] == -1.0).count();
+        assert_eq!(
+            cards,
+            items.iter().filter(|item| item.reviews.len() == 2).count()
+        );
+        assert_eq!(
+            rows.len(),
+            items.iter().filter(|item| item.reviews.len() == 2).count() + items.len()
+        );
+        assert!(rows
+            .windows(2)
+            .all(|w| w[1][1] == w[0][1] + 1.0 && w[1][0] >= w[0][0]));
+        assert_eq!(to_benchmark_csv(&items, 42), csv);
+        assert_ne!(to_benchmark_csv(&items, 0), csv);
+    }
 }
@@ -0,0 +1,128 @@
+use crate::dataset::{FSRSItem, FSRSReview};
+use crate::error::{IoSnafu, Result};
+use burn::data::dataset::Dataset;
+use snafu::ResultExt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+/// Bytes used by each review: the rating as a u32, then delta_t as an f32.
+const REVIEW_SIZE: usize = 8;
+
+/// A dataset kept in a temporary file rather than in memory, for training on collections too
+/// large to hold as [FSRSItem]s. Only an offset for each item is kept in memory, and items are
+/// read back as the data loader requests them. The file is deleted when the dataset is dropped.
+/// Items' groups and review durations are not stored, as training doesn't use them.
+pub(crate) struct DiskDataset {
+    file: Mutex<File>,
+    path: PathBuf,
+    /// The start of each item in the file, followed by the end of the last.
+    offsets: Vec<u64>,
+}
+
+impl DiskDataset {
+    pub fn new(items: impl IntoIterator<Item = FSRSItem>) -> Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "fsrs-dataset-{}-{}",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .context(IoSnafu)?;
+        // from here on, dropping the dataset removes the file, even if writing fails
+        let mut dataset = Self {
+            file: Mutex::new(file),
+            path,
+            offsets: vec![0],
+        };
+        dataset.write(items).context(IoSnafu)?;
+        Ok(dataset)
+    }
+
+    fn write(&mut self, items: impl IntoIterator<Item = FSRSItem>) -> std::io::Result<()> {
+        let file = self.file.get_mut().expect("dataset file lock poisoned");
+        let mut writer = BufWriter::new(&*file);
+        let mut offset = 0;
+        for item in items {
+            for review in &item.reviews {
+                writer.write_all(&review.rating.to_le_bytes())?;
+                writer.write_all(&review.delta_t.to_le_bytes())?;
+            }
+            offset += (item.reviews.len() * REVIEW_SIZE) as u64;
+            self.offsets.push(offset);
+        }
+        writer.flush()
+    }
+}
+
+impl Dataset<FSRSItem> for DiskDataset {
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn get(&self, index: usize) -> Option<FSRSItem> {
+        let start = *self.offsets.get(index)?;
+        let end = *self.offsets.get(index + 1)?;
+        let mut bytes = vec![0; (end - start) as usize];
+        let mut file = self.file.lock().expect("dataset file lock poisoned");
+        file.seek(SeekFrom::Start(start)).ok()?;
+        file.read_exact(&mut bytes).ok()?;
+        drop(file);
+        let reviews = bytes
+            .chunks_exact(REVIEW_SIZE)
+            .map(|review| {
+                let (rating, delta_t) = review.split_at(4);
+                FSRSReview {
+                    rating: u32::from_le_bytes(rating.try_into().unwrap()),
+                    delta_t: f32::from_le_bytes(delta_t.try_into().unwrap()),
+                    duration_ms: None,
+                }
+            })
+            .collect();
+        Some(FSRSItem {
+            reviews,
+            group: None,
+        })
+    }
+}
+
+impl Drop for DiskDataset {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convertor_tests::anki21_sample_file_converted_to_fsrs;
+    use crate::dataset::FSRSDataset;
+
+    #[test]
+    fn matches_in_memory_dataset() -> Result<()> {
+        let items = anki21_sample_file_converted_to_fsrs();
+        let dataset = DiskDataset::new(items.clone())?;
+        let path = dataset.path.clone();
+        assert!(path.exists());
+        let in_memory = FSRSDataset::from(items);
+        assert_eq!(dataset.len(), in_memory.len());
+        for index in [0, 1, 704, dataset.len() - 1] {
+            assert_eq!(dataset.get(index), in_memory.get(index));
+        }
+        assert_eq!(dataset.get(dataset.len()), None);
+        drop(dataset);
+        assert!(!path.exists());
+
+        let empty = DiskDataset::new(vec![])?;
+        assert_eq!(empty.len(), 0);
+        assert_eq!(empty.get(0), None);
+        Ok(())
+    }
+}
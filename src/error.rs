@@ -1,10 +1,32 @@
 use snafu::Snafu;
 
 #[derive(Snafu, Debug)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
 pub enum FSRSError {
     NotEnoughData,
     Interrupted,
     InvalidWeights,
+    /// A setting or argument was outside its valid range.
+    #[snafu(display("invalid value for {field}"))]
+    InvalidInput {
+        field: &'static str,
+    },
+    /// The item at `index` can't be used, for example because it has no reviews.
+    #[snafu(display("item {index} is invalid: {reason}"))]
+    InvalidItem {
+        index: usize,
+        reason: &'static str,
+    },
+    /// A temporary file used during training could not be written or read.
+    #[snafu(display("I/O error: {source}"), visibility(pub(crate)))]
+    Io {
+        source: std::io::Error,
+    },
+    /// Items could not be encoded, or a file of items could not be decoded.
+    #[snafu(display("serialization error: {message}"))]
+    Serialization {
+        message: String,
+    },
 }
 
 pub type Result<T, E = FSRSError> = std::result::Result<T, E>;
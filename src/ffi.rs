@@ -0,0 +1,328 @@
+//! A C interface to [FSRS]. Functions return an [FSRSStatus], and write their results through
+//! out-pointers supplied by the caller. An [FSRS] created with [fsrs_new] must be released with
+//! [fsrs_free].
+
+use crate::{FSRSError, FSRSItem, FSRSReview, ItemState, MemoryState, NextStates, FSRS};
+use std::slice;
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FSRSStatus {
+    Ok = 0,
+    NotEnoughData = 1,
+    Interrupted = 2,
+    InvalidWeights = 3,
+    /// A required pointer was null, or an output buffer was too small.
+    InvalidArgument = 4,
+    /// A value was outside its valid range.
+    InvalidInput = 5,
+    /// An item had no reviews, or an invalid rating.
+    InvalidItem = 6,
+    /// A temporary file could not be written or read.
+    Io = 7,
+    /// Items could not be encoded or decoded.
+    Serialization = 8,
+}
+
+impl From<FSRSError> for FSRSStatus {
+    fn from(err: FSRSError) -> Self {
+        match err {
+            FSRSError::NotEnoughData => FSRSStatus::NotEnoughData,
+            FSRSError::Interrupted => FSRSStatus::Interrupted,
+            FSRSError::InvalidWeights => FSRSStatus::InvalidWeights,
+            FSRSError::InvalidInput { .. } => FSRSStatus::InvalidInput,
+            FSRSError::InvalidItem { .. } => FSRSStatus::InvalidItem,
+            FSRSError::Io { .. } => FSRSStatus::Io,
+            FSRSError::Serialization { .. } => FSRSStatus::Serialization,
+        }
+    }
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FFIReview {
+    pub rating: u32,
+    pub delta_t: f32,
+}
+
+/// A card's review history, equivalent to [FSRSItem].
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct FFIItem {
+    pub reviews: *const FFIReview,
+    pub len: usize,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FFIMemoryState {
+    pub stability: f32,
+    pub difficulty: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FFIItemState {
+    pub memory: FFIMemoryState,
+    pub interval: u32,
+    pub retrievability: f32,
+    pub next_day_retrievability: f32,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FFINextStates {
+    pub again: FFIItemState,
+    pub hard: FFIItemState,
+    pub good: FFIItemState,
+    pub easy: FFIItemState,
+}
+
+impl From<MemoryState> for FFIMemoryState {
+    fn from(state: MemoryState) -> Self {
+        Self {
+            stability: state.stability,
+            difficulty: state.difficulty,
+        }
+    }
+}
+
+impl From<FFIMemoryState> for MemoryState {
+    fn from(state: FFIMemoryState) -> Self {
+        Self {
+            stability: state.stability,
+            difficulty: state.difficulty,
+        }
+    }
+}
+
+impl From<ItemState> for FFIItemState {
+    fn from(state: ItemState) -> Self {
+        Self {
+            memory: state.memory.into(),
+            interval: state.interval,
+            retrievability: state.retrievability,
+            next_day_retrievability: state.next_day_retrievability,
+        }
+    }
+}
+
+impl From<NextStates> for FFINextStates {
+    fn from(states: NextStates) -> Self {
+        Self {
+            again: states.again.into(),
+            hard: states.hard.into(),
+            good: states.good.into(),
+            easy: states.easy.into(),
+        }
+    }
+}
+
+/// # Safety
+/// `reviews` must point to `len` valid reviews, or may be null if `len` is 0.
+unsafe fn item_from_ffi(reviews: *const FFIReview, len: usize) -> FSRSItem {
+    let reviews: &[FFIReview] = if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(reviews, len)
+    };
+    FSRSItem {
+        reviews: reviews
+            .iter()
+            .map(|r| FSRSReview {
+                rating: r.rating,
+                delta_t: r.delta_t,
+                duration_ms: None,
+            })
+            .collect(),
+        group: None,
+    }
+}
+
+/// Create a new [FSRS]. `weights` may be null or empty to use the default weights.
+///
+/// # Safety
+/// `weights` must be null or point to `len` floats, and `out` must be a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn fsrs_new(
+    weights: *const f32,
+    len: usize,
+    out: *mut *mut FSRS,
+) -> FSRSStatus {
+    if out.is_null() {
+        return FSRSStatus::InvalidArgument;
+    }
+    let weights: &[f32] = if weights.is_null() {
+        &[]
+    } else {
+        slice::from_raw_parts(weights, len)
+    };
+    match FSRS::new(Some(weights)) {
+        Ok(fsrs) => {
+            *out = Box::into_raw(Box::new(fsrs));
+            FSRSStatus::Ok
+        }
+        Err(err) => err.into(),
+    }
+}
+
+/// Release an [FSRS] created by [fsrs_new].
+///
+/// # Safety
+/// `fsrs` must be null or a pointer returned by [fsrs_new] that has not been freed.
+#[no_mangle]
+pub unsafe extern "C" fn fsrs_free(fsrs: *mut FSRS) {
+    if !fsrs.is_null() {
+        drop(Box::from_raw(fsrs));
+    }
+}
+
+/// Calculate the memory state for a card's review history.
+///
+/// # Safety
+/// `fsrs` and `out` must be valid pointers, and `reviews` must point to `len` reviews.
+#[no_mangle]
+pub unsafe extern "C" fn fsrs_memory_state(
+    fsrs: *const FSRS,
+    reviews: *const FFIReview,
+    len: usize,
+    out: *mut FFIMemoryState,
+) -> FSRSStatus {
+    if fsrs.is_null() || reviews.is_null() || len == 0 || out.is_null() {
+        return FSRSStatus::InvalidArgument;
+    }
+    *out = (*fsrs).memory_state(item_from_ffi(reviews, len)).into();
+    FSRSStatus::Ok
+}
+
+/// Calculate the next states for each rating. `current` may be null for a new card.
+///
+/// # Safety
+/// `fsrs` and `out` must be valid pointers, and `current` must be null or valid.
+#[no_mangle]
+pub unsafe extern "C" fn fsrs_next_states(
+    fsrs: *const FSRS,
+    current: *const FFIMemoryState,
+    desired_retention: f32,
+    days_elapsed: f32,
+    maximum_interval: u32,
+    out: *mut FFINextStates,
+) -> FSRSStatus {
+    if fsrs.is_null() || out.is_null() {
+        return FSRSStatus::InvalidArgument;
+    }
+    let current = current.as_ref().map(|state| MemoryState::from(*state));
+    *out = (*fsrs)
+        .next_states(current, desired_retention, days_elapsed, maximum_interval)
+        .into();
+    FSRSStatus::Ok
+}
+
+/// Train weights on the given items. `out_weights` must have room for `capacity` floats; the
+/// number of weights written is stored in `out_len`.
+///
+/// # Safety
+/// `fsrs`, `out_weights` and `out_len` must be valid pointers, `items` must point to `len`
+/// items, and each item's reviews must be valid.
+#[no_mangle]
+pub unsafe extern "C" fn fsrs_compute_weights(
+    fsrs: *const FSRS,
+    items: *const FFIItem,
+    len: usize,
+    out_weights: *mut f32,
+    capacity: usize,
+    out_len: *mut usize,
+) -> FSRSStatus {
+    if fsrs.is_null() || (items.is_null() && len > 0) || out_weights.is_null() || out_len.is_null()
+    {
+        return FSRSStatus::InvalidArgument;
+    }
+    let items: &[FFIItem] = if len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(items, len)
+    };
+    let items: Vec<FSRSItem> = items
+        .iter()
+        .map(|item| item_from_ffi(item.reviews, item.len))
+        .collect();
+    match (*fsrs).compute_weights(items, None) {
+        Ok(weights) if weights.len() <= capacity => {
+            slice::from_raw_parts_mut(out_weights, weights.len()).copy_from_slice(&weights);
+            *out_len = weights.len();
+            FSRSStatus::Ok
+        }
+        Ok(_) => FSRSStatus::InvalidArgument,
+        Err(err) => err.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ptr;
+
+    #[test]
+    fn schedule_through_ffi() {
+        unsafe {
+            let mut fsrs = ptr::null_mut();
+            assert_eq!(fsrs_new(ptr::null(), 0, &mut fsrs), FSRSStatus::Ok);
+            let bad_weights = [1.0];
+            let mut unused = ptr::null_mut();
+            assert_eq!(
+                fsrs_new(bad_weights.as_ptr(), 1, &mut unused),
+                FSRSStatus::InvalidWeights
+            );
+
+            let reviews = [
+                FFIReview {
+                    rating: 3,
+                    delta_t: 0.0,
+                },
+                FFIReview {
+                    rating: 3,
+                    delta_t: 3.0,
+                },
+            ];
+            let mut state = FFIMemoryState {
+                stability: 0.0,
+                difficulty: 0.0,
+            };
+            assert_eq!(
+                fsrs_memory_state(fsrs, reviews.as_ptr(), reviews.len(), &mut state),
+                FSRSStatus::Ok
+            );
+            let expected = (*fsrs).memory_state(item_from_ffi(reviews.as_ptr(), reviews.len()));
+            assert_eq!(state, FFIMemoryState::from(expected));
+
+            let mut next_states = FFINextStates::from((*fsrs).next_states(None, 0.9, 0.0, 36500));
+            assert_eq!(
+                fsrs_next_states(fsrs, &state, 0.9, 5.0, 36500, &mut next_states),
+                FSRSStatus::Ok
+            );
+            assert_eq!(
+                next_states,
+                FFINextStates::from((*fsrs).next_states(Some(state.into()), 0.9, 5.0, 36500))
+            );
+            assert_eq!(
+                fsrs_memory_state(fsrs, ptr::null(), 0, &mut state),
+                FSRSStatus::InvalidArgument
+            );
+
+            let mut weights = [0.0; 19];
+            let mut weights_len = 0;
+            assert_eq!(
+                fsrs_compute_weights(
+                    fsrs,
+                    ptr::null(),
+                    0,
+                    weights.as_mut_ptr(),
+                    weights.len(),
+                    &mut weights_len
+                ),
+                FSRSStatus::NotEnoughData
+            );
+            fsrs_free(fsrs);
+        }
+    }
+}
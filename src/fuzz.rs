@@ -0,0 +1,179 @@
+use crate::{ForgettingCurve, ItemState, NextStates};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+
+/// Intervals falling between `start` and `end` days contribute `factor` days of fuzz per day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FuzzRange {
+    pub start: f32,
+    pub end: f32,
+    pub factor: f32,
+}
+
+/// The fuzz ranges Anki uses.
+pub static DEFAULT_FUZZ_RANGES: &[FuzzRange] = &[
+    FuzzRange {
+        start: 2.5,
+        end: 7.0,
+        factor: 0.15,
+    },
+    FuzzRange {
+        start: 7.0,
+        end: 20.0,
+        factor: 0.1,
+    },
+    FuzzRange {
+        start: 20.0,
+        end: f32::INFINITY,
+        factor: 0.05,
+    },
+];
+
+/// The inclusive range of days that `interval` may be moved to by fuzzing. Intervals shorter
+/// than 2.5 days are not fuzzed.
+pub fn fuzz_bounds(interval: f32, ranges: &[FuzzRange]) -> (u32, u32) {
+    if interval < 2.5 {
+        let interval = interval.round().max(1.0) as u32;
+        return (interval, interval);
+    }
+    let delta = ranges.iter().fold(1.0, |delta, range| {
+        delta + range.factor * (interval.min(range.end) - range.start).max(0.0)
+    });
+    let min = (interval - delta).round().max(2.0) as u32;
+    let max = (interval + delta).round() as u32;
+    (min.min(max), max)
+}
+
+/// Pick a day within [fuzz_bounds]. The choice is determined by `seed`, which would usually be
+/// the card's id, so sibling cards answered together don't all come due on the same day, and
+/// repeated calls for the same card are stable.
+pub fn fuzzed_interval(interval: f32, seed: u64, ranges: &[FuzzRange]) -> u32 {
+    let (min, max) = fuzz_bounds(interval, ranges);
+    StdRng::seed_from_u64(seed).gen_range(min..=max)
+}
+
+/// Pick the day within [fuzz_bounds] that has the fewest cards already due, so the workload is
+/// spread evenly over the coming days. `due_counts` maps a number of days from today to the
+/// number of cards due on that day. Ties go to the shorter interval, as that keeps retention
+/// closest to the desired retention.
+pub fn load_balanced_interval(
+    interval: f32,
+    due_counts: &HashMap<u32, usize>,
+    ranges: &[FuzzRange],
+) -> u32 {
+    let (min, max) = fuzz_bounds(interval, ranges);
+    (min..=max)
+        .min_by_key(|day| (due_counts.get(day).copied().unwrap_or_default(), *day))
+        .unwrap()
+}
+
+/// Pick the day within [fuzz_bounds] closest to `interval` that is not in `blocked_days`, such
+/// as days the user never studies, given as numbers of days from today. Ties go to the shorter
+/// interval, as that keeps retention closest to the desired retention. If every day in the range
+/// is blocked, as always happens for intervals too short to fuzz, the rounded interval is kept.
+pub fn interval_avoiding_days(
+    interval: f32,
+    blocked_days: &HashSet<u32>,
+    ranges: &[FuzzRange],
+) -> u32 {
+    let (min, max) = fuzz_bounds(interval, ranges);
+    let target = (interval.round().max(1.0) as u32).clamp(min, max);
+    (min..=max)
+        .filter(|day| !blocked_days.contains(day))
+        .min_by_key(|&day| (day.abs_diff(target), day))
+        .unwrap_or(target)
+}
+
+impl NextStates {
+    /// Apply [fuzzed_interval] to the interval of each rating, leaving memory states untouched.
+    /// The retrievability at the scheduled interval is updated to match the fuzzed interval, on
+    /// `curve`, which should be the curve the states were computed with, such as
+    /// `FSRS::forgetting_curve`.
+    pub fn fuzzed(mut self, seed: u64, ranges: &[FuzzRange], curve: ForgettingCurve) -> Self {
+        for state in [
+            &mut self.again,
+            &mut self.hard,
+            &mut self.good,
+            &mut self.easy,
+        ] {
+            let interval = fuzzed_interval(state.interval as f32, seed, ranges);
+            *state = ItemState::new(state.memory, interval, curve);
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bounds() {
+        assert_eq!(fuzz_bounds(1.0, DEFAULT_FUZZ_RANGES), (1, 1));
+        assert_eq!(fuzz_bounds(2.0, DEFAULT_FUZZ_RANGES), (2, 2));
+        assert_eq!(fuzz_bounds(10.0, DEFAULT_FUZZ_RANGES), (8, 12));
+        assert_eq!(fuzz_bounds(100.0, DEFAULT_FUZZ_RANGES), (93, 107));
+        assert_eq!(fuzz_bounds(10.0, &[]), (9, 11));
+    }
+
+    #[test]
+    fn fuzz_is_seeded() {
+        for seed in 0..100 {
+            let interval = fuzzed_interval(100.0, seed, DEFAULT_FUZZ_RANGES);
+            assert!((93..=107).contains(&interval));
+            assert_eq!(interval, fuzzed_interval(100.0, seed, DEFAULT_FUZZ_RANGES));
+        }
+        let intervals = (0..100)
+            .map(|seed| fuzzed_interval(100.0, seed, DEFAULT_FUZZ_RANGES))
+            .collect::<std::collections::HashSet<_>>();
+        assert!(intervals.len() > 1);
+    }
+
+    #[test]
+    fn load_balancing() {
+        let due_counts = HashMap::from([(8, 5), (9, 1), (10, 3), (11, 1), (12, 4)]);
+        assert_eq!(
+            load_balanced_interval(10.0, &due_counts, DEFAULT_FUZZ_RANGES),
+            9
+        );
+        // days with nothing due are preferred
+        assert_eq!(
+            load_balanced_interval(100.0, &due_counts, DEFAULT_FUZZ_RANGES),
+            93
+        );
+        // short intervals aren't moved
+        assert_eq!(
+            load_balanced_interval(1.0, &due_counts, DEFAULT_FUZZ_RANGES),
+            1
+        );
+    }
+
+    #[test]
+    fn avoiding_days() {
+        let weekends = (0..20)
+            .flat_map(|week| [week * 7 + 5, week * 7 + 6])
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            interval_avoiding_days(10.0, &weekends, DEFAULT_FUZZ_RANGES),
+            10
+        );
+        // 12 and 13 are blocked, and 11 is closer than 14
+        assert_eq!(
+            interval_avoiding_days(12.4, &weekends, DEFAULT_FUZZ_RANGES),
+            11
+        );
+        assert_eq!(
+            interval_avoiding_days(13.0, &weekends, DEFAULT_FUZZ_RANGES),
+            14
+        );
+        let blocked = HashSet::from([8, 9, 10, 11, 12]);
+        assert_eq!(
+            interval_avoiding_days(10.0, &blocked, DEFAULT_FUZZ_RANGES),
+            10
+        );
+        assert_eq!(
+            interval_avoiding_days(2.0, &HashSet::from([2]), DEFAULT_FUZZ_RANGES),
+            2
+        );
+    }
+}
@@ -1,19 +1,38 @@
-use std::collections::HashMap;
+#[cfg(feature = "training")]
+use std::collections::BTreeMap;
+#[cfg(feature = "training")]
 use std::ops::{Add, Sub};
+#[cfg(feature = "training")]
+use std::sync::{Arc, Mutex};
 
-use crate::model::{Get, MemoryStateTensors, FSRS};
-use burn::tensor::{Data, Shape, Tensor};
-use burn::{data::dataloader::batcher::Batcher, tensor::backend::Backend};
-
-use crate::dataset::FSRSBatch;
-use crate::dataset::FSRSBatcher;
+use crate::dataset::RatingMap;
+#[cfg(feature = "training")]
+use crate::dataset::{check_items, group_items, is_same_day, FSRSBatch, FSRSBatcher};
 use crate::error::Result;
-use crate::model::Model;
-use crate::training::BCELoss;
-use crate::{FSRSError, FSRSItem};
-use burn::tensor::ElementConversion;
+#[cfg(feature = "training")]
+use crate::memory_model::MemoryModel;
+#[cfg(feature = "training")]
+use crate::model::{Get, MemoryStateTensors, Model, FSRS};
+#[cfg(feature = "training")]
+use crate::training::{BCELoss, ProgressState};
+#[cfg(feature = "training")]
+use crate::weight_clipper::weight_info;
+use crate::FSRSError;
+#[cfg(feature = "training")]
+use crate::FSRSItem;
+#[cfg(feature = "training")]
+use burn::data::dataloader::batcher::Batcher;
+#[cfg(feature = "training")]
+use burn::tensor::{backend::Backend, Data, ElementConversion, Shape, Tensor};
+#[cfg(feature = "training")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-/// This is a slice for efficiency, but should always be 17 in length.
+/// This is a slice for efficiency. It should be 17 in length (FSRS-4.5), or 19 when the
+/// short-term weights for same-day reviews are included. Weights for
+/// [FSRS::new_with_trainable_decay] are followed by one more, the magnitude of the forgetting
+/// curve's decay.
 pub type Weights = [f32];
 
 pub static DEFAULT_WEIGHTS: &[f32] = &[
@@ -21,6 +40,7 @@ pub static DEFAULT_WEIGHTS: &[f32] = &[
     2.61,
 ];
 
+#[cfg(feature = "training")]
 fn infer<B: Backend>(
     model: &Model<B>,
     batch: FSRSBatch<B>,
@@ -31,11 +51,38 @@ fn infer<B: Backend>(
 }
 
 #[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct MemoryState {
     pub stability: f32,
     pub difficulty: f32,
 }
 
+impl MemoryState {
+    /// The probability of recalling the card `days_elapsed` days after its last review, on the
+    /// default forgetting curve. Use [FSRS::current_retrievability] for a custom or trained one.
+    pub fn retrievability(&self, days_elapsed: f32) -> f32 {
+        retention_for_interval(self.stability, days_elapsed)
+    }
+
+    /// Sample the forgetting curve at `points` evenly spaced days from 0 to `horizon_days`
+    /// inclusive, returning `(days_elapsed, retrievability)` pairs suitable for plotting.
+    pub fn forgetting_curve(&self, horizon_days: f32, points: usize) -> Vec<(f32, f32)> {
+        let step = if points > 1 {
+            horizon_days / (points - 1) as f32
+        } else {
+            0.0
+        };
+        (0..points)
+            .map(|i| {
+                let day = step * i as f32;
+                (day, self.retrievability(day))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "training")]
 impl<B: Backend> From<MemoryStateTensors<B>> for MemoryState {
     fn from(m: MemoryStateTensors<B>) -> Self {
         MemoryState {
@@ -45,6 +92,7 @@ impl<B: Backend> From<MemoryStateTensors<B>> for MemoryState {
     }
 }
 
+#[cfg(feature = "training")]
 impl<B: Backend> From<MemoryState> for MemoryStateTensors<B> {
     fn from(m: MemoryState) -> Self {
         MemoryStateTensors {
@@ -57,12 +105,81 @@ impl<B: Backend> From<MemoryState> for MemoryStateTensors<B> {
     }
 }
 
-fn next_interval(stability: f32, request_retention: f32) -> u32 {
-    (9.0 * stability * (1.0 / request_retention - 1.0))
-        .round()
-        .max(1.0) as u32
+/// The forgetting curve is `R(t) = (1 + t / (FACTOR * S)) ^ DECAY`, which gives a
+/// retrievability of 90% when the days elapsed equal the stability.
+pub(crate) const DECAY: f32 = -1.0;
+pub(crate) const FACTOR: f32 = 9.0;
+
+/// The shape of the forgetting curve, `R(t) = (1 + t / (factor * S)) ^ decay`. The default is
+/// the curve used by FSRS-4.5; a different one can be given to [FSRS::with_forgetting_curve].
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ForgettingCurve {
+    pub decay: f32,
+    pub factor: f32,
+}
+
+impl Default for ForgettingCurve {
+    fn default() -> Self {
+        Self {
+            decay: DECAY,
+            factor: FACTOR,
+        }
+    }
+}
+
+impl ForgettingCurve {
+    /// The curve with the given decay, and the factor that keeps a retrievability of 90% when
+    /// the days elapsed equal the stability.
+    pub fn with_decay(decay: f32) -> Self {
+        Self {
+            decay,
+            factor: 1.0 / (0.9f32.powf(1.0 / decay) - 1.0),
+        }
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.decay.is_nan() || self.decay >= 0.0 {
+            return Err(FSRSError::InvalidInput { field: "decay" });
+        }
+        if self.factor.is_nan() || self.factor <= 0.0 {
+            return Err(FSRSError::InvalidInput { field: "factor" });
+        }
+        Ok(())
+    }
+
+    /// See [next_interval].
+    pub fn next_interval(&self, stability: f32, desired_retention: f32) -> f32 {
+        self.factor * stability * ((1.0 / desired_retention).powf(-1.0 / self.decay) - 1.0)
+    }
+
+    /// See [retention_for_interval].
+    pub fn retention_for_interval(&self, stability: f32, interval: f32) -> f32 {
+        (interval / (stability * self.factor) + 1.0).powf(self.decay)
+    }
+
+    #[cfg(feature = "training")]
+    fn rounded_interval(&self, stability: f32, desired_retention: f32) -> u32 {
+        self.next_interval(stability, desired_retention)
+            .round()
+            .max(1.0) as u32
+    }
+}
+
+/// The number of days after which a card with the given stability is expected to fall to
+/// `desired_retention`. This is not rounded; [FSRS::next_interval] returns the whole-day interval
+/// used for scheduling.
+pub fn next_interval(stability: f32, desired_retention: f32) -> f32 {
+    ForgettingCurve::default().next_interval(stability, desired_retention)
+}
+
+/// The inverse of [next_interval]: the retrievability a card with the given stability is
+/// expected to have after `interval` days.
+pub fn retention_for_interval(stability: f32, interval: f32) -> f32 {
+    ForgettingCurve::default().retention_for_interval(stability, interval)
 }
 
+#[cfg(feature = "training")]
 impl<B: Backend> FSRS<B> {
     /// Calculate the current memory state for a given card's history of reviews.
     /// Weights must have been provided when calling FSRS::new().
@@ -81,11 +198,154 @@ impl<B: Backend> FSRS<B> {
         self.model().forward(time_history, rating_history).into()
     }
 
+    /// Like [FSRS::memory_state], but returns the memory state after every review in the item's
+    /// history instead of only the final one.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn memory_state_history(&self, item: &FSRSItem) -> Vec<MemoryState> {
+        let model = self.model();
+        let mut state = None;
+        item.reviews
+            .iter()
+            .map(|review| {
+                let delta_t =
+                    Tensor::from_data(Data::new(vec![review.delta_t.elem()], Shape { dims: [1] }));
+                let rating =
+                    Tensor::from_data(Data::new(vec![review.rating.elem()], Shape { dims: [1] }));
+                let next = model.step(delta_t, rating, state.take());
+                let memory = MemoryState::from(next.clone());
+                state = Some(next);
+                memory
+            })
+            .collect()
+    }
+
+    /// Like [FSRS::memory_state], but only replays the last `max_reviews` reviews, which bounds
+    /// the work for cards with very long histories. The reviews before them are summarized by
+    /// `prefix_state`, such as a state saved when they were made; without one, the first
+    /// replayed review is treated as the card's first. Returns `prefix_state` if there are no
+    /// reviews to replay.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn memory_state_truncated(
+        &self,
+        item: &FSRSItem,
+        max_reviews: usize,
+        prefix_state: Option<MemoryState>,
+    ) -> Option<MemoryState> {
+        let model = self.model();
+        let skip = item.reviews.len().saturating_sub(max_reviews);
+        let mut state = prefix_state
+            .filter(|_| skip > 0)
+            .map(MemoryStateTensors::from);
+        for review in &item.reviews[skip..] {
+            let delta_t =
+                Tensor::from_data(Data::new(vec![review.delta_t.elem()], Shape { dims: [1] }));
+            let rating =
+                Tensor::from_data(Data::new(vec![review.rating.elem()], Shape { dims: [1] }));
+            state = Some(model.step(delta_t, rating, state));
+        }
+        state.map(MemoryState::from).or(prefix_state)
+    }
+
+    /// Calculate the current memory states of many cards at once. The items are padded and
+    /// run through the model in batches, which is much faster than calling
+    /// [FSRS::memory_state] once per card. Fails with [FSRSError::InvalidItem] if an item has no
+    /// reviews, or a review with an invalid rating or `delta_t`.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn memory_state_batch(&self, items: &[FSRSItem]) -> Result<Vec<MemoryState>> {
+        items
+            .iter()
+            .enumerate()
+            .try_for_each(|(index, item)| item.check_reviews(index))?;
+        let model = self.model();
+        let mut states = Vec::with_capacity(items.len());
+        for chunk in items.chunks(512) {
+            let batch_size = chunk.len();
+            let seq_len = chunk.iter().map(|item| item.reviews.len()).max().unwrap();
+            // shorter histories are padded with zero ratings, which the model skips over
+            let (time_history, rating_history): (Vec<Vec<f32>>, Vec<Vec<f32>>) = chunk
+                .par_iter()
+                .map(|item| {
+                    let (mut delta_t, mut rating): (Vec<_>, Vec<_>) = item
+                        .reviews
+                        .iter()
+                        .map(|r| (r.delta_t, r.rating as f32))
+                        .unzip();
+                    delta_t.resize(seq_len, 0.0);
+                    rating.resize(seq_len, 0.0);
+                    (delta_t, rating)
+                })
+                .unzip();
+            let shape = Shape {
+                dims: [batch_size, seq_len],
+            };
+            let time_history =
+                Tensor::from_data(Data::new(time_history.concat(), shape.clone()).convert())
+                    .transpose();
+            let rating_history =
+                Tensor::from_data(Data::new(rating_history.concat(), shape).convert()).transpose();
+            let state = model.forward(time_history, rating_history);
+            let stability: Vec<f32> = state.stability.to_data().convert().value;
+            let difficulty: Vec<f32> = state.difficulty.to_data().convert().value;
+            states.extend(
+                stability
+                    .into_iter()
+                    .zip(difficulty)
+                    .map(|(stability, difficulty)| MemoryState {
+                        stability,
+                        difficulty,
+                    }),
+            );
+        }
+        Ok(states)
+    }
+
+    /// Reschedule many cards at once, such as a whole collection when switching to FSRS. Each
+    /// item holds a card's full review history; its memory state is computed as in
+    /// [FSRS::memory_state_batch], and its new interval, capped at `maximum_interval`, counts
+    /// days from its last review, so the card is due that many days after it.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn reschedule(
+        &self,
+        items: &[FSRSItem],
+        desired_retention: f32,
+        maximum_interval: u32,
+    ) -> Result<Vec<ItemState>> {
+        if desired_retention.is_nan() || desired_retention <= 0.0 || desired_retention >= 1.0 {
+            return Err(FSRSError::InvalidInput {
+                field: "desired_retention",
+            });
+        }
+        if maximum_interval == 0 {
+            return Err(FSRSError::InvalidInput {
+                field: "maximum_interval",
+            });
+        }
+        let curve = self.forgetting_curve();
+        Ok(self
+            .memory_state_batch(items)?
+            .into_par_iter()
+            .map(|memory| {
+                let interval = curve
+                    .rounded_interval(memory.stability, desired_retention)
+                    .min(maximum_interval);
+                ItemState::new(memory, interval, curve)
+            })
+            .collect())
+    }
+
     /// If a card has incomplete learning history, memory state can be approximated from
-    /// current sm2 values.
+    /// current sm2 values. `sm2_retention` is the retention the card was scheduled at under
+    /// SM-2 (such as the collection's true retention), and is used to convert the SM-2
+    /// interval into a stability.
     /// Weights must have been provided when calling FSRS::new().
-    pub fn memory_state_from_sm2(&self, ease_factor: f32, interval: f32) -> MemoryState {
-        let stability = interval.max(0.1);
+    pub fn memory_state_from_sm2(
+        &self,
+        ease_factor: f32,
+        interval: f32,
+        sm2_retention: f32,
+    ) -> MemoryState {
+        let stability =
+            interval.max(0.1) / self.forgetting_curve().next_interval(1.0, sm2_retention);
         let w = &self.model().w;
         let w8: f32 = w.get(8).into_scalar().elem();
         let w9: f32 = w.get(9).into_scalar().elem();
@@ -113,23 +373,43 @@ impl<B: Backend> FSRS<B> {
             let model = self.model();
             model.init_stability(rating.clone()).into_scalar().elem()
         });
-        next_interval(stability, desired_retention)
+        self.forgetting_curve()
+            .rounded_interval(stability, desired_retention)
     }
 
-    /// The intervals and memory states for each answer button.
+    /// The whole-day interval for a card answered with `rating`, scaled by the rating's
+    /// multiplier from [FSRS::with_interval_multipliers].
+    fn scheduled_interval(&self, stability: f32, desired_retention: f32, rating: u32) -> u32 {
+        let multiplier = self.interval_multipliers()[rating as usize - 1];
+        (self
+            .forgetting_curve()
+            .next_interval(stability, desired_retention)
+            * multiplier)
+            .round()
+            .max(1.0) as u32
+    }
+
+    /// The intervals and memory states for each answer button. Intervals are capped at
+    /// `maximum_interval` days, but the reported stability is not.
     /// Weights must have been provided when calling FSRS::new().
     pub fn next_states(
         &self,
         current_memory_state: Option<MemoryState>,
         desired_retention: f32,
-        days_elapsed: u32,
+        days_elapsed: f32,
+        maximum_interval: u32,
     ) -> NextStates {
         let delta_t = Tensor::from_data(Data::new(vec![days_elapsed.elem()], Shape { dims: [1] }));
         let current_memory_state_tensors = current_memory_state.map(MemoryStateTensors::from);
         let model = self.model();
         let mut next_memory_states = (1..=4).map(|rating| {
-            if let (Some(current_memory_state), 0) = (current_memory_state, days_elapsed) {
-                // When there's an existing memory state and no days have elapsed, we leave it unchanged.
+            if let (Some(current_memory_state), true, false) = (
+                current_memory_state,
+                is_same_day(days_elapsed),
+                model.has_short_term_weights(),
+            ) {
+                // When there's an existing memory state and less than a day has elapsed, we leave
+                // it unchanged, unless there are short-term weights to model same-day reviews with.
                 current_memory_state
             } else {
                 MemoryState::from(model.step(
@@ -140,40 +420,132 @@ impl<B: Backend> FSRS<B> {
             }
         });
 
-        let mut get_next_state = || {
+        let curve = self.forgetting_curve();
+        let mut get_next_state = |rating| {
             let memory = next_memory_states.next().unwrap();
-            let interval = next_interval(memory.stability, desired_retention);
-            ItemState { memory, interval }
+            let interval = self
+                .scheduled_interval(memory.stability, desired_retention, rating)
+                .min(maximum_interval);
+            ItemState::new(memory, interval, curve)
         };
 
         NextStates {
-            again: get_next_state(),
-            hard: get_next_state(),
-            good: get_next_state(),
-            easy: get_next_state(),
+            again: get_next_state(1),
+            hard: get_next_state(2),
+            good: get_next_state(3),
+            easy: get_next_state(4),
         }
     }
 
+    /// Like [FSRS::next_states], for many cards that already have a memory state. Each entry is
+    /// a card's current memory state, desired retention and days elapsed. All cards are stepped
+    /// through the model together, which is much faster than calling [FSRS::next_states] once
+    /// per card.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn next_states_batch(
+        &self,
+        cards: &[(MemoryState, f32, f32)],
+        maximum_interval: u32,
+    ) -> Vec<NextStates> {
+        if cards.is_empty() {
+            return vec![];
+        }
+        let shape = Shape {
+            dims: [cards.len()],
+        };
+        let current = MemoryStateTensors {
+            stability: Tensor::from_data(Data::new(
+                cards.iter().map(|c| c.0.stability.elem()).collect(),
+                shape.clone(),
+            )),
+            difficulty: Tensor::from_data(Data::new(
+                cards.iter().map(|c| c.0.difficulty.elem()).collect(),
+                shape.clone(),
+            )),
+        };
+        let delta_t = Tensor::from_data(Data::new(
+            cards.iter().map(|c| c.2.elem()).collect(),
+            shape.clone(),
+        ));
+        let model = self.model();
+        let [again, hard, good, easy] = [1, 2, 3, 4].map(|rating: u32| {
+            let rating =
+                Tensor::from_data(Data::new(vec![rating.elem(); cards.len()], shape.clone()));
+            let next = model.step(delta_t.clone(), rating, Some(current.clone()));
+            let stability: Vec<f32> = next.stability.to_data().convert().value;
+            let difficulty: Vec<f32> = next.difficulty.to_data().convert().value;
+            stability
+                .into_iter()
+                .zip(difficulty)
+                .map(|(stability, difficulty)| MemoryState {
+                    stability,
+                    difficulty,
+                })
+                .collect::<Vec<_>>()
+        });
+        let curve = self.forgetting_curve();
+        cards
+            .iter()
+            .enumerate()
+            .map(|(i, &(current, desired_retention, days_elapsed))| {
+                let item_state = |next: &[MemoryState], rating| {
+                    // as in next_states, the state is unchanged on a same-day review
+                    let memory = if is_same_day(days_elapsed) && !model.has_short_term_weights() {
+                        current
+                    } else {
+                        next[i]
+                    };
+                    let interval = self
+                        .scheduled_interval(memory.stability, desired_retention, rating)
+                        .min(maximum_interval);
+                    ItemState::new(memory, interval, curve)
+                };
+                NextStates {
+                    again: item_state(&again, 1),
+                    hard: item_state(&hard, 2),
+                    good: item_state(&good, 3),
+                    easy: item_state(&easy, 4),
+                }
+            })
+            .collect()
+    }
+
     /// Determine how well the model and weights predict performance.
     /// Weights must have been provided when calling FSRS::new().
-    pub fn evaluate<F>(&self, items: Vec<FSRSItem>, mut progress: F) -> Result<ModelEvaluation>
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(items = items.len())))]
+    pub fn evaluate<F>(&self, items: Vec<FSRSItem>, progress: F) -> Result<ModelEvaluation>
+    where
+        F: FnMut(ItemProgress) -> bool,
+    {
+        self.evaluate_parts(&[items.as_slice()], progress)
+    }
+
+    /// Like [FSRS::evaluate], but pools several slices of items, so sets split apart for
+    /// training can be evaluated together without first being gathered into one Vec.
+    pub(crate) fn evaluate_parts<F>(
+        &self,
+        parts: &[&[FSRSItem]],
+        mut progress: F,
+    ) -> Result<ModelEvaluation>
     where
         F: FnMut(ItemProgress) -> bool,
     {
-        if items.is_empty() {
+        let items = || parts.iter().flat_map(|part| part.iter());
+        let total = items().count();
+        if total == 0 {
             return Err(FSRSError::NotEnoughData);
         }
+        items()
+            .enumerate()
+            .try_for_each(|(index, item)| item.check(index))?;
         let batcher = FSRSBatcher::new(self.device());
         let mut all_predictions = vec![];
         let mut all_true_val = vec![];
         let mut all_retention = vec![];
         let mut all_labels = vec![];
-        let mut progress_info = ItemProgress {
-            current: 0,
-            total: items.len(),
-        };
+        let mut progress_info = ItemProgress { current: 0, total };
         let model = self.model();
-        for chunk in items.chunks(512) {
+        for chunk in parts.iter().flat_map(|part| part.chunks(512)) {
             let batch = batcher.batch(chunk.to_vec());
             let (_state, retention) = infer::<B>(model, batch.clone());
             let pred: Vec<f32> = retention.clone().to_data().convert().value;
@@ -187,30 +559,301 @@ impl<B: Backend> FSRS<B> {
                 return Err(FSRSError::Interrupted);
             }
         }
-        let rmse = calibration_rmse(&all_predictions, &all_true_val);
+        let calibration = calibration_bins(&all_predictions, &all_true_val);
+        let rmse = calibration_rmse(&calibration);
+        let auc = auc(&all_predictions, &all_true_val);
+        let brier = brier_score(&all_predictions, &all_true_val);
+        let by_first_rating = group_evaluations(
+            items().map(|item| item.reviews[0].rating),
+            &all_predictions,
+            &all_true_val,
+        );
+        let by_review_count = group_evaluations(
+            items().map(|item| item.history().count() as u32),
+            &all_predictions,
+            &all_true_val,
+        );
         let all_retention = Tensor::cat(all_retention, 0);
         let all_labels = Tensor::cat(all_labels, 0).float();
         let loss = BCELoss::<B>::new().forward(all_retention, all_labels);
         Ok(ModelEvaluation {
             log_loss: loss.to_data().value[0].elem(),
             rmse_bins: rmse,
+            auc,
+            roc: roc_curve(&all_predictions, &all_true_val),
+            brier,
+            calibration,
+            by_first_rating,
+            by_review_count,
         })
     }
 
+    /// Like [FSRS::evaluate], but reports progress through a shared [ProgressState], as
+    /// [FSRS::compute_weights] does. Evaluation is treated as a single epoch, and setting
+    /// `want_abort` cancels it with [FSRSError::Interrupted].
+    pub fn evaluate_with_progress(
+        &self,
+        items: Vec<FSRSItem>,
+        progress: Arc<Mutex<ProgressState>>,
+    ) -> Result<ModelEvaluation> {
+        self.evaluate(items, |item_progress| {
+            let mut info = progress.lock().unwrap();
+            info.epoch = 1;
+            info.epoch_total = 1;
+            info.items_processed = item_progress.current;
+            info.items_total = item_progress.total;
+            !info.want_abort
+        })
+    }
+
+    /// Evaluate each [FSRSItem::group] separately, such as to find decks the weights suit poorly.
+    /// Use [FSRS::evaluate] for the pooled metrics.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn evaluate_by_group(
+        &self,
+        items: Vec<FSRSItem>,
+    ) -> Result<BTreeMap<Option<u64>, ModelEvaluation>> {
+        group_items(items)
+            .into_iter()
+            .map(|(group, items)| Ok((group, self.evaluate(items, |_| true)?)))
+            .collect()
+    }
+
+    /// Move each weight down and up by `step` times the range it is clipped to, one at a time,
+    /// and report how the log loss on `items` changes. Weights whose changes barely affect the
+    /// loss are poorly determined by the history, and a re-optimization that mostly moves them
+    /// is unlikely to be worth applying.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn weight_sensitivity(
+        &self,
+        items: Vec<FSRSItem>,
+        step: f32,
+    ) -> Result<Vec<WeightSensitivity>> {
+        if step.is_nan() || step <= 0.0 || step > 1.0 {
+            return Err(FSRSError::InvalidInput { field: "step" });
+        }
+        let weights: Vec<f32> = self.model().w.val().to_data().convert().value;
+        let baseline = self.evaluate(items.clone(), |_| true)?.log_loss;
+        let log_loss_with = |index: usize, value: f32| -> Result<f32> {
+            let mut weights = weights.clone();
+            weights[index] = value;
+            let fsrs = self.with_other_weights(&weights)?;
+            Ok(fsrs.evaluate(items.clone(), |_| true)?.log_loss - baseline)
+        };
+        weight_info(weights.len(), self.has_trainable_decay())?
+            .into_iter()
+            .enumerate()
+            .map(|(index, info)| {
+                let delta = step * (info.max - info.min);
+                Ok(WeightSensitivity {
+                    index,
+                    name: info.name,
+                    decreased: log_loss_with(index, weights[index] - delta)?,
+                    increased: log_loss_with(index, weights[index] + delta)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Compare the weights this instance was created with against `other_weights` on the same
+    /// items, so that a newly optimized set can be checked against the current one before it
+    /// replaces it.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn universal_metrics(
+        &self,
+        items: Vec<FSRSItem>,
+        other_weights: &Weights,
+    ) -> Result<ModelComparison> {
+        let other = self.with_other_weights(other_weights)?;
+        let current_predictions = self.predict(&items)?;
+        let other_predictions = other.predict(&items)?;
+        let (current_pred, true_val): (Vec<f32>, Vec<f32>) =
+            current_predictions.into_iter().unzip();
+        let other_pred: Vec<f32> = other_predictions.into_iter().map(|(p, _)| p).collect();
+        Ok(ModelComparison {
+            current: self.evaluate(items.clone(), |_| true)?,
+            other: other.evaluate(items, |_| true)?,
+            current_universal: calibration_rmse(&calibration_bins_by(
+                &other_pred,
+                &current_pred,
+                &true_val,
+            )),
+            other_universal: calibration_rmse(&calibration_bins_by(
+                &current_pred,
+                &other_pred,
+                &true_val,
+            )),
+        })
+    }
+
+    /// The predicted retrievability at the final review of each item, paired with the outcome
+    /// of that review (1.0 if recalled, 0.0 if forgotten). These are the values [FSRS::evaluate]
+    /// computes its metrics from.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn predict(&self, items: &[FSRSItem]) -> Result<Vec<(f32, f32)>> {
+        check_items(items)?;
+        let batcher = FSRSBatcher::new(self.device());
+        let model = self.model();
+        let mut predictions = Vec::with_capacity(items.len());
+        for chunk in items.chunks(512) {
+            let batch = batcher.batch(chunk.to_vec());
+            let (_state, retention) = infer::<B>(model, batch.clone());
+            let pred: Vec<f32> = retention.to_data().convert().value;
+            let true_val: Vec<f32> = batch.labels.float().to_data().convert().value;
+            predictions.extend(pred.into_iter().zip(true_val));
+        }
+        Ok(predictions)
+    }
+
+    /// The `n` items whose final review was predicted worst, most surprising first, such as to
+    /// find cards that were answered dishonestly or that interfere with others, which pull the
+    /// weights away from the rest of the history. Items are judged by the log loss of their
+    /// final review, so a card recalled at a low predicted retrievability ranks as highly as
+    /// one forgotten at a high one.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn worst_fits(&self, items: &[FSRSItem], n: usize) -> Result<Vec<Misprediction>> {
+        let mut mispredictions: Vec<_> = self
+            .predict(items)?
+            .into_iter()
+            .enumerate()
+            .map(|(index, (predicted, outcome))| Misprediction {
+                index,
+                predicted,
+                recalled: outcome == 1.0,
+                log_loss: review_log_loss(predicted, outcome),
+            })
+            .collect();
+        mispredictions.sort_by(|a, b| b.log_loss.total_cmp(&a.log_loss));
+        mispredictions.truncate(n);
+        Ok(mispredictions)
+    }
+
     /// How well the user is likely to remember the item after `days_elapsed` since the previous
-    /// review.
-    pub fn current_retrievability(&self, state: MemoryState, days_elapsed: u32) -> f32 {
-        (days_elapsed as f32 / (state.stability * 9.0) + 1.0).powf(-1.0)
+    /// review, on this instance's [FSRS::forgetting_curve].
+    pub fn current_retrievability(&self, state: MemoryState, days_elapsed: f32) -> f32 {
+        self.forgetting_curve()
+            .retention_for_interval(state.stability, days_elapsed)
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[cfg(feature = "training")]
+#[derive(Debug, Clone)]
 pub struct ModelEvaluation {
     pub log_loss: f32,
     pub rmse_bins: f32,
+    /// Area under the ROC curve: how well predictions separate recalled from forgotten reviews.
+    pub auc: f32,
+    /// The points of the ROC curve, for plotting it; see [roc_curve].
+    pub roc: Vec<RocPoint>,
+    /// Mean squared difference between predicted retrievability and the review outcome.
+    pub brier: f32,
+    /// Predicted vs actual recall, grouped by predicted retrievability. Only bins that
+    /// contain at least one review are included, ordered from lowest to highest prediction.
+    pub calibration: Vec<CalibrationBin>,
+    /// Metrics for the items grouped by the rating of their first review.
+    pub by_first_rating: Vec<GroupEvaluation>,
+    /// Metrics for the items grouped by the number of reviews preceding the predicted one.
+    pub by_review_count: Vec<GroupEvaluation>,
+}
+
+#[cfg(feature = "training")]
+/// Metrics for a subset of the evaluated items, ordered by `key`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupEvaluation {
+    pub key: u32,
+    /// The number of items in this group.
+    pub count: usize,
+    pub log_loss: f32,
+    pub rmse_bins: f32,
+}
+
+#[cfg(feature = "training")]
+/// The result of [FSRS::universal_metrics].
+#[derive(Debug, Clone)]
+pub struct ModelComparison {
+    /// The evaluation of the weights the [FSRS] instance was created with.
+    pub current: ModelEvaluation,
+    /// The evaluation of the weights being compared against.
+    pub other: ModelEvaluation,
+    /// The calibration error of the current weights' predictions, when the reviews are grouped by
+    /// the other weights' predictions instead of their own. This measures how much of the current
+    /// weights' error the other weights can detect. Lower is better.
+    pub current_universal: f32,
+    /// The same measure for the other weights, grouped by the current weights' predictions.
+    pub other_universal: f32,
+}
+
+#[cfg(feature = "training")]
+impl ModelComparison {
+    /// True if the other weights' predictions hold up better when judged by the current
+    /// weights than the reverse.
+    pub fn other_is_better(&self) -> bool {
+        self.other_universal < self.current_universal
+    }
+}
+
+#[cfg(feature = "training")]
+/// The change in log loss from moving one weight, as returned by [FSRS::weight_sensitivity].
+/// Weights are clipped to their range, so a weight at the edge of it may not move in one
+/// direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightSensitivity {
+    pub index: usize,
+    /// The weight's [crate::WeightInfo::name].
+    pub name: &'static str,
+    /// The change in log loss when the weight is decreased. Positive when the fit gets worse.
+    pub decreased: f32,
+    pub increased: f32,
+}
+
+#[cfg(feature = "training")]
+impl WeightSensitivity {
+    /// The larger increase in log loss of the two directions, for ranking weights by how much
+    /// they matter.
+    pub fn max_increase(&self) -> f32 {
+        self.decreased.max(self.increased)
+    }
+}
+
+#[cfg(feature = "training")]
+/// An item's final review and how well it was predicted, as returned by [FSRS::worst_fits].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Misprediction {
+    /// The item's position in the slice passed to [FSRS::worst_fits].
+    pub index: usize,
+    /// The predicted retrievability at the review.
+    pub predicted: f32,
+    pub recalled: bool,
+    pub log_loss: f32,
+}
+
+#[cfg(feature = "training")]
+/// A point on the ROC curve, from predicting a recall for every review whose predicted
+/// retrievability is at least `threshold`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RocPoint {
+    pub threshold: f32,
+    /// The fraction of recalled reviews predicted as recalled.
+    pub true_positive_rate: f32,
+    /// The fraction of forgotten reviews predicted as recalled.
+    pub false_positive_rate: f32,
+}
+
+#[cfg(feature = "training")]
+/// A point on the calibration graph.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CalibrationBin {
+    /// The mean predicted retrievability of the reviews in this bin.
+    pub predicted: f32,
+    /// The fraction of the reviews in this bin that were recalled.
+    pub actual: f32,
+    /// The number of reviews in this bin.
+    pub count: usize,
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct NextStates {
     pub again: ItemState,
     pub hard: ItemState,
@@ -218,10 +861,45 @@ pub struct NextStates {
     pub easy: ItemState,
 }
 
+impl NextStates {
+    /// The state for a rating from 1 (Again) to 4 (Easy).
+    pub fn for_rating(&self, rating: u32) -> Option<&ItemState> {
+        match rating {
+            1 => Some(&self.again),
+            2 => Some(&self.hard),
+            3 => Some(&self.good),
+            4 => Some(&self.easy),
+            _ => None,
+        }
+    }
+
+    /// The state for an app's own grade, as mapped by `map`.
+    pub fn for_grade(&self, map: &RatingMap, grade: u32) -> Option<&ItemState> {
+        self.for_rating(map.rating(grade)?)
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Record))]
 pub struct ItemState {
     pub memory: MemoryState,
     pub interval: u32,
+    /// The chance of recalling the card when it next comes due, `interval` days from now.
+    pub retrievability: f32,
+    /// The chance of recalling the card if it were reviewed one day from now.
+    pub next_day_retrievability: f32,
+}
+
+impl ItemState {
+    pub(crate) fn new(memory: MemoryState, interval: u32, curve: ForgettingCurve) -> Self {
+        Self {
+            memory,
+            interval,
+            retrievability: curve.retention_for_interval(memory.stability, interval as f32),
+            next_day_retrievability: curve.retention_for_interval(memory.stability, 1.0),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -230,33 +908,96 @@ pub struct ItemProgress {
     pub total: usize,
 }
 
+/// Like [FSRS::evaluate], but for any [MemoryModel], so that other models can be compared with
+/// FSRS on the same items. Predictions are clamped away from 0 and 1, so that a model that is
+/// certain and wrong has a large but finite log loss. The model must already be fitted, as
+/// [FSRS::compute_weights] only trains FSRS.
+#[cfg(feature = "training")]
+pub fn evaluate_model<M: MemoryModel>(model: &M, items: &[FSRSItem]) -> Result<ModelEvaluation> {
+    if items.is_empty() {
+        return Err(FSRSError::NotEnoughData);
+    }
+    check_items(items)?;
+    let (pred, true_val): (Vec<f32>, Vec<f32>) = items
+        .iter()
+        .map(|item| {
+            let current = item.current();
+            let retrievability = model
+                .replay(item.history().map(|r| (r.rating, r.delta_t)))
+                .map_or(0.5, |state| model.retrievability(state, current.delta_t));
+            let label = if current.rating == 1 { 0.0 } else { 1.0 };
+            (retrievability.clamp(1e-4, 1.0 - 1e-4), label)
+        })
+        .unzip();
+    let calibration = calibration_bins(&pred, &true_val);
+    Ok(ModelEvaluation {
+        log_loss: log_loss(&pred, &true_val),
+        rmse_bins: calibration_rmse(&calibration),
+        auc: auc(&pred, &true_val),
+        roc: roc_curve(&pred, &true_val),
+        brier: brier_score(&pred, &true_val),
+        calibration,
+        by_first_rating: group_evaluations(
+            items.iter().map(|item| item.reviews[0].rating),
+            &pred,
+            &true_val,
+        ),
+        by_review_count: group_evaluations(
+            items.iter().map(|item| item.history().count() as u32),
+            &pred,
+            &true_val,
+        ),
+    })
+}
+
+#[cfg(feature = "training")]
 fn get_bin(x: f32, bins: i32) -> i32 {
     let log_base = (bins.add(1) as f32).ln();
     let binned_x = (x * log_base).exp().floor().sub(1.0);
     (binned_x as i32).min(bins - 1).max(0)
 }
 
-fn calibration_rmse(pred: &[f32], true_val: &[f32]) -> f32 {
-    if pred.len() != true_val.len() {
-        panic!("Vectors pred and true_val must have the same length");
+#[cfg(feature = "training")]
+fn calibration_bins(pred: &[f32], true_val: &[f32]) -> Vec<CalibrationBin> {
+    calibration_bins_by(pred, pred, true_val)
+}
+
+#[cfg(feature = "training")]
+/// Like [calibration_bins], but reviews are assigned to bins by `bin_by` instead of by their
+/// own prediction.
+fn calibration_bins_by(bin_by: &[f32], pred: &[f32], true_val: &[f32]) -> Vec<CalibrationBin> {
+    if pred.len() != true_val.len() || bin_by.len() != pred.len() {
+        panic!("Vectors bin_by, pred and true_val must have the same length");
     }
 
-    let mut groups = HashMap::new();
+    let mut groups = BTreeMap::new();
 
-    for (p, t) in pred.iter().zip(true_val) {
-        let bin = get_bin(*p, 20);
+    for ((b, p), t) in bin_by.iter().zip(pred).zip(true_val) {
+        let bin = get_bin(*b, 20);
         groups.entry(bin).or_insert_with(Vec::new).push((p, t));
     }
 
+    groups
+        .values()
+        .map(|group| {
+            let count = group.len() as f32;
+            CalibrationBin {
+                predicted: group.iter().map(|(p, _)| *p).sum::<f32>() / count,
+                actual: group.iter().map(|(_, t)| *t).sum::<f32>() / count,
+                count: group.len(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(feature = "training")]
+fn calibration_rmse(bins: &[CalibrationBin]) -> f32 {
     let mut total_sum = 0.0;
     let mut total_count = 0.0;
 
-    for group in groups.values() {
-        let count = group.len() as f32;
-        let pred_mean = group.iter().map(|(p, _)| *p).sum::<f32>() / count;
-        let true_mean = group.iter().map(|(_, t)| *t).sum::<f32>() / count;
-
-        let rmse = (pred_mean - true_mean).powi(2);
+    for bin in bins {
+        let count = bin.count as f32;
+        let rmse = (bin.predicted - bin.actual).powi(2);
         total_sum += rmse * count;
         total_count += count;
     }
@@ -264,7 +1005,136 @@ fn calibration_rmse(pred: &[f32], true_val: &[f32]) -> f32 {
     (total_sum / total_count).sqrt()
 }
 
-#[cfg(test)]
+#[cfg(feature = "training")]
+fn group_evaluations(
+    keys: impl Iterator<Item = u32>,
+    pred: &[f32],
+    true_val: &[f32],
+) -> Vec<GroupEvaluation> {
+    let mut groups = BTreeMap::new();
+    for ((key, p), t) in keys.zip(pred).zip(true_val) {
+        let (group_pred, group_true) = groups
+            .entry(key)
+            .or_insert_with(|| (Vec::new(), Vec::new()));
+        group_pred.push(*p);
+        group_true.push(*t);
+    }
+    groups
+        .into_iter()
+        .map(|(key, (group_pred, group_true))| GroupEvaluation {
+            key,
+            count: group_pred.len(),
+            log_loss: log_loss(&group_pred, &group_true),
+            rmse_bins: calibration_rmse(&calibration_bins(&group_pred, &group_true)),
+        })
+        .collect()
+}
+
+#[cfg(feature = "training")]
+fn log_loss(pred: &[f32], true_val: &[f32]) -> f32 {
+    let total: f64 = pred
+        .iter()
+        .zip(true_val)
+        .map(|(&p, &t)| {
+            let (p, t) = (p as f64, t as f64);
+            -(t * p.ln() + (1.0 - t) * (1.0 - p).ln())
+        })
+        .sum();
+    (total / pred.len() as f64) as f32
+}
+
+#[cfg(feature = "training")]
+/// Computed from the ranks of the predictions (the Mann-Whitney U statistic), with tied
+/// predictions sharing their average rank. When every review has the same outcome the AUC is
+/// undefined, and 0.5 is returned.
+fn auc(pred: &[f32], true_val: &[f32]) -> f32 {
+    let mut order: Vec<usize> = (0..pred.len()).collect();
+    order.sort_by(|&a, &b| pred[a].total_cmp(&pred[b]));
+    let mut positive_rank_sum = 0.0;
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && pred[order[end]] == pred[order[start]] {
+            end += 1;
+        }
+        // ranks are 1-based
+        let average_rank = (start + end + 1) as f64 / 2.0;
+        for &i in &order[start..end] {
+            positive_rank_sum += average_rank * true_val[i] as f64;
+        }
+        start = end;
+    }
+    let positives = true_val.iter().map(|&t| t as f64).sum::<f64>();
+    let negatives = true_val.len() as f64 - positives;
+    if positives == 0.0 || negatives == 0.0 {
+        return 0.5;
+    }
+    ((positive_rank_sum - positives * (positives + 1.0) / 2.0) / (positives * negatives)) as f32
+}
+
+/// The ROC curve of predictions against outcomes (1.0 if recalled, 0.0 if forgotten), with a
+/// point for each distinct prediction from highest to lowest, after a first point with an
+/// infinite threshold at the origin, as scikit-learn's `roc_curve` returns them. The area under
+/// the points joined by straight lines is the AUC of [ModelEvaluation::auc]. Empty if every
+/// review was recalled or every review forgotten, as the rates are then undefined.
+#[cfg(feature = "training")]
+pub fn roc_curve(pred: &[f32], true_val: &[f32]) -> Vec<RocPoint> {
+    let positives = true_val.iter().filter(|&&t| t == 1.0).count();
+    let negatives = true_val.len() - positives;
+    if positives == 0 || negatives == 0 {
+        return vec![];
+    }
+    let mut order: Vec<usize> = (0..pred.len()).collect();
+    order.sort_by(|&a, &b| pred[b].total_cmp(&pred[a]));
+    let mut points = vec![RocPoint {
+        threshold: f32::INFINITY,
+        true_positive_rate: 0.0,
+        false_positive_rate: 0.0,
+    }];
+    let (mut true_positives, mut false_positives) = (0, 0);
+    for (position, &i) in order.iter().enumerate() {
+        if true_val[i] == 1.0 {
+            true_positives += 1;
+        } else {
+            false_positives += 1;
+        }
+        // tied predictions share a threshold, so only the last of them adds a point
+        if order
+            .get(position + 1)
+            .map_or(true, |&next| pred[next] != pred[i])
+        {
+            points.push(RocPoint {
+                threshold: pred[i],
+                true_positive_rate: true_positives as f32 / positives as f32,
+                false_positive_rate: false_positives as f32 / negatives as f32,
+            });
+        }
+    }
+    points
+}
+
+/// The log loss of a single prediction, given its outcome (1.0 if recalled, 0.0 if forgotten).
+#[cfg(feature = "training")]
+pub(crate) fn review_log_loss(predicted: f32, outcome: f32) -> f32 {
+    let likelihood = if outcome == 1.0 {
+        predicted
+    } else {
+        1.0 - predicted
+    };
+    -likelihood.max(f32::MIN_POSITIVE).ln()
+}
+
+#[cfg(feature = "training")]
+fn brier_score(pred: &[f32], true_val: &[f32]) -> f32 {
+    let total: f64 = pred
+        .iter()
+        .zip(true_val)
+        .map(|(p, t)| ((p - t) as f64).powi(2))
+        .sum();
+    (total / pred.len() as f64) as f32
+}
+
+#[cfg(all(test, feature = "training"))]
 mod tests {
     use super::*;
     use crate::{convertor_tests::anki21_sample_file_converted_to_fsrs, FSRSReview};
@@ -310,25 +1180,31 @@ mod tests {
             reviews: vec![
                 FSRSReview {
                     rating: 1,
-                    delta_t: 0,
+                    delta_t: 0.0,
+                    duration_ms: None,
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 1,
+                    delta_t: 1.0,
+                    duration_ms: None,
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 3,
+                    delta_t: 3.0,
+                    duration_ms: None,
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 8,
+                    delta_t: 8.0,
+                    duration_ms: None,
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 21,
+                    delta_t: 21.0,
+                    duration_ms: None,
                 },
             ],
+            group: None,
         };
         let fsrs = FSRS::new(Some(WEIGHTS))?;
         assert_eq!(
@@ -346,7 +1222,8 @@ mod tests {
                     difficulty: 7.005062
                 }),
                 0.9,
-                21
+                21.0,
+                36500
             )
             .good
             .memory,
@@ -358,16 +1235,269 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn memory_state_batch() -> Result<()> {
+        let items = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(97)
+            .collect::<Vec<_>>();
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let states = fsrs.memory_state_batch(&items)?;
+        assert_eq!(states.len(), items.len());
+        for (item, state) in items.into_iter().zip(states) {
+            assert_eq!(fsrs.memory_state(item), state);
+        }
+        assert!(matches!(
+            fsrs.memory_state_batch(&[FSRSItem {
+                reviews: vec![],
+                group: None,
+            }]),
+            Err(FSRSError::InvalidItem {
+                index: 0,
+                reason: "no reviews"
+            })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn reschedule() -> Result<()> {
+        let items = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(97)
+            .collect::<Vec<_>>();
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let rescheduled = fsrs.reschedule(&items, 0.9, 100)?;
+        assert_eq!(rescheduled.len(), items.len());
+        for (item, card) in items.iter().zip(&rescheduled) {
+            let memory = fsrs.memory_state(item.clone());
+            assert_eq!(card.memory, memory);
+            assert_eq!(
+                card.interval,
+                fsrs.next_interval(Some(memory.stability), 0.9, 3).min(100)
+            );
+        }
+
+        assert!(matches!(
+            fsrs.reschedule(&items, 1.0, 100),
+            Err(FSRSError::InvalidInput {
+                field: "desired_retention"
+            })
+        ));
+        let empty = FSRSItem {
+            reviews: vec![],
+            group: None,
+        };
+        assert!(matches!(
+            fsrs.reschedule(&[items[0].clone(), empty], 0.9, 100),
+            Err(FSRSError::InvalidItem { index: 1, .. })
+        ));
+        let mut negative = items[0].clone();
+        negative.reviews[1].delta_t = -1.0;
+        assert!(matches!(
+            fsrs.reschedule(&[negative], 0.9, 100),
+            Err(FSRSError::InvalidItem {
+                index: 0,
+                reason: "delta_t negative or not finite"
+            })
+        ));
+        let first_review = FSRSItem {
+            reviews: items[0].reviews[..1].to_vec(),
+            group: None,
+        };
+        assert_eq!(fsrs.reschedule(&[first_review], 0.9, 100)?.len(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_next_interval() {
         let request_retentions = (1..=10).map(|i| i as f32 / 10.0).collect::<Vec<_>>();
         let intervals = request_retentions
             .iter()
-            .map(|r| next_interval(1.0, *r))
+            .map(|r| ForgettingCurve::default().rounded_interval(1.0, *r))
             .collect::<Vec<_>>();
         assert_eq!(intervals, [81, 36, 21, 14, 9, 6, 4, 2, 1, 1,]);
     }
 
+    #[test]
+    fn memory_state_retrievability() {
+        let state = MemoryState {
+            stability: 10.0,
+            difficulty: 5.0,
+        };
+        assert_eq!(state.retrievability(0.0), 1.0);
+        assert_eq!(state.retrievability(90.0), 0.5);
+        assert!((state.retrievability(10.0) - 0.9).abs() < 1e-6);
+        assert!(state.retrievability(20.0) < state.retrievability(10.0));
+    }
+
+    #[test]
+    fn memory_state_forgetting_curve() {
+        let state = MemoryState {
+            stability: 10.0,
+            difficulty: 5.0,
+        };
+        let curve = state.forgetting_curve(90.0, 10);
+        assert_eq!(curve.len(), 10);
+        assert_eq!(curve[0], (0.0, 1.0));
+        assert_eq!(curve[1].0, 10.0);
+        assert_eq!(curve[9], (90.0, 0.5));
+        assert!(curve.windows(2).all(|w| w[1].1 < w[0].1));
+        assert_eq!(state.forgetting_curve(90.0, 1), vec![(0.0, 1.0)]);
+        assert!(state.forgetting_curve(90.0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_retention_for_interval() {
+        assert_eq!(next_interval(10.0, 0.5), 90.0);
+        assert_eq!(retention_for_interval(10.0, 90.0), 0.5);
+        for stability in [0.5, 3.0, 42.0, 365.0] {
+            let interval = next_interval(stability, 0.9);
+            assert!((retention_for_interval(stability, interval) - 0.9).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn evaluate_other_models() -> Result<()> {
+        struct Constant;
+
+        impl MemoryModel for Constant {
+            fn initial_state(&self, _rating: u32) -> MemoryState {
+                MemoryState {
+                    stability: 1.0,
+                    difficulty: 1.0,
+                }
+            }
+
+            fn next_state(&self, state: MemoryState, _delta_t: f32, _rating: u32) -> MemoryState {
+                state
+            }
+
+            fn retrievability(&self, _state: MemoryState, _delta_t: f32) -> f32 {
+                0.9
+            }
+        }
+
+        let items = anki21_sample_file_converted_to_fsrs();
+        let fsrs = FSRS::new(Some(&[]))?.evaluate(items.clone(), |_| true)?;
+        let scalar = evaluate_model(&crate::ScalarFSRS::new(&[])?, &items)?;
+        assert!((scalar.log_loss - fsrs.log_loss).abs() < 1e-3);
+        assert!((scalar.rmse_bins - fsrs.rmse_bins).abs() < 1e-3);
+        let constant = evaluate_model(&Constant, &items)?;
+        assert_eq!(constant.calibration.len(), 1);
+        assert!(evaluate_model(&Constant, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn forgetting_curve() -> Result<()> {
+        assert_eq!(
+            ForgettingCurve::with_decay(DECAY),
+            ForgettingCurve::default()
+        );
+        let curve = ForgettingCurve::with_decay(-0.5);
+        assert!((curve.retention_for_interval(3.0, 3.0) - 0.9).abs() < 1e-6);
+        assert!(ForgettingCurve::with_decay(0.0).validate().is_err());
+
+        let fsrs = FSRS::new(Some(&[]))?;
+        let state = MemoryState {
+            stability: 10.0,
+            difficulty: 5.0,
+        };
+        let default_states = fsrs.next_states(Some(state), 0.8, 10.0, 36500);
+        let fsrs = fsrs.with_forgetting_curve(curve)?;
+        assert_eq!(fsrs.forgetting_curve(), curve);
+        let states = fsrs.next_states(Some(state), 0.8, 10.0, 36500);
+        assert!(states.good.interval > default_states.good.interval);
+        // retrievability is reported on the same curve as the intervals
+        let good = &states.good;
+        assert_eq!(
+            good.retrievability,
+            curve.retention_for_interval(good.memory.stability, good.interval as f32)
+        );
+        assert_eq!(
+            fsrs.current_retrievability(state, 20.0),
+            curve.retention_for_interval(10.0, 20.0)
+        );
+        assert_ne!(
+            fsrs.current_retrievability(state, 20.0),
+            state.retrievability(20.0)
+        );
+        let sm2_state = fsrs.memory_state_from_sm2(2.5, 10.0, 0.8);
+        assert!((fsrs.current_retrievability(sm2_state, 10.0) - 0.8).abs() < 1e-5);
+        assert_eq!(
+            fsrs.next_interval(Some(10.0), 0.9, 3),
+            FSRS::new(Some(&[]))?.next_interval(Some(10.0), 0.9, 3)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_calibration_bins() {
+        let bins = calibration_bins(&[0.95, 0.05, 0.96, 0.5], &[1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(bins.len(), 3);
+        assert_eq!(bins[0].count, 1);
+        assert_eq!(bins[0].actual, 0.0);
+        assert_eq!(bins[2].count, 2);
+        assert_eq!(bins[2].predicted, 0.955);
+        assert_eq!(bins[2].actual, 0.5);
+    }
+
+    #[test]
+    fn test_auc() {
+        assert_eq!(auc(&[0.1, 0.4, 0.35, 0.8], &[0.0, 0.0, 1.0, 1.0]), 0.75);
+        assert_eq!(auc(&[0.1, 0.2, 0.3], &[0.0, 1.0, 1.0]), 1.0);
+        assert_eq!(auc(&[0.5, 0.5], &[0.0, 1.0]), 0.5);
+        assert_eq!(auc(&[0.3, 0.6], &[1.0, 1.0]), 0.5);
+    }
+
+    #[test]
+    fn test_roc_curve() {
+        let pred = [0.1, 0.4, 0.35, 0.8, 0.4];
+        let true_val = [0.0, 0.0, 1.0, 1.0, 1.0];
+        let roc = roc_curve(&pred, &true_val);
+        let points: Vec<_> = roc
+            .iter()
+            .map(|p| (p.threshold, p.true_positive_rate, p.false_positive_rate))
+            .collect();
+        assert_eq!(
+            points,
+            [
+                (f32::INFINITY, 0.0, 0.0),
+                (0.8, 1.0 / 3.0, 0.0),
+                (0.4, 2.0 / 3.0, 0.5),
+                (0.35, 1.0, 0.5),
+                (0.1, 1.0, 1.0),
+            ]
+        );
+        let area: f32 = roc
+            .windows(2)
+            .map(|w| {
+                (w[1].false_positive_rate - w[0].false_positive_rate)
+                    * (w[1].true_positive_rate + w[0].true_positive_rate)
+                    / 2.0
+            })
+            .sum();
+        assert!((area - auc(&pred, &true_val)).abs() < 1e-6);
+        assert!(roc_curve(&[0.3, 0.6], &[1.0, 1.0]).is_empty());
+    }
+
+    #[test]
+    fn test_group_evaluations() {
+        let groups = group_evaluations([3, 1, 3].into_iter(), &[0.5, 0.9, 0.5], &[1.0, 0.0, 0.0]);
+        assert_eq!(groups.len(), 2);
+        assert_eq!((groups[0].key, groups[0].count), (1, 1));
+        assert_eq!((groups[1].key, groups[1].count), (3, 2));
+        assert_eq!(groups[1].log_loss, 2f32.ln());
+        assert_eq!(groups[1].rmse_bins, 0.0);
+    }
+
+    #[test]
+    fn test_brier_score() {
+        assert_eq!(brier_score(&[1.0, 0.0], &[1.0, 0.0]), 0.0);
+        assert_eq!(brier_score(&[0.5, 0.5], &[1.0, 0.0]), 0.25);
+    }
+
     #[test]
     fn test_evaluate() -> Result<()> {
         let items = anki21_sample_file_converted_to_fsrs();
@@ -377,6 +1507,43 @@ mod tests {
 
         Data::from([metrics.log_loss, metrics.rmse_bins])
             .assert_approx_eq(&Data::from([0.20944944, 0.042762663]), 5);
+        assert!(metrics.auc > 0.5 && metrics.auc < 1.0);
+        assert!(metrics.brier > 0.0 && metrics.brier < 0.25);
+        let by_first_rating = &metrics.by_first_rating;
+        assert!(by_first_rating.iter().all(|g| (1..=4).contains(&g.key)));
+        assert_eq!(
+            by_first_rating.iter().map(|g| g.count).sum::<usize>(),
+            items.len()
+        );
+        assert!(metrics.by_review_count[0].key >= 1);
+        for groups in [by_first_rating, &metrics.by_review_count] {
+            let weighted_log_loss = groups
+                .iter()
+                .map(|g| g.log_loss * g.count as f32)
+                .sum::<f32>()
+                / items.len() as f32;
+            assert!((weighted_log_loss - metrics.log_loss).abs() < 1e-4);
+        }
+        let predictions = fsrs.predict(&items)?;
+        assert_eq!(predictions.len(), items.len());
+        assert_eq!(
+            brier_score(
+                &predictions.iter().map(|p| p.0).collect::<Vec<_>>(),
+                &predictions.iter().map(|p| p.1).collect::<Vec<_>>()
+            ),
+            metrics.brier
+        );
+        let calibration = &metrics.calibration;
+        assert!(calibration
+            .windows(2)
+            .all(|w| w[0].predicted < w[1].predicted));
+        assert_eq!(
+            calibration.iter().map(|bin| bin.count).sum::<usize>(),
+            items
+                .iter()
+                .map(|item| item.reviews.len() - 1)
+                .sum::<usize>()
+        );
 
         let fsrs = FSRS::new(Some(WEIGHTS))?;
         let metrics = fsrs.evaluate(items, |_| true).unwrap();
@@ -386,39 +1553,119 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn evaluate_with_progress() -> Result<()> {
+        let items = anki21_sample_file_converted_to_fsrs();
+        let fsrs = FSRS::new(Some(&[]))?;
+
+        let progress = ProgressState::new_shared();
+        progress.lock().unwrap().want_abort = true;
+        assert!(matches!(
+            fsrs.evaluate_with_progress(items.clone(), progress),
+            Err(FSRSError::Interrupted)
+        ));
+
+        let progress = ProgressState::new_shared();
+        fsrs.evaluate_with_progress(items.clone(), progress.clone())?;
+        let info = progress.lock().unwrap();
+        assert_eq!(info.current(), items.len());
+        assert_eq!(info.total(), items.len());
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_by_group() -> Result<()> {
+        let items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .map(|item| FSRSItem {
+                group: Some(u64::from(item.reviews[0].rating == 1)),
+                ..item
+            })
+            .collect();
+        let fsrs = FSRS::new(Some(&[]))?;
+        let by_group = fsrs.evaluate_by_group(items.clone())?;
+        assert_eq!(by_group.keys().collect::<Vec<_>>(), [&Some(0), &Some(1)]);
+        let lapsed: Vec<_> = items
+            .into_iter()
+            .filter(|item| item.group == Some(1))
+            .collect();
+        assert_eq!(
+            by_group[&Some(1)].log_loss,
+            fsrs.evaluate(lapsed, |_| true)?.log_loss
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn universal_metrics() -> Result<()> {
+        let items = anki21_sample_file_converted_to_fsrs();
+        let fsrs = FSRS::new(Some(&[]))?;
+
+        // compared with itself, the universal metric is the ordinary calibration error
+        let comparison = fsrs.universal_metrics(items.clone(), DEFAULT_WEIGHTS)?;
+        assert_eq!(comparison.current_universal, comparison.current.rmse_bins);
+        assert_eq!(comparison.current_universal, comparison.other_universal);
+        assert!(!comparison.other_is_better());
+
+        let comparison = fsrs.universal_metrics(items, WEIGHTS)?;
+        assert!(comparison.other.log_loss < comparison.current.log_loss);
+        assert!(comparison.other_is_better());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn next_states_serde() -> Result<()> {
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let next_states = fsrs.next_states(None, 0.9, 0.0, 36500);
+        let json = serde_json::to_string(&next_states).unwrap();
+        assert_eq!(
+            serde_json::from_str::<NextStates>(&json).unwrap(),
+            next_states
+        );
+        Ok(())
+    }
+
     #[test]
     fn next_states() -> Result<()> {
         let item = FSRSItem {
             reviews: vec![
                 FSRSReview {
                     rating: 1,
-                    delta_t: 0,
+                    delta_t: 0.0,
+                    duration_ms: None,
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 1,
+                    delta_t: 1.0,
+                    duration_ms: None,
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 3,
+                    delta_t: 3.0,
+                    duration_ms: None,
                 },
                 FSRSReview {
                     rating: 3,
-                    delta_t: 8,
+                    delta_t: 8.0,
+                    duration_ms: None,
                 },
             ],
+            group: None,
         };
         let fsrs = FSRS::new(Some(WEIGHTS))?;
         let state = fsrs.memory_state(item);
         assert_eq!(
-            fsrs.next_states(Some(state), 0.9, 21),
+            fsrs.next_states(Some(state), 0.9, 21.0, 36500),
             NextStates {
                 again: ItemState {
                     memory: MemoryState {
                         stability: 4.5604353,
                         difficulty: 8.881129,
                     },
-                    interval: 5
+                    interval: 5,
+                    retrievability: 0.8914081,
+                    next_day_retrievability: 0.97621536,
                 },
                 hard: ItemState {
                     memory: MemoryState {
@@ -426,6 +1673,8 @@ mod tests {
                         difficulty: 7.9430957
                     },
                     interval: 26,
+                    retrievability: 0.90038353,
+                    next_day_retrievability: 0.99576277,
                 },
                 good: ItemState {
                     memory: MemoryState {
@@ -433,6 +1682,8 @@ mod tests {
                         difficulty: 7.005062
                     },
                     interval: 51,
+                    retrievability: 0.90060484,
+                    next_day_retrievability: 0.99784064,
                 },
                 easy: ItemState {
                     memory: MemoryState {
@@ -440,10 +1691,169 @@ mod tests {
                         difficulty: 6.0670285
                     },
                     interval: 121,
+                    retrievability: 0.90001154,
+                    next_day_retrievability: 0.9990827,
                 }
             }
         );
         assert_eq!(fsrs.next_interval(Some(121.01552), 0.9, 1), 121);
+        let capped = fsrs.next_states(Some(state), 0.9, 21.0, 100);
+        assert_eq!(capped.good.interval, 51);
+        assert_eq!(capped.easy.interval, 100);
+        assert_eq!(capped.easy.memory.stability, 121.01552);
+        assert!(capped.easy.retrievability > 0.9);
+        Ok(())
+    }
+
+    #[test]
+    fn weight_sensitivity() -> Result<()> {
+        let items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(10)
+            .collect();
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let sensitivity = fsrs.weight_sensitivity(items.clone(), 0.05)?;
+        assert_eq!(sensitivity.len(), 17);
+        assert_eq!(sensitivity[8].index, 8);
+        assert_eq!(sensitivity[0].name, "initial_stability_again");
+        assert!(sensitivity
+            .iter()
+            .all(|s| s.decreased.is_finite() && s.increased.is_finite()));
+        // the fitted weights are near a minimum of the loss for these items
+        assert!(sensitivity.iter().any(|s| s.max_increase() > 0.0));
+
+        assert!(matches!(
+            fsrs.weight_sensitivity(items, 0.0),
+            Err(FSRSError::InvalidInput { field: "step" })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn worst_fits() -> Result<()> {
+        let items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(10)
+            .collect();
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let worst = fsrs.worst_fits(&items, 5)?;
+        assert_eq!(worst.len(), 5);
+        assert!(worst.windows(2).all(|w| w[0].log_loss >= w[1].log_loss));
+        let predictions = fsrs.predict(&items)?;
+        for misprediction in &worst {
+            let (predicted, outcome) = predictions[misprediction.index];
+            assert_eq!(misprediction.predicted, predicted);
+            assert_eq!(misprediction.recalled, outcome == 1.0);
+        }
+        // surprising outcomes are forgetting a likely card, or recalling an unlikely one
+        assert!(worst.iter().all(|m| m.recalled != (m.predicted > 0.5)));
+        assert_eq!(fsrs.worst_fits(&items, items.len() + 1)?.len(), items.len());
+        Ok(())
+    }
+
+    #[test]
+    fn interval_multipliers() -> Result<()> {
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let state = MemoryState {
+            stability: 20.0,
+            difficulty: 5.0,
+        };
+        let plain = fsrs.next_states(Some(state), 0.9, 20.0, 36500);
+        let scaled = fsrs
+            .clone()
+            .with_interval_multipliers([1.0, 0.8, 1.0, 1.3])?;
+        assert_eq!(scaled.interval_multipliers(), [1.0, 0.8, 1.0, 1.3]);
+        let states = scaled.next_states(Some(state), 0.9, 20.0, 36500);
+        assert_eq!(states.again, plain.again);
+        assert_eq!(states.good, plain.good);
+        for (plain, scaled, multiplier) in [
+            (plain.hard, states.hard, 0.8),
+            (plain.easy, states.easy, 1.3),
+        ] {
+            assert_eq!(scaled.memory, plain.memory);
+            assert_eq!(
+                scaled.interval,
+                (next_interval(plain.memory.stability, 0.9) * multiplier).round() as u32
+            );
+        }
+        assert_eq!(
+            scaled.next_states_batch(&[(state, 0.9, 20.0)], 36500),
+            vec![states]
+        );
+        assert_eq!(
+            scaled.next_states(Some(state), 0.9, 20.0, 10).easy.interval,
+            10
+        );
+        for multipliers in [[1.0, 0.0, 1.0, 1.0], [1.0, 1.0, 1.0, f32::NAN]] {
+            assert!(matches!(
+                fsrs.clone().with_interval_multipliers(multipliers),
+                Err(FSRSError::InvalidInput {
+                    field: "interval_multipliers"
+                })
+            ));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn memory_state_history() -> Result<()> {
+        let item = FSRSItem {
+            reviews: vec![
+                FSRSReview {
+                    rating: 1,
+                    delta_t: 0.0,
+                    duration_ms: None,
+                },
+                FSRSReview {
+                    rating: 3,
+                    delta_t: 1.0,
+                    duration_ms: None,
+                },
+                FSRSReview {
+                    rating: 3,
+                    delta_t: 3.0,
+                    duration_ms: None,
+                },
+                FSRSReview {
+                    rating: 3,
+                    delta_t: 8.0,
+                    duration_ms: None,
+                },
+            ],
+            group: None,
+        };
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let history = fsrs.memory_state_history(&item);
+        assert_eq!(history.len(), 4);
+        assert_eq!(history[0].stability, WEIGHTS[0]);
+        assert!(history.windows(2).all(|w| w[1].stability > w[0].stability));
+        assert_eq!(history[3], fsrs.memory_state(item));
+        Ok(())
+    }
+
+    #[test]
+    fn next_states_batch() -> Result<()> {
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let mut cards: Vec<(MemoryState, f32, f32)> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(211)
+            .enumerate()
+            .map(|(i, item)| {
+                let elapsed = item.current().delta_t;
+                let retention = [0.8, 0.9, 0.95][i % 3];
+                (fsrs.memory_state(item), retention, elapsed)
+            })
+            .collect();
+        cards.push((cards[0].0, 0.9, 0.0));
+        let batch = fsrs.next_states_batch(&cards, 365);
+        assert_eq!(batch.len(), cards.len());
+        for (card, next_states) in cards.into_iter().zip(batch) {
+            assert_eq!(
+                next_states,
+                fsrs.next_states(Some(card.0), card.1, card.2, 365)
+            );
+        }
+        assert!(fsrs.next_states_batch(&[], 365).is_empty());
         Ok(())
     }
 
@@ -451,41 +1861,91 @@ mod tests {
     fn states_are_unchaged_when_no_days_elapsed() -> Result<()> {
         let fsrs = FSRS::new(Some(&[]))?;
         // the first time a card is seen, a memory state must be set
-        let mut state_a = fsrs.next_states(None, 1.0, 0).again.memory;
+        let mut state_a = fsrs.next_states(None, 1.0, 0.0, 36500).again.memory;
         // but if no days have elapsed and it's reviewed again, the state should be unchanged
-        let state_b = fsrs.next_states(Some(state_a), 1.0, 0).again.memory;
+        let state_b = fsrs
+            .next_states(Some(state_a), 1.0, 0.0, 36500)
+            .again
+            .memory;
         assert_eq!(state_a, state_b);
         // if a day elapses, it's counted
-        state_a = fsrs.next_states(Some(state_a), 1.0, 1).again.memory;
+        state_a = fsrs
+            .next_states(Some(state_a), 1.0, 1.0, 36500)
+            .again
+            .memory;
         assert_ne!(state_a, state_b);
 
         Ok(())
     }
 
+    #[test]
+    fn truncated_memory_state() -> Result<()> {
+        let fsrs = FSRS::new(Some(WEIGHTS))?;
+        let item = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .max_by_key(|item| item.reviews.len())
+            .unwrap();
+        let history = fsrs.memory_state_history(&item);
+        let full = *history.last().unwrap();
+        let len = item.reviews.len();
+        assert_eq!(fsrs.memory_state_truncated(&item, len, None), Some(full));
+        // a prefix state saved earlier gives the same result as replaying everything
+        let truncated = fsrs
+            .memory_state_truncated(&item, 3, Some(history[len - 4]))
+            .unwrap();
+        assert!((truncated.stability - full.stability).abs() < 1e-3);
+        assert!((truncated.difficulty - full.difficulty).abs() < 1e-3);
+        assert_ne!(fsrs.memory_state_truncated(&item, 3, None), Some(full));
+        Ok(())
+    }
+
+    #[test]
+    fn fractional_days_elapsed() -> Result<()> {
+        let fsrs = FSRS::new(Some(&[]))?;
+        let state = fsrs.next_states(None, 0.9, 0.0, 36500).good.memory;
+        let [early, on_time, late] = [2.5, 3.0, 3.5].map(|days_elapsed| {
+            fsrs.next_states(Some(state), 0.9, days_elapsed, 36500)
+                .good
+                .memory
+                .stability
+        });
+        assert!(early < on_time && on_time < late);
+        Ok(())
+    }
+
     #[test]
     fn memory_from_sm2() -> Result<()> {
         let fsrs = FSRS::new(Some(&[]))?;
         assert_eq!(
-            fsrs.memory_state_from_sm2(2.5, 10.0),
+            fsrs.memory_state_from_sm2(2.5, 10.0, 0.9),
             MemoryState {
-                stability: 10.0,
+                stability: 9.999995,
                 difficulty: 6.265295
             }
         );
         assert_eq!(
-            fsrs.memory_state_from_sm2(1.3, 20.0),
+            fsrs.memory_state_from_sm2(1.3, 20.0, 0.9),
             MemoryState {
-                stability: 20.0,
+                stability: 19.99999,
                 difficulty: 9.956561
             }
         );
+        // a lower SM-2 retention means the interval overshot the stability
+        assert_eq!(
+            fsrs.memory_state_from_sm2(2.5, 10.0, 0.8),
+            MemoryState {
+                stability: 4.4444447,
+                difficulty: 6.7734365
+            }
+        );
         let interval = 15;
         let ease_factor = 2.0;
         let fsrs_factor = fsrs
             .next_states(
-                Some(fsrs.memory_state_from_sm2(ease_factor, interval as f32)),
+                Some(fsrs.memory_state_from_sm2(ease_factor, interval as f32, 0.9)),
                 0.9,
-                interval,
+                interval as f32,
+                36500,
             )
             .good
             .memory
@@ -0,0 +1,96 @@
+use crate::error::Result;
+use crate::inference::{next_interval, Weights};
+use crate::memory_model::MemoryModel;
+use crate::scalar::ScalarFSRS;
+
+/// Learning step patterns commonly used in Anki, in minutes, that
+/// [recommend_learning_steps] chooses from.
+pub const COMMON_LEARNING_STEPS: &[&[u32]] = &[
+    &[1],
+    &[1, 10],
+    &[10],
+    &[1, 10, 60],
+    &[10, 60],
+    &[10, 1440],
+    &[1, 10, 1440],
+];
+
+const MINUTES_PER_DAY: f32 = 1440.0;
+
+/// Learning and relearning steps suited to a set of weights, as returned by
+/// [recommend_learning_steps]. Steps are in minutes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LearningStepsRecommendation {
+    pub learning: Vec<u32>,
+    /// The interval FSRS would give a new card that was forgotten at its first review. No
+    /// learning step should be longer, or the step delays the card past when it is due.
+    pub learning_limit: u32,
+    pub relearning: Vec<u32>,
+    /// The interval FSRS would give a card just after it lapsed. No relearning step should be
+    /// longer.
+    pub relearning_limit: u32,
+}
+
+/// Recommend learning and relearning steps for trained weights. Steps hold a card back from
+/// FSRS until they are done, so a step longer than the interval FSRS would schedule keeps the
+/// card from being reviewed when it is due, while steps far shorter than it add reviews that do
+/// little. Of the [COMMON_LEARNING_STEPS] whose last step fits within the interval after a
+/// first Again, and after a lapse of a card graduated with Good, the pattern with the longest
+/// last step and then the fewest steps is recommended.
+pub fn recommend_learning_steps(
+    weights: &Weights,
+    desired_retention: f32,
+) -> Result<LearningStepsRecommendation> {
+    let model = ScalarFSRS::new(weights)?;
+    let minutes =
+        |stability| (next_interval(stability, desired_retention) * MINUTES_PER_DAY).round() as u32;
+
+    let learning_limit = minutes(model.initial_state(1).stability);
+    let graduated = model.initial_state(3);
+    let lapse_delay = next_interval(graduated.stability, desired_retention);
+    let relearning_limit = minutes(model.next_state(graduated, lapse_delay, 1).stability);
+
+    Ok(LearningStepsRecommendation {
+        learning: steps_within(learning_limit),
+        learning_limit,
+        relearning: steps_within(relearning_limit),
+        relearning_limit,
+    })
+}
+
+fn steps_within(limit: u32) -> Vec<u32> {
+    COMMON_LEARNING_STEPS
+        .iter()
+        .filter(|steps| steps[steps.len() - 1] <= limit)
+        .max_by_key(|steps| (steps[steps.len() - 1], std::cmp::Reverse(steps.len())))
+        .unwrap_or(&COMMON_LEARNING_STEPS[0])
+        .to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DEFAULT_WEIGHTS;
+
+    #[test]
+    fn recommended_steps() -> Result<()> {
+        let steps = recommend_learning_steps(DEFAULT_WEIGHTS, 0.9)?;
+        // a first Again has a stability of 0.4 days
+        assert_eq!(steps.learning_limit, 576);
+        assert_eq!(steps.learning, [10, 60]);
+        assert!(steps.relearning_limit > 1440);
+        assert_eq!(steps.relearning, [10, 1440]);
+
+        let strict = recommend_learning_steps(DEFAULT_WEIGHTS, 0.99)?;
+        assert_eq!(strict.learning, [10]);
+        assert!(strict.relearning_limit < steps.relearning_limit);
+        assert!(strict
+            .relearning
+            .iter()
+            .all(|&s| s <= strict.relearning_limit));
+
+        assert_eq!(steps_within(0), [1]);
+        assert!(recommend_learning_steps(&[1.0], 0.9).is_err());
+        Ok(())
+    }
+}
@@ -1,25 +1,120 @@
 #![allow(clippy::single_range_in_vec_init)]
 
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
+#[cfg(feature = "arrow")]
+mod arrow_io;
+#[cfg(feature = "training")]
 mod batch_shuffle;
-#[cfg(test)]
+#[cfg(feature = "benchmark")]
+mod benchmark;
+#[cfg(all(test, feature = "training"))]
 mod convertor_tests;
+#[cfg(feature = "training")]
 mod cosine_annealing;
 mod dataset;
+#[cfg(feature = "training")]
+mod disk_dataset;
 mod error;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod fuzz;
 mod inference;
+#[cfg(feature = "inference")]
+mod learning_steps;
+mod memory_model;
+#[cfg(feature = "uniffi")]
+mod mobile;
+#[cfg(feature = "training")]
 mod model;
+#[cfg(feature = "training")]
+mod onnx;
+#[cfg(feature = "training")]
 mod optimal_retention;
+mod postpone;
+#[cfg(feature = "training")]
 mod pre_training;
-#[cfg(test)]
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "inference")]
+mod scalar;
+#[cfg(feature = "training")]
+mod significance;
+#[cfg(all(test, feature = "training"))]
 mod test_helpers;
+#[cfg(feature = "training")]
 mod training;
+mod true_retention;
+#[cfg(feature = "wasm")]
+mod wasm;
 mod weight_clipper;
 
-pub use dataset::{FSRSItem, FSRSReview};
+#[cfg(feature = "arrow")]
+pub use arrow_io::{
+    items_from_record_batches, items_to_record_batch, read_ipc, read_parquet, write_ipc,
+    write_parquet,
+};
+#[cfg(feature = "training")]
+pub use batch_shuffle::BatchShuffle;
+#[cfg(feature = "benchmark")]
+pub use benchmark::{run_benchmark, BenchmarkResult};
+pub use dataset::{
+    dedup_items, from_jsonl, group_items, summarize, to_benchmark_csv, to_jsonl, DatasetSummary,
+    FSRSItem, FSRSItemBuilder, FSRSReview, RatingMap, JSONL_VERSION,
+};
+#[cfg(feature = "training")]
+pub use dataset::{filter_outlier, FilterConfig, FilterResult, OutlierGroup, SameDayReviews};
 pub use error::{FSRSError, Result};
+pub use fuzz::{
+    fuzz_bounds, fuzzed_interval, interval_avoiding_days, load_balanced_interval, FuzzRange,
+    DEFAULT_FUZZ_RANGES,
+};
+#[cfg(feature = "training")]
+pub use inference::{
+    evaluate_model, roc_curve, CalibrationBin, GroupEvaluation, Misprediction, ModelComparison,
+    ModelEvaluation, RocPoint, WeightSensitivity,
+};
 pub use inference::{
-    ItemProgress, ItemState, MemoryState, ModelEvaluation, NextStates, DEFAULT_WEIGHTS,
+    next_interval, retention_for_interval, ForgettingCurve, ItemProgress, ItemState, MemoryState,
+    NextStates, DEFAULT_WEIGHTS,
 };
+#[cfg(feature = "inference")]
+pub use learning_steps::{
+    recommend_learning_steps, LearningStepsRecommendation, COMMON_LEARNING_STEPS,
+};
+pub use memory_model::MemoryModel;
+#[cfg(feature = "uniffi")]
+pub use mobile::{ProgressCallback, Scheduler};
+#[cfg(feature = "training")]
 pub use model::FSRS;
-pub use optimal_retention::SimulatorConfig;
-pub use training::ProgressState;
+#[cfg(feature = "training")]
+pub use optimal_retention::{
+    expected_workload, forecast_workload, retention_tradeoff, simulate, simulate_decks,
+    simulate_with_uncertainty, DailyBand, DeckConfig, ExistingCard, JointSimulationResult,
+    NewCardStage, RetentionObjective, ReviewDuration, ReviewOrder, SimulationBands,
+    SimulationResult, SimulatorConfig, SimulatorConfigBuilder, WorkloadSummary,
+};
+pub use postpone::{cards_to_advance, cards_to_postpone, CardMove, DueCard};
+#[cfg(feature = "training")]
+pub use pre_training::{
+    pretrain, pretrain_with_config, AverageRecall, PretrainConfig, PretrainGroup, PretrainResult,
+};
+#[cfg(feature = "inference")]
+pub use scalar::ScalarFSRS;
+#[cfg(feature = "training")]
+pub use significance::SignificanceTest;
+#[cfg(feature = "training")]
+pub use training::{
+    ComputeWeightsHandle, ComputedWeights, FineTuneConfig, FittedWeights, ProgressState,
+    TrainingEvent, TrainingOptions, UpdateConfig, WeightInterval, MIN_TRAINING_REVIEWS,
+};
+pub use true_retention::{
+    true_retention, RetentionCount, RetentionPeriod, RetentionStats, MATURE_DAYS,
+};
+#[cfg(feature = "wasm")]
+pub use wasm::{WasmFSRS, WasmItemState, WasmMemoryState, WasmNextStates};
+pub use weight_clipper::{
+    check_weights, compare_weights, migrate_weights, weight_info, WeightChange, WeightInfo,
+    WeightIssue,
+};
@@ -0,0 +1,38 @@
+//! A common interface to models of memory, so that alternatives to FSRS (such as HLR or ACT-R)
+//! can be benchmarked on the same review histories, with the same metrics.
+//!
+//! Only evaluation is generic over it, through [crate::evaluate_model]. Training and the
+//! simulator remain specific to FSRS: training fits the tensor model's weights with gradients,
+//! which a scalar interface can't provide, and the simulator schedules intervals by inverting
+//! the FSRS forgetting curve in double precision. An alternative model must be fitted with its
+//! own tooling before it is compared here.
+
+use crate::inference::MemoryState;
+
+/// How memory changes with each review, for evaluating a model with [crate::evaluate_model]. A
+/// model whose state isn't a stability and difficulty may store whatever it needs in the two
+/// fields of [MemoryState].
+pub trait MemoryModel {
+    /// The memory state after a card's first review.
+    fn initial_state(&self, rating: u32) -> MemoryState;
+
+    /// The memory state after a review `delta_t` days after the previous one.
+    fn next_state(&self, state: MemoryState, delta_t: f32, rating: u32) -> MemoryState;
+
+    /// The probability of recalling a card `delta_t` days after its last review.
+    fn retrievability(&self, state: MemoryState, delta_t: f32) -> f32;
+
+    /// The memory state after each of the given `(rating, delta_t)` reviews, or None if there
+    /// are none.
+    fn replay(&self, reviews: impl IntoIterator<Item = (u32, f32)>) -> Option<MemoryState>
+    where
+        Self: Sized,
+    {
+        let mut reviews = reviews.into_iter();
+        let (first_rating, _) = reviews.next()?;
+        Some(reviews.fold(
+            self.initial_state(first_rating),
+            |state, (rating, delta_t)| self.next_state(state, delta_t, rating),
+        ))
+    }
+}
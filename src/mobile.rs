@@ -0,0 +1,68 @@
+//! Kotlin and Swift bindings generated with UniFFI. Run the `uniffi-bindgen` binary against the
+//! built library to produce the foreign-language sources.
+
+use crate::{FSRSError, FSRSItem, MemoryState, NextStates, SimulatorConfig, TrainingEvent, FSRS};
+use std::sync::Arc;
+
+/// Receives training progress. Returning false aborts training.
+#[uniffi::export(callback_interface)]
+pub trait ProgressCallback: Send + Sync {
+    fn on_progress(&self, current: u64, total: u64) -> bool;
+}
+
+/// [FSRS], shared with the foreign side by reference.
+#[derive(uniffi::Object)]
+pub struct Scheduler {
+    inner: FSRS,
+}
+
+#[uniffi::export]
+impl Scheduler {
+    /// Weights may be empty to use the default values.
+    #[uniffi::constructor]
+    pub fn new(weights: Vec<f32>) -> Result<Arc<Self>, FSRSError> {
+        Ok(Arc::new(Self {
+            inner: FSRS::new(Some(&weights))?,
+        }))
+    }
+
+    pub fn memory_state(&self, item: FSRSItem) -> MemoryState {
+        self.inner.memory_state(item)
+    }
+
+    pub fn next_states(
+        &self,
+        current_memory_state: Option<MemoryState>,
+        desired_retention: f32,
+        days_elapsed: f32,
+        maximum_interval: u32,
+    ) -> NextStates {
+        self.inner.next_states(
+            current_memory_state,
+            desired_retention,
+            days_elapsed,
+            maximum_interval,
+        )
+    }
+
+    pub fn compute_weights(
+        &self,
+        items: Vec<FSRSItem>,
+        progress: Option<Box<dyn ProgressCallback>>,
+    ) -> Result<Vec<f32>, FSRSError> {
+        self.inner
+            .compute_weights_with_callback(items, move |event| match (&progress, event) {
+                (Some(progress), TrainingEvent::Progress(info)) => {
+                    progress.on_progress(info.current as u64, info.total as u64)
+                }
+                _ => true,
+            })
+    }
+
+    /// The desired retention that maximizes the number of cards remembered, using the default
+    /// simulator settings.
+    pub fn optimal_retention(&self, weights: Vec<f32>) -> Result<f64, FSRSError> {
+        self.inner
+            .optimal_retention(&SimulatorConfig::default(), &weights, |_| true)
+    }
+}
@@ -1,5 +1,6 @@
 use crate::error::{FSRSError, Result};
-use crate::inference::Weights;
+use crate::inference::{ForgettingCurve, Weights, DECAY, FACTOR};
+use crate::training::TrainingOptions;
 use crate::weight_clipper::clip_weights;
 use crate::DEFAULT_WEIGHTS;
 use burn::backend::ndarray::NdArrayDevice;
@@ -41,7 +42,12 @@ impl<B: Backend, const N: usize> Pow<B, N> for Tensor<B, N> {
 impl<B: Backend> Model<B> {
     #[allow(clippy::new_without_default)]
     pub fn new(config: ModelConfig) -> Self {
-        let initial_params = config
+        let short_term: &[f32] = if config.short_term {
+            &[0.52, 0.66]
+        } else {
+            &[]
+        };
+        let initial_params: Vec<f32> = config
             .initial_stability
             .unwrap_or([0.4, 0.6, 2.4, 5.8])
             .into_iter()
@@ -51,19 +57,29 @@ impl<B: Backend> Model<B> {
                 2.18, 0.05, 0.34, 1.26, // failure
                 0.29, 2.61, // hard penalty, easy bonus
             ])
+            .chain(short_term.iter().copied()) // same-day reviews
+            .chain(config.trainable_decay.then_some(-DECAY)) // decay
             .collect();
+        let len = initial_params.len();
 
         Self {
             w: Param::from(Tensor::from_floats(Data::new(
                 initial_params,
-                Shape { dims: [17] },
+                Shape { dims: [len] },
             ))),
             config,
         }
     }
 
     pub fn power_forgetting_curve(&self, t: Tensor<B, 1>, s: Tensor<B, 1>) -> Tensor<B, 1> {
-        (t / (s * 9) + 1).powf(-1.0)
+        if self.has_trainable_decay() {
+            // as in ForgettingCurve::with_decay, but differentiable
+            let decay = -self.w.get(self.w.dims()[0] - 1);
+            let factor = ((decay.clone().powf(-1.0) * 0.9f32.ln()).exp() - 1).powf(-1.0);
+            return (t / (s * factor) + 1).pow(decay);
+        }
+        let curve = self.config.curve();
+        (t / (s * curve.factor) + 1).powf(curve.decay)
     }
 
     fn stability_after_success(
@@ -101,6 +117,27 @@ impl<B: Backend> Model<B> {
             * ((-r + 1) * self.w.get(14)).exp()
     }
 
+    /// Stability after a review on the same day as the previous one. Only available when the
+    /// short-term weights (w17 and w18) were provided.
+    fn short_term_stability(
+        &self,
+        last_s: Tensor<B, 1>,
+        rating: Tensor<B, 1>,
+    ) -> Option<Tensor<B, 1>> {
+        if !self.has_short_term_weights() {
+            return None;
+        }
+        Some(last_s * ((rating - 3 + self.w.get(18)) * self.w.get(17)).exp())
+    }
+
+    pub(crate) fn has_short_term_weights(&self) -> bool {
+        self.w.dims()[0] - usize::from(self.has_trainable_decay()) == 19
+    }
+
+    pub(crate) fn has_trainable_decay(&self) -> bool {
+        self.config.trainable_decay
+    }
+
     fn mean_reversion(&self, new_d: Tensor<B, 1>) -> Tensor<B, 1> {
         self.w.get(7) * (self.w.get(4) - new_d.clone()) + new_d
     }
@@ -124,6 +161,8 @@ impl<B: Backend> Model<B> {
         state: Option<MemoryStateTensors<B>>,
     ) -> MemoryStateTensors<B> {
         let (new_s, new_d) = if let Some(state) = state {
+            // as in is_same_day
+            let same_day = delta_t.clone().lower_elem(1.0);
             let retention = self.power_forgetting_curve(delta_t, state.stability.clone());
             let mut new_difficulty = self.next_difficulty(state.difficulty.clone(), rating.clone());
             new_difficulty = self.mean_reversion(new_difficulty).clamp(1.0, 10.0);
@@ -140,6 +179,11 @@ impl<B: Backend> Model<B> {
             );
             let mut new_stability = stability_after_success
                 .mask_where(rating.clone().equal_elem(1), stability_after_failure);
+            if let Some(short_term_stability) =
+                self.short_term_stability(state.stability.clone(), rating.clone())
+            {
+                new_stability = new_stability.mask_where(same_day, short_term_stability);
+            }
             // mask padding zeros for rating
             new_stability = new_stability.mask_where(rating.clone().equal_elem(0), state.stability);
             new_difficulty = new_difficulty.mask_where(rating.equal_elem(0), state.difficulty);
@@ -185,9 +229,38 @@ pub struct ModelConfig {
     #[config(default = false)]
     pub freeze_stability: bool,
     pub initial_stability: Option<[f32; 4]>,
+    /// Add the two short-term weights, so that same-day reviews are modelled.
+    #[config(default = false)]
+    pub short_term: bool,
+    /// Add a final weight for the forgetting curve's decay, so that it is trained too.
+    #[config(default = false)]
+    pub trainable_decay: bool,
+    /// The shape of the forgetting curve, if not the default; see [ForgettingCurve].
+    pub decay: Option<f32>,
+    pub factor: Option<f32>,
+    /// Weights that training is pulled towards, by adding `prior_strength` times the squared
+    /// distance from them to each batch's loss.
+    pub prior: Option<Vec<f32>>,
+    #[config(default = 0.0)]
+    pub prior_strength: f32,
 }
 
 impl ModelConfig {
+    pub(crate) fn with_curve(self, curve: ForgettingCurve) -> Self {
+        Self {
+            decay: Some(curve.decay),
+            factor: Some(curve.factor),
+            ..self
+        }
+    }
+
+    pub(crate) fn curve(&self) -> ForgettingCurve {
+        ForgettingCurve {
+            decay: self.decay.unwrap_or(DECAY),
+            factor: self.factor.unwrap_or(FACTOR),
+        }
+    }
+
     pub fn init<B: Backend>(&self) -> Model<B> {
         Model::new(self.clone())
     }
@@ -198,6 +271,9 @@ impl ModelConfig {
 #[derive(Debug, Clone)]
 pub struct FSRS<B: Backend = NdArrayBackend> {
     model: Option<Model<B>>,
+    curve: ForgettingCurve,
+    interval_multipliers: [f32; 4],
+    training_options: TrainingOptions,
     device: B::Device,
 }
 
@@ -207,43 +283,157 @@ impl FSRS<NdArrayBackend> {
     pub fn new(weights: Option<&Weights>) -> Result<Self> {
         Self::new_with_backend(weights, NdArrayDevice::Cpu)
     }
+
+    /// Like [FSRS::new], but the weights end with one more, the magnitude of the forgetting
+    /// curve's decay, which then defines the curve, and which [FSRS::compute_weights] trains
+    /// along with the others. An empty slice uses the default weights and decay.
+    pub fn new_with_trainable_decay(weights: Option<&Weights>) -> Result<Self> {
+        Self::from_weights(weights, true, NdArrayDevice::Cpu)
+    }
 }
 
 impl<B: Backend> FSRS<B> {
     pub fn new_with_backend<B2: Backend>(
-        mut weights: Option<&Weights>,
+        weights: Option<&Weights>,
         device: B2::Device,
     ) -> Result<FSRS<B2>> {
-        if let Some(weights) = &mut weights {
-            if weights.is_empty() {
-                *weights = DEFAULT_WEIGHTS
-            } else if weights.len() != 17 {
-                return Err(FSRSError::InvalidWeights);
+        Self::from_weights(weights, false, device)
+    }
+
+    pub(crate) fn from_weights<B2: Backend>(
+        weights: Option<&Weights>,
+        trainable_decay: bool,
+        device: B2::Device,
+    ) -> Result<FSRS<B2>> {
+        let default_weights: Vec<f32> = DEFAULT_WEIGHTS
+            .iter()
+            .copied()
+            .chain(trainable_decay.then_some(-DECAY))
+            .collect();
+        let weights = match weights {
+            Some([]) => Some(default_weights.as_slice()),
+            Some(weights) if matches!(weights.len() - usize::from(trainable_decay), 17 | 19) => {
+                Some(weights)
             }
+            Some(_) => return Err(FSRSError::InvalidWeights),
+            None => None,
+        };
+        let config = ModelConfig {
+            trainable_decay,
+            ..Default::default()
         }
+        .with_curve(ForgettingCurve::default());
+        let model = weights.map(|weights| weights_to_model(weights, config));
+        let curve = match &model {
+            Some(model) if model.has_trainable_decay() => {
+                let weights: Vec<f32> = model.w.val().to_data().convert().value;
+                ForgettingCurve::with_decay(-weights[weights.len() - 1])
+            }
+            _ => ForgettingCurve::default(),
+        };
         Ok(FSRS {
-            model: weights.map(weights_to_model),
+            model,
+            curve,
+            interval_multipliers: [1.0; 4],
+            training_options: TrainingOptions::default(),
             device,
         })
     }
 
+    /// Use a different forgetting curve from the default, for scheduling, evaluation and
+    /// training alike. Weights trained with one curve should not be used with another, and
+    /// weights that include a trained decay already define their curve.
+    pub fn with_forgetting_curve(mut self, curve: ForgettingCurve) -> Result<Self> {
+        curve.validate()?;
+        if self.has_trainable_decay() {
+            return Err(FSRSError::InvalidInput { field: "decay" });
+        }
+        self.curve = curve;
+        if let Some(model) = &mut self.model {
+            model.config = model.config.clone().with_curve(curve);
+        }
+        Ok(self)
+    }
+
+    pub fn forgetting_curve(&self) -> ForgettingCurve {
+        self.curve
+    }
+
+    /// Scale the interval scheduled for each rating, from Again to Easy, by [FSRS::next_states]
+    /// and [FSRS::next_states_batch]. The scaling is applied after the interval is computed
+    /// from the desired retention, and leaves the memory states unchanged, so that an Easy bonus
+    /// or Hard interval familiar from SM-2 can be kept. For example, `[1.0, 0.8, 1.0, 1.3]`
+    /// shortens Hard intervals by 20% and lengthens Easy intervals by 30%. Each multiplier must
+    /// be positive; the default is 1.0 for every rating.
+    pub fn with_interval_multipliers(mut self, multipliers: [f32; 4]) -> Result<Self> {
+        if multipliers.iter().any(|m| !m.is_finite() || *m <= 0.0) {
+            return Err(FSRSError::InvalidInput {
+                field: "interval_multipliers",
+            });
+        }
+        self.interval_multipliers = multipliers;
+        Ok(self)
+    }
+
+    pub fn interval_multipliers(&self) -> [f32; 4] {
+        self.interval_multipliers
+    }
+
+    /// Change how weights are trained by later calls, such as [FSRS::compute_weights].
+    pub fn with_training_options(mut self, options: TrainingOptions) -> Self {
+        self.training_options = options;
+        self
+    }
+
+    pub fn training_options(&self) -> &TrainingOptions {
+        &self.training_options
+    }
+
     pub(crate) fn model(&self) -> &Model<B> {
         self.model
             .as_ref()
             .expect("command requires weights to be set on creation")
     }
 
+    /// Whether this was created with the short-term weights for same-day reviews.
+    pub(crate) fn has_short_term_weights(&self) -> bool {
+        self.model
+            .as_ref()
+            .map_or(false, Model::has_short_term_weights)
+    }
+
+    /// An instance with other weights in the same layout as this one's, and the same forgetting
+    /// curve, unless the weights end with a trainable decay that defines their own.
+    pub(crate) fn with_other_weights(&self, weights: &Weights) -> Result<Self> {
+        let fsrs =
+            Self::from_weights::<B>(Some(weights), self.has_trainable_decay(), self.device())?;
+        if fsrs.has_trainable_decay() {
+            Ok(fsrs)
+        } else {
+            fsrs.with_forgetting_curve(self.forgetting_curve())
+        }
+    }
+
+    /// Whether this was created with [FSRS::new_with_trainable_decay].
+    pub(crate) fn has_trainable_decay(&self) -> bool {
+        self.model
+            .as_ref()
+            .map_or(false, Model::has_trainable_decay)
+    }
+
     pub(crate) fn device(&self) -> B::Device {
         self.device.clone()
     }
 }
 
-pub(crate) fn weights_to_model<B: Backend>(weights: &Weights) -> Model<B> {
-    let config = ModelConfig::default();
+pub(crate) fn weights_to_model<B: Backend>(weights: &Weights, config: ModelConfig) -> Model<B> {
+    let clipped = clip_weights(weights, config.trainable_decay);
     let mut model = Model::<B>::new(config);
     model.w = Param::from(Tensor::from_floats(Data::new(
-        clip_weights(weights),
-        Shape { dims: [17] },
+        clipped,
+        Shape {
+            dims: [weights.len()],
+        },
     )));
     model
 }
@@ -252,6 +442,7 @@ pub(crate) fn weights_to_model<B: Backend>(weights: &Weights) -> Model<B> {
 mod tests {
     use super::*;
     use crate::test_helpers::{Model, Tensor};
+    use burn::backend::NdArrayAutodiffBackend;
     use burn::tensor::Data;
 
     #[test]
@@ -366,10 +557,107 @@ mod tests {
         )
     }
 
+    #[test]
+    fn trainable_decay() {
+        let model = Model::new(ModelConfig {
+            trainable_decay: true,
+            ..Default::default()
+        });
+        assert_eq!(model.w.dims(), [18]);
+        assert!(model.has_trainable_decay());
+        assert!(!model.has_short_term_weights());
+        // the initial decay matches the default curve
+        let delta_t = Tensor::from_floats([0.0, 1.0, 5.0, 30.0]);
+        let stability = Tensor::from_floats([1.0, 2.0, 5.0, 10.0]);
+        let trained = model.power_forgetting_curve(delta_t.clone(), stability.clone());
+        let fixed = Model::new(ModelConfig::default()).power_forgetting_curve(delta_t, stability);
+        for (trained, fixed) in trained.to_data().value.iter().zip(fixed.to_data().value) {
+            assert!((trained - fixed).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn short_term_weights() {
+        let model = Model::new(ModelConfig {
+            short_term: true,
+            ..Default::default()
+        });
+        assert_eq!(model.w.dims(), [19]);
+        assert!(model.has_short_term_weights());
+    }
+
+    #[test]
+    fn short_term_stability() {
+        let model = Model::new(ModelConfig::default());
+        assert!(model
+            .short_term_stability(Tensor::from_floats([5.0]), Tensor::from_floats([3.0]))
+            .is_none());
+
+        let weights: Vec<f32> = DEFAULT_WEIGHTS.iter().copied().chain([0.5, 0.3]).collect();
+        let model = weights_to_model::<NdArrayAutodiffBackend>(&weights, ModelConfig::default());
+        let stability = Tensor::from_floats([5.0; 4]);
+        let rating = Tensor::from_floats([1.0, 2.0, 3.0, 4.0]);
+        let short_term = model
+            .short_term_stability(stability.clone(), rating.clone())
+            .unwrap();
+        assert_eq!(
+            short_term.to_data(),
+            Data::from([2.1370747, 3.5234404, 5.809171, 9.577704])
+        );
+        // same-day reviews use the short-term stability, later ones are unaffected
+        let state = MemoryStateTensors {
+            stability,
+            difficulty: Tensor::from_floats([5.0; 4]),
+        };
+        let same_day = model.step(
+            Tensor::from_floats([0.0; 4]),
+            rating.clone(),
+            Some(state.clone()),
+        );
+        assert_eq!(same_day.stability.to_data(), short_term.to_data());
+        let hours_later = model.step(
+            Tensor::from_floats([0.3; 4]),
+            rating.clone(),
+            Some(state.clone()),
+        );
+        assert_eq!(hours_later.stability.to_data(), short_term.to_data());
+        let next_day = model.step(
+            Tensor::from_floats([1.0; 4]),
+            rating.clone(),
+            Some(state.clone()),
+        );
+        let next_day_without_short_term = Model::new(ModelConfig::default()).step(
+            Tensor::from_floats([1.0; 4]),
+            rating,
+            Some(state),
+        );
+        assert_eq!(
+            next_day.stability.to_data(),
+            next_day_without_short_term.stability.to_data()
+        );
+    }
+
     #[test]
     fn fsrs() {
         assert!(FSRS::new(Some(&[])).is_ok());
         assert!(FSRS::new(Some(&[1.])).is_err());
         assert!(FSRS::new(Some(DEFAULT_WEIGHTS)).is_ok());
+        let weights: Vec<f32> = DEFAULT_WEIGHTS.iter().copied().chain([0.5, 0.3]).collect();
+        assert!(FSRS::new(Some(&weights)).is_ok());
+        assert!(FSRS::new(Some(&weights[..18])).is_err());
+
+        // a trainable decay is asked for explicitly, rather than guessed from the length
+        assert!(FSRS::new_with_trainable_decay(Some(DEFAULT_WEIGHTS)).is_err());
+        let with_decay: Vec<f32> = DEFAULT_WEIGHTS.iter().copied().chain([0.5]).collect();
+        let fsrs = FSRS::new_with_trainable_decay(Some(&with_decay)).unwrap();
+        assert!(fsrs.has_trainable_decay());
+        assert!(!fsrs.has_short_term_weights());
+        assert_eq!(fsrs.forgetting_curve(), ForgettingCurve::with_decay(-0.5));
+        let with_decay: Vec<f32> = weights.iter().copied().chain([0.5]).collect();
+        let fsrs = FSRS::new_with_trainable_decay(Some(&with_decay)).unwrap();
+        assert!(fsrs.has_trainable_decay());
+        assert!(fsrs.has_short_term_weights());
+        let fsrs = FSRS::new_with_trainable_decay(Some(&[])).unwrap();
+        assert_eq!(fsrs.forgetting_curve(), ForgettingCurve::default());
     }
 }
@@ -0,0 +1,330 @@
+//! Exports a single review step of the model as an ONNX graph, so trained weights can be used
+//! from runtimes that can't link to this crate. The protobuf messages are written by hand, as only
+//! a handful of ONNX fields are needed.
+
+use crate::inference::ForgettingCurve;
+use crate::FSRS;
+use burn::tensor::backend::Backend;
+
+const IR_VERSION: u64 = 8;
+const OPSET_VERSION: u64 = 13;
+const FLOAT: u64 = 1;
+const INT64: u64 = 7;
+const ATTRIBUTE_INT: u64 = 2;
+
+impl<B: Backend> FSRS<B> {
+    /// Serialize the model as an ONNX graph. The graph takes `delta_t`, `rating`, `stability`
+    /// and `difficulty` inputs of shape `[batch]`, and returns `new_stability`, `new_difficulty`
+    /// and `retrievability` (the probability of recall before the review). A stability of 0
+    /// marks a card's first review. Retrievability follows [FSRS::forgetting_curve].
+    pub fn export_onnx(&self) -> Vec<u8> {
+        let weights: Vec<f32> = self.model().w.val().to_data().convert().value;
+        step_graph(&weights, self.forgetting_curve()).into_model()
+    }
+}
+
+fn step_graph(w: &[f32], curve: ForgettingCurve) -> GraphBuilder {
+    let mut g = GraphBuilder::default();
+    let (t, rating, s, d) = ("delta_t", "rating", "stability", "difficulty");
+    let one = g.constant(1.0);
+    let rating_offset = {
+        let three = g.constant(3.0);
+        g.op("Sub", &[rating, &three])
+    };
+
+    // forgetting curve
+    let factor = g.constant(curve.factor);
+    let decay = g.constant(curve.decay);
+    let scaled = g.op("Mul", &[s, &factor]);
+    let ratio = g.op("Div", &[t, &scaled]);
+    let base = g.op("Add", &[&ratio, &one]);
+    let r = g.op("Pow", &[&base, &decay]);
+    let lapse_prob = g.op("Sub", &[&one, &r]);
+
+    // difficulty
+    let w6 = g.constant(w[6]);
+    let delta_d = g.op("Mul", &[&w6, &rating_offset]);
+    let next_d = g.op("Sub", &[d, &delta_d]);
+    let w4 = g.constant(w[4]);
+    let w7 = g.constant(w[7]);
+    let reverted = g.op("Sub", &[&w4, &next_d]);
+    let reverted = g.op("Mul", &[&w7, &reverted]);
+    let reverted = g.op("Add", &[&reverted, &next_d]);
+    let review_d = g.clip(&reverted, 1.0, 10.0);
+
+    // stability after success
+    let hard_penalty = g.where_rating(rating, 2.0, w[15]);
+    let easy_bonus = g.where_rating(rating, 4.0, w[16]);
+    let exp_w8 = g.constant(w[8].exp());
+    let eleven = g.constant(11.0);
+    let neg_w9 = g.constant(-w[9]);
+    let w10 = g.constant(w[10]);
+    let inv_d = g.op("Sub", &[&eleven, &review_d]);
+    let s_pow = g.op("Pow", &[s, &neg_w9]);
+    let r_term = g.op("Mul", &[&lapse_prob, &w10]);
+    let r_term = g.op("Exp", &[&r_term]);
+    let r_term = g.op("Sub", &[&r_term, &one]);
+    let increase = g.op("Mul", &[&exp_w8, &inv_d]);
+    let increase = g.op("Mul", &[&increase, &s_pow]);
+    let increase = g.op("Mul", &[&increase, &r_term]);
+    let increase = g.op("Mul", &[&increase, &hard_penalty]);
+    let increase = g.op("Mul", &[&increase, &easy_bonus]);
+    let increase = g.op("Add", &[&increase, &one]);
+    let success_s = g.op("Mul", &[s, &increase]);
+
+    // stability after failure
+    let w11 = g.constant(w[11]);
+    let neg_w12 = g.constant(-w[12]);
+    let w13 = g.constant(w[13]);
+    let w14 = g.constant(w[14]);
+    let d_pow = g.op("Pow", &[&review_d, &neg_w12]);
+    let s_plus_one = g.op("Add", &[s, &one]);
+    let s_term = g.op("Pow", &[&s_plus_one, &w13]);
+    let s_term = g.op("Sub", &[&s_term, &one]);
+    let r_term = g.op("Mul", &[&lapse_prob, &w14]);
+    let r_term = g.op("Exp", &[&r_term]);
+    let failure_s = g.op("Mul", &[&w11, &d_pow]);
+    let failure_s = g.op("Mul", &[&failure_s, &s_term]);
+    let failure_s = g.op("Mul", &[&failure_s, &r_term]);
+
+    let again = g.equal_const(rating, 1.0);
+    let mut review_s = g.op("Where", &[&again, &failure_s, &success_s]);
+    if w.len() >= 19 {
+        let w17 = g.constant(w[17]);
+        let w18 = g.constant(w[18]);
+        let exponent = g.op("Add", &[&rating_offset, &w18]);
+        let exponent = g.op("Mul", &[&exponent, &w17]);
+        let factor = g.op("Exp", &[&exponent]);
+        let short_term_s = g.op("Mul", &[s, &factor]);
+        // as in is_same_day
+        let same_day = g.less_const(t, 1.0);
+        review_s = g.op("Where", &[&same_day, &short_term_s, &review_s]);
+    }
+
+    // first review
+    let initial = g.tensor(&w[..4]);
+    let index = {
+        let index = g.op("Sub", &[rating, &one]);
+        g.cast(&index, INT64)
+    };
+    let init_s = g.op("Gather", &[&initial, &index]);
+    let w5 = g.constant(w[5]);
+    let init_d = g.op("Mul", &[&w5, &rating_offset]);
+    let init_d = g.op("Sub", &[&w4, &init_d]);
+    let init_d = g.clip(&init_d, 1.0, 10.0);
+
+    let is_new = g.equal_const(s, 0.0);
+    let new_s = g.op("Where", &[&is_new, &init_s, &review_s]);
+    let new_s = g.clip(&new_s, 0.1, 36500.0);
+    let new_d = g.op("Where", &[&is_new, &init_d, &review_d]);
+    g.identity(&new_s, "new_stability");
+    g.identity(&new_d, "new_difficulty");
+    g.identity(&r, "retrievability");
+    g
+}
+
+#[derive(Default)]
+struct GraphBuilder {
+    nodes: Vec<Vec<u8>>,
+    initializers: Vec<Vec<u8>>,
+    next_id: usize,
+}
+
+impl GraphBuilder {
+    fn name(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{prefix}{}", self.next_id)
+    }
+
+    fn node(&mut self, op_type: &str, inputs: &[&str], output: &str, attribute: Option<Vec<u8>>) {
+        let mut node = vec![];
+        for input in inputs {
+            write_bytes(&mut node, 1, input.as_bytes());
+        }
+        write_bytes(&mut node, 2, output.as_bytes());
+        write_bytes(&mut node, 3, output.as_bytes());
+        write_bytes(&mut node, 4, op_type.as_bytes());
+        if let Some(attribute) = attribute {
+            write_bytes(&mut node, 5, &attribute);
+        }
+        self.nodes.push(node);
+    }
+
+    fn op(&mut self, op_type: &str, inputs: &[&str]) -> String {
+        let output = self.name("n");
+        self.node(op_type, inputs, &output, None);
+        output
+    }
+
+    fn identity(&mut self, input: &str, output: &str) {
+        self.node("Identity", &[input], output, None);
+    }
+
+    fn cast(&mut self, input: &str, to: u64) -> String {
+        let mut attribute = vec![];
+        write_bytes(&mut attribute, 1, b"to");
+        write_varint_field(&mut attribute, 3, to);
+        write_varint_field(&mut attribute, 20, ATTRIBUTE_INT);
+        let output = self.name("n");
+        self.node("Cast", &[input], &output, Some(attribute));
+        output
+    }
+
+    fn initializer(&mut self, dims: &[u64], values: &[f32]) -> String {
+        let name = self.name("c");
+        let mut tensor = vec![];
+        for &dim in dims {
+            write_varint_field(&mut tensor, 1, dim);
+        }
+        write_varint_field(&mut tensor, 2, FLOAT);
+        write_bytes(&mut tensor, 8, name.as_bytes());
+        let raw: Vec<u8> = values.iter().flat_map(|v| v.to_le_bytes()).collect();
+        write_bytes(&mut tensor, 9, &raw);
+        self.initializers.push(tensor);
+        name
+    }
+
+    /// A scalar, broadcast against the `[batch]` inputs.
+    fn constant(&mut self, value: f32) -> String {
+        self.initializer(&[], &[value])
+    }
+
+    fn tensor(&mut self, values: &[f32]) -> String {
+        self.initializer(&[values.len() as u64], values)
+    }
+
+    fn clip(&mut self, input: &str, min: f32, max: f32) -> String {
+        let min = self.constant(min);
+        let max = self.constant(max);
+        self.op("Clip", &[input, &min, &max])
+    }
+
+    fn equal_const(&mut self, input: &str, value: f32) -> String {
+        let value = self.constant(value);
+        self.op("Equal", &[input, &value])
+    }
+
+    fn less_const(&mut self, input: &str, value: f32) -> String {
+        let value = self.constant(value);
+        self.op("Less", &[input, &value])
+    }
+
+    /// `value` where the rating matches, otherwise 1.
+    fn where_rating(&mut self, rating: &str, matching: f32, value: f32) -> String {
+        let condition = self.equal_const(rating, matching);
+        let value = self.constant(value);
+        let one = self.constant(1.0);
+        self.op("Where", &[&condition, &value, &one])
+    }
+
+    fn into_model(self) -> Vec<u8> {
+        let mut graph = vec![];
+        for node in &self.nodes {
+            write_bytes(&mut graph, 1, node);
+        }
+        write_bytes(&mut graph, 2, b"fsrs_step");
+        for initializer in &self.initializers {
+            write_bytes(&mut graph, 5, initializer);
+        }
+        for input in ["delta_t", "rating", "stability", "difficulty"] {
+            write_bytes(&mut graph, 11, &value_info(input));
+        }
+        for output in ["new_stability", "new_difficulty", "retrievability"] {
+            write_bytes(&mut graph, 12, &value_info(output));
+        }
+
+        let mut opset = vec![];
+        write_varint_field(&mut opset, 2, OPSET_VERSION);
+
+        let mut model = vec![];
+        write_varint_field(&mut model, 1, IR_VERSION);
+        write_bytes(&mut model, 2, b"fsrs-rs");
+        write_bytes(&mut model, 3, env!("CARGO_PKG_VERSION").as_bytes());
+        write_bytes(&mut model, 7, &graph);
+        write_bytes(&mut model, 8, &opset);
+        model
+    }
+}
+
+/// A float tensor of shape `[batch]`.
+fn value_info(name: &str) -> Vec<u8> {
+    let mut dim = vec![];
+    write_bytes(&mut dim, 2, b"batch");
+    let mut shape = vec![];
+    write_bytes(&mut shape, 1, &dim);
+    let mut tensor_type = vec![];
+    write_varint_field(&mut tensor_type, 1, FLOAT);
+    write_bytes(&mut tensor_type, 2, &shape);
+    let mut type_proto = vec![];
+    write_bytes(&mut type_proto, 1, &tensor_type);
+    let mut info = vec![];
+    write_bytes(&mut info, 1, name.as_bytes());
+    write_bytes(&mut info, 2, &type_proto);
+    info
+}
+
+fn write_varint(buf: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        buf.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    buf.push(value as u8);
+}
+
+fn write_varint_field(buf: &mut Vec<u8>, field: u64, value: u64) {
+    write_varint(buf, field << 3);
+    write_varint(buf, value);
+}
+
+fn write_bytes(buf: &mut Vec<u8>, field: u64, bytes: &[u8]) {
+    write_varint(buf, field << 3 | 2);
+    write_varint(buf, bytes.len() as u64);
+    buf.extend_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Result, DEFAULT_WEIGHTS};
+
+    #[test]
+    fn varint() {
+        let mut buf = vec![];
+        write_varint(&mut buf, 1);
+        write_varint(&mut buf, 300);
+        assert_eq!(buf, [0x01, 0xac, 0x02]);
+    }
+
+    #[test]
+    fn export() -> Result<()> {
+        let model = FSRS::new(Some(&[]))?.export_onnx();
+        // ir_version, then producer_name
+        assert_eq!(&model[..4], &[0x08, 0x08, 0x12, 0x07]);
+        assert_eq!(&model[4..11], b"fsrs-rs");
+
+        let long_term = step_graph(DEFAULT_WEIGHTS, ForgettingCurve::default());
+        let short_term = step_graph(
+            &[DEFAULT_WEIGHTS, &[0.5, 0.3]].concat(),
+            ForgettingCurve::default(),
+        );
+        assert!(short_term.nodes.len() > long_term.nodes.len());
+        assert_eq!(
+            long_term.nodes.len(),
+            long_term.next_id - long_term.initializers.len() + 3
+        );
+
+        let curve = ForgettingCurve::with_decay(-0.5);
+        let has_constant = |graph: &GraphBuilder, value: f32| {
+            graph
+                .initializers
+                .iter()
+                .any(|tensor| tensor.ends_with(&value.to_le_bytes()))
+        };
+        assert!(has_constant(
+            &step_graph(DEFAULT_WEIGHTS, curve),
+            curve.factor
+        ));
+        assert!(!has_constant(&long_term, curve.factor));
+        Ok(())
+    }
+}
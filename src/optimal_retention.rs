@@ -1,16 +1,22 @@
+use crate::dataset::is_same_day;
 use crate::error::{FSRSError, Result};
-use crate::inference::{ItemProgress, Weights};
-use crate::{DEFAULT_WEIGHTS, FSRS};
+use crate::inference::{ItemProgress, MemoryState, Weights, DECAY, FACTOR};
+use crate::training::ProgressState;
+use crate::{FSRSItem, FSRSReview, DEFAULT_WEIGHTS, FSRS};
 use burn::tensor::backend::Backend;
-use itertools::{izip, Itertools};
+use itertools::izip;
 use ndarray::{s, Array1, Array2, Ix0, Ix1, SliceInfoElem, Zip};
 use ndarray_rand::rand_distr::Distribution;
 use ndarray_rand::RandomExt;
 use rand::{
     distributions::{Uniform, WeightedIndex},
     rngs::StdRng,
+    seq::SliceRandom,
     SeedableRng,
 };
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::sync::{Arc, Mutex};
 use strum::EnumCount;
 
 #[derive(Debug, EnumCount)]
@@ -28,6 +34,7 @@ enum Column {
     Cost,
     #[allow(unused)]
     Rand,
+    Lapses,
 }
 
 impl ndarray::SliceNextDim for Column {
@@ -46,13 +53,48 @@ pub struct SimulatorConfig {
     pub deck_size: usize,
     pub learn_span: usize,
     pub max_cost_perday: f64,
+    /// The longest interval, in days, that a card is scheduled with, matching the maximum
+    /// interval deck option. Cards are never moved past it to avoid a rest day.
     pub max_ivl: f64,
-    pub recall_costs: [f64; 3],
-    pub forget_cost: f64,
+    /// Cards due beyond this many reviews stay due, and are reviewed on later days. No limit by
+    /// default.
+    pub max_reviews_per_day: usize,
+    /// No limit by default.
+    pub max_new_per_day: usize,
+    /// Changes the new card limit as the deck is learnt, such as 20 a day for the first 5000
+    /// cards and none after. Stages must be in order, and `max_new_per_day` still applies.
+    pub new_card_ramp: Vec<NewCardStage>,
+    /// The order due cards are reviewed in, which decides those left for later days when a
+    /// daily limit is reached.
+    pub review_order: ReviewOrder,
+    /// Multiplies the daily time and card limits on each day of the week, starting from the
+    /// first simulated day. A day with a multiplier of 0 is a rest day: nothing is studied, and
+    /// cards are scheduled around it.
+    pub weekday_load: [f64; 7],
+    /// Seconds taken by a review rated Again, Hard, Good and Easy.
+    pub review_costs: [f64; 4],
+    /// Seconds taken to learn a new card.
     pub learn_cost: f64,
+    /// Extra seconds a lapsed card takes after its Again review, for relearning steps and the
+    /// further reviews they cause on the same day.
+    pub relearning_cost: f64,
+    /// Cards that have lapsed this many times are leeches. No cards are leeches by default.
+    pub leech_threshold: Option<usize>,
+    /// The chance that a leech is suspended or deleted each time it lapses. It is then never
+    /// reviewed again, and no longer counts as memorized.
+    pub leech_suspend_prob: f64,
     pub first_rating_prob: [f64; 4],
     pub review_rating_prob: [f64; 3],
     pub loss_aversion: f64,
+    /// Cards already in the collection when the simulation starts, such as the user's current
+    /// deck. They are studied alongside `deck_size` new cards.
+    pub existing_cards: Vec<ExistingCard>,
+    /// What [FSRS::optimal_retention] optimizes for.
+    pub objective: RetentionObjective,
+    /// Seeds the random number generator, so the same config always gives the same results.
+    pub seed: u64,
+    /// The most threads to run simulations on at once. By default, one for each CPU core.
+    pub max_threads: Option<usize>,
 }
 
 impl Default for SimulatorConfig {
@@ -62,16 +104,362 @@ impl Default for SimulatorConfig {
             learn_span: 365,
             max_cost_perday: 1800.0,
             max_ivl: 36500.0,
-            recall_costs: [14.0, 10.0, 6.0],
-            forget_cost: 50.0,
+            max_reviews_per_day: usize::MAX,
+            max_new_per_day: usize::MAX,
+            new_card_ramp: vec![],
+            review_order: ReviewOrder::default(),
+            weekday_load: [1.0; 7],
+            review_costs: [50.0, 14.0, 10.0, 6.0],
             learn_cost: 20.0,
+            relearning_cost: 0.0,
+            leech_threshold: None,
+            leech_suspend_prob: 0.0,
             first_rating_prob: [0.15, 0.2, 0.6, 0.05],
             review_rating_prob: [0.3, 0.6, 0.1],
             loss_aversion: 2.5,
+            existing_cards: vec![],
+            objective: RetentionObjective::default(),
+            seed: 42,
+            max_threads: None,
         }
     }
 }
 
+impl SimulatorConfig {
+    /// Start from the default settings, changing only those that differ.
+    pub fn builder() -> SimulatorConfigBuilder {
+        SimulatorConfigBuilder::default()
+    }
+
+    /// Check that every setting is within its valid range, so a simulation can't silently
+    /// produce meaningless results. Each simulation runs this first, failing with
+    /// [FSRSError::InvalidInput] for a config built as a struct literal.
+    pub fn validate(&self) -> Result<()> {
+        check(
+            self.deck_size > 0 || !self.existing_cards.is_empty(),
+            "deck_size",
+        )?;
+        self.validate_settings()
+    }
+
+    /// Like [SimulatorConfig::validate], but without checking that there are cards to study,
+    /// for [simulate_decks], which takes the cards from each deck instead.
+    fn validate_settings(&self) -> Result<()> {
+        let valid_probs =
+            |probs: &[f64]| probs.iter().all(|&p| p >= 0.0) && probs.iter().sum::<f64>() > 0.0;
+        check(self.learn_span > 0, "learn_span")?;
+        check(self.max_cost_perday > 0.0, "max_cost_perday")?;
+        check(self.max_ivl >= 1.0, "max_ivl")?;
+        check(
+            self.new_card_ramp
+                .windows(2)
+                .all(|pair| pair[0].after_learnt < pair[1].after_learnt),
+            "new_card_ramp",
+        )?;
+        check(
+            self.weekday_load.iter().all(|&load| load >= 0.0)
+                && self.weekday_load.iter().any(|&load| load > 0.0),
+            "weekday_load",
+        )?;
+        check(
+            self.review_costs.iter().all(|&cost| cost > 0.0),
+            "review_costs",
+        )?;
+        check(self.learn_cost > 0.0, "learn_cost")?;
+        check(self.relearning_cost >= 0.0, "relearning_cost")?;
+        check(self.leech_threshold != Some(0), "leech_threshold")?;
+        check(
+            (0.0..=1.0).contains(&self.leech_suspend_prob),
+            "leech_suspend_prob",
+        )?;
+        check(valid_probs(&self.first_rating_prob), "first_rating_prob")?;
+        check(valid_probs(&self.review_rating_prob), "review_rating_prob")?;
+        check(self.loss_aversion > 0.0, "loss_aversion")?;
+        if let RetentionObjective::MinTimeForTarget { target } = self.objective {
+            check(target > 0.0, "objective")?;
+        }
+        check(self.max_threads != Some(0), "max_threads")?;
+        Ok(())
+    }
+}
+
+fn check(valid: bool, field: &'static str) -> Result<()> {
+    if valid {
+        Ok(())
+    } else {
+        Err(FSRSError::InvalidInput { field })
+    }
+}
+
+/// Builds a [SimulatorConfig], checking its settings with [SimulatorConfig::validate].
+#[derive(Debug, Clone, Default)]
+pub struct SimulatorConfigBuilder {
+    config: SimulatorConfig,
+}
+
+impl SimulatorConfigBuilder {
+    pub fn deck_size(mut self, deck_size: usize) -> Self {
+        self.config.deck_size = deck_size;
+        self
+    }
+
+    pub fn learn_span(mut self, learn_span: usize) -> Self {
+        self.config.learn_span = learn_span;
+        self
+    }
+
+    pub fn max_cost_perday(mut self, max_cost_perday: f64) -> Self {
+        self.config.max_cost_perday = max_cost_perday;
+        self
+    }
+
+    pub fn max_ivl(mut self, max_ivl: f64) -> Self {
+        self.config.max_ivl = max_ivl;
+        self
+    }
+
+    pub fn max_reviews_per_day(mut self, max_reviews_per_day: usize) -> Self {
+        self.config.max_reviews_per_day = max_reviews_per_day;
+        self
+    }
+
+    pub fn max_new_per_day(mut self, max_new_per_day: usize) -> Self {
+        self.config.max_new_per_day = max_new_per_day;
+        self
+    }
+
+    pub fn new_card_ramp(mut self, new_card_ramp: Vec<NewCardStage>) -> Self {
+        self.config.new_card_ramp = new_card_ramp;
+        self
+    }
+
+    pub fn review_order(mut self, review_order: ReviewOrder) -> Self {
+        self.config.review_order = review_order;
+        self
+    }
+
+    pub fn weekday_load(mut self, weekday_load: [f64; 7]) -> Self {
+        self.config.weekday_load = weekday_load;
+        self
+    }
+
+    pub fn review_costs(mut self, review_costs: [f64; 4]) -> Self {
+        self.config.review_costs = review_costs;
+        self
+    }
+
+    pub fn learn_cost(mut self, learn_cost: f64) -> Self {
+        self.config.learn_cost = learn_cost;
+        self
+    }
+
+    pub fn relearning_cost(mut self, relearning_cost: f64) -> Self {
+        self.config.relearning_cost = relearning_cost;
+        self
+    }
+
+    pub fn leech_threshold(mut self, leech_threshold: Option<usize>) -> Self {
+        self.config.leech_threshold = leech_threshold;
+        self
+    }
+
+    pub fn leech_suspend_prob(mut self, leech_suspend_prob: f64) -> Self {
+        self.config.leech_suspend_prob = leech_suspend_prob;
+        self
+    }
+
+    pub fn first_rating_prob(mut self, first_rating_prob: [f64; 4]) -> Self {
+        self.config.first_rating_prob = first_rating_prob;
+        self
+    }
+
+    pub fn review_rating_prob(mut self, review_rating_prob: [f64; 3]) -> Self {
+        self.config.review_rating_prob = review_rating_prob;
+        self
+    }
+
+    pub fn loss_aversion(mut self, loss_aversion: f64) -> Self {
+        self.config.loss_aversion = loss_aversion;
+        self
+    }
+
+    pub fn existing_cards(mut self, existing_cards: Vec<ExistingCard>) -> Self {
+        self.config.existing_cards = existing_cards;
+        self
+    }
+
+    pub fn objective(mut self, objective: RetentionObjective) -> Self {
+        self.config.objective = objective;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.config.seed = seed;
+        self
+    }
+
+    pub fn max_threads(mut self, max_threads: Option<usize>) -> Self {
+        self.config.max_threads = max_threads;
+        self
+    }
+
+    pub fn build(self) -> Result<SimulatorConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// Desired retentions outside this range make the simulation unrealistic.
+const RETENTION_RANGE: std::ops::RangeInclusive<f64> = 0.7..=0.99;
+
+fn check_retention(desired_retention: f64) -> Result<()> {
+    if RETENTION_RANGE.contains(&desired_retention) {
+        Ok(())
+    } else {
+        Err(FSRSError::InvalidInput {
+            field: "desired_retention",
+        })
+    }
+}
+
+/// The goal used to pick the optimal retention. Each simulation is summarized by the cards
+/// remembered at its end and the total time spent studying.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum RetentionObjective {
+    /// Remember as many cards as possible, studying at most `max_cost_perday` each day.
+    #[default]
+    MaxMemorized,
+    /// Remember as many cards as possible for each minute spent studying.
+    MemorizedPerMinute,
+    /// Spend as little time as possible while remembering at least `target` cards. Retentions
+    /// that fall short of the target rank below those that reach it.
+    MinTimeForTarget { target: f64 },
+}
+
+impl RetentionObjective {
+    /// A key for comparing simulation outcomes, where greater is better.
+    fn score(&self, memorized: f64, seconds: f64) -> (f64, f64) {
+        match *self {
+            RetentionObjective::MaxMemorized => (memorized, 0.0),
+            RetentionObjective::MemorizedPerMinute => (memorized / (seconds / 60.0), 0.0),
+            RetentionObjective::MinTimeForTarget { target } => {
+                (-(target - memorized).max(0.0), -seconds)
+            }
+        }
+    }
+}
+
+/// A step of [SimulatorConfig::new_card_ramp].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NewCardStage {
+    /// The stage starts once this many new cards have been learnt.
+    pub after_learnt: usize,
+    pub max_new_per_day: usize,
+}
+
+/// The new card limit for the ramp stage reached after `learnt` cards, never learning past the
+/// start of the next stage.
+fn ramp_limit(ramp: &[NewCardStage], learnt: usize) -> usize {
+    let next = ramp.partition_point(|stage| stage.after_learnt <= learnt);
+    let limit = match next {
+        0 => usize::MAX,
+        _ => ramp[next - 1].max_new_per_day,
+    };
+    match ramp.get(next) {
+        Some(stage) => limit.min(stage.after_learnt - learnt),
+        None => limit,
+    }
+}
+
+/// The order due cards are reviewed in each day, for [SimulatorConfig::review_order].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReviewOrder {
+    /// The order the cards were added in.
+    #[default]
+    Added,
+    /// The earliest due cards first, so the most overdue cards are reviewed before others.
+    DueDate,
+    /// A different random order each day.
+    Random,
+    /// The easiest cards first.
+    DifficultyAscending,
+}
+
+/// How long a review took, for [SimulatorConfig::estimate_costs].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReviewDuration {
+    pub rating: u32,
+    /// Whether this was the card's first review, when it was learnt.
+    pub first_review: bool,
+    pub seconds: f64,
+}
+
+impl SimulatorConfig {
+    /// Set the review and learn costs to the median durations of the user's reviews, so the
+    /// simulation reflects how quickly they study. The median is used as durations are often
+    /// inflated by a user stepping away mid-review. Costs with no matching reviews are left
+    /// unchanged.
+    pub fn estimate_costs(&mut self, reviews: &[ReviewDuration]) {
+        let median = |filter: &dyn Fn(&ReviewDuration) -> bool| {
+            let mut seconds: Vec<f64> = reviews
+                .iter()
+                .filter(|review| filter(review))
+                .map(|review| review.seconds)
+                .collect();
+            if seconds.is_empty() {
+                return None;
+            }
+            seconds.sort_by(|a, b| a.total_cmp(b));
+            let mid = seconds.len() / 2;
+            Some(if seconds.len() % 2 == 0 {
+                (seconds[mid - 1] + seconds[mid]) / 2.0
+            } else {
+                seconds[mid]
+            })
+        };
+        if let Some(cost) = median(&|review| review.first_review) {
+            self.learn_cost = cost;
+        }
+        for (rating, review_cost) in (1..=4).zip(&mut self.review_costs) {
+            if let Some(cost) = median(&|review| !review.first_review && review.rating == rating) {
+                *review_cost = cost;
+            }
+        }
+    }
+
+    /// Like [SimulatorConfig::estimate_costs], but using the [FSRSReview::duration_ms] recorded
+    /// in items. Items are expected to hold an item for each review of each card after the
+    /// first, as training does, so each review is counted once: the first review of the item
+    /// with two reviews is when the card was learnt, and each item's last review is a review.
+    /// Same-day reviews are skipped, as the simulator counts those in `relearning_cost`.
+    pub fn estimate_costs_from_items(&mut self, items: &[FSRSItem]) {
+        let seconds = |review: &FSRSReview| review.duration_ms.map(|ms| ms as f64 / 1000.0);
+        let learnt = items
+            .iter()
+            .filter(|item| item.reviews.len() == 2)
+            .filter_map(|item| {
+                let review = &item.reviews[0];
+                Some(ReviewDuration {
+                    rating: review.rating,
+                    first_review: true,
+                    seconds: seconds(review)?,
+                })
+            });
+        let reviewed = items
+            .iter()
+            .filter_map(|item| item.reviews.last())
+            .filter(|review| !is_same_day(review.delta_t))
+            .filter_map(|review| {
+                Some(ReviewDuration {
+                    rating: review.rating,
+                    first_review: false,
+                    seconds: seconds(review)?,
+                })
+            });
+        self.estimate_costs(&learnt.chain(reviewed).collect::<Vec<_>>());
+    }
+}
+
 fn stability_after_success(w: &[f64], s: f64, r: f64, d: f64, response: usize) -> f64 {
     let hard_penalty = if response == 1 { w[15] } else { 1.0 };
     let easy_bonus = if response == 3 { w[16] } else { 1.0 };
@@ -89,29 +477,407 @@ fn stability_after_failure(w: &[f64], s: f64, r: f64, d: f64) -> f64 {
         .max(0.1)
 }
 
-fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: Option<u64>) -> f64 {
+/// Per-day statistics from a simulation, indexed by day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationResult {
+    /// Reviews of previously learnt cards.
+    pub review_cnt_per_day: Vec<usize>,
+    /// New cards introduced.
+    pub learn_cnt_per_day: Vec<usize>,
+    /// Seconds spent on reviews and new cards.
+    pub time_per_day: Vec<f64>,
+    /// The sum of every card's probability of recall.
+    pub memorized_cnt_per_day: Vec<f64>,
+}
+
+impl SimulationResult {
+    /// The expected number of cards remembered on each day, paired with the total minutes spent
+    /// studying before that day, for plotting knowledge gained against effort.
+    pub fn knowledge_curve(&self) -> Vec<(f64, f64)> {
+        let mut minutes = 0.0;
+        self.time_per_day
+            .iter()
+            .zip(&self.memorized_cnt_per_day)
+            .map(|(seconds, &memorized)| {
+                let point = (minutes, memorized);
+                minutes += seconds / 60.0;
+                point
+            })
+            .collect()
+    }
+}
+
+/// Simulate studying a deck with the given weights, scheduling each card at `desired_retention`.
+/// The same seed always produces the same result; if none is provided, [SimulatorConfig::seed]
+/// is used.
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+pub fn simulate(
+    config: &SimulatorConfig,
+    weights: &Weights,
+    desired_retention: f64,
+    seed: Option<u64>,
+) -> Result<SimulationResult> {
+    config.validate()?;
+    check_retention(desired_retention)?;
+    let weights = simulator_weights(weights)?;
+    Ok(run_simulation(
+        config,
+        &weights,
+        desired_retention,
+        seed.unwrap_or(config.seed),
+    ))
+}
+
+/// How a daily statistic varies across simulation runs, indexed by day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DailyBand {
+    pub mean: Vec<f64>,
+    pub lower: Vec<f64>,
+    pub upper: Vec<f64>,
+}
+
+/// The results of [simulate_with_uncertainty].
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimulationBands {
+    pub reviews: DailyBand,
+    /// Seconds spent each day.
+    pub time: DailyBand,
+    pub memorized: DailyBand,
+}
+
+/// Repeat the simulation `runs` times with different seeds, reporting the mean of each daily
+/// statistic and the band containing the central `coverage` fraction of runs; for example, 0.8
+/// gives the 10th and 90th percentiles. At least one run is made.
+pub fn simulate_with_uncertainty(
+    config: &SimulatorConfig,
+    weights: &Weights,
+    desired_retention: f64,
+    runs: usize,
+    coverage: f64,
+) -> Result<SimulationBands> {
+    config.validate()?;
+    check_retention(desired_retention)?;
+    let weights = simulator_weights(weights)?;
+    let results: Vec<SimulationResult> = with_thread_limit(config.max_threads, || {
+        (0..runs.max(1))
+            .into_par_iter()
+            .map(|i| {
+                run_simulation(
+                    config,
+                    &weights,
+                    desired_retention,
+                    config.seed.wrapping_add(i as u64),
+                )
+            })
+            .collect()
+    });
+    let band = |stat: &dyn Fn(&SimulationResult, usize) -> f64| {
+        let mut band = DailyBand {
+            mean: vec![],
+            lower: vec![],
+            upper: vec![],
+        };
+        for day in 0..config.learn_span {
+            let mut values: Vec<f64> = results.iter().map(|result| stat(result, day)).collect();
+            values.sort_by(|a, b| a.total_cmp(b));
+            band.mean
+                .push(values.iter().sum::<f64>() / values.len() as f64);
+            band.lower.push(percentile(&values, (1.0 - coverage) / 2.0));
+            band.upper.push(percentile(&values, (1.0 + coverage) / 2.0));
+        }
+        band
+    };
+    Ok(SimulationBands {
+        reviews: band(&|result, day| result.review_cnt_per_day[day] as f64),
+        time: band(&|result, day| result.time_per_day[day]),
+        memorized: band(&|result, day| result.memorized_cnt_per_day[day]),
+    })
+}
+
+/// Linearly interpolated between the closest ranks of sorted `values`.
+pub(crate) fn percentile(values: &[f64], p: f64) -> f64 {
+    let pos = p.clamp(0.0, 1.0) * (values.len() - 1) as f64;
+    let (lower, upper) = (pos.floor() as usize, pos.ceil() as usize);
+    values[lower] + (values[upper] - values[lower]) * (pos - lower as f64)
+}
+
+/// A deck studied with its own weights and desired retention, for [simulate_decks].
+#[derive(Debug, Clone)]
+pub struct DeckConfig {
+    /// May be empty to use the default weights.
+    pub weights: Vec<f32>,
+    pub desired_retention: f64,
+    /// New cards still to be learnt.
+    pub new_cards: usize,
+    pub existing_cards: Vec<ExistingCard>,
+}
+
+/// The results of [simulate_decks].
+#[derive(Debug, Clone, PartialEq)]
+pub struct JointSimulationResult {
+    /// One result for each deck, in the order they were provided.
+    pub decks: Vec<SimulationResult>,
+    /// The sum over all decks.
+    pub total: SimulationResult,
+}
+
+/// Simulate several decks that share the daily time and card limits, so the workload of each
+/// reflects the time taken by the others. `config.deck_size` and `config.existing_cards` are
+/// ignored in favour of each deck's cards; the other settings apply to all decks.
+pub fn simulate_decks(
+    config: &SimulatorConfig,
+    decks: &[DeckConfig],
+) -> Result<JointSimulationResult> {
+    config.validate_settings()?;
+    let weights = decks
+        .iter()
+        .map(|deck| {
+            check_retention(deck.desired_retention)?;
+            simulator_weights(&deck.weights)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let simulated_decks = decks
+        .iter()
+        .zip(&weights)
+        .map(|(deck, w)| SimulatedDeck {
+            w,
+            request_retention: deck.desired_retention,
+            new_cards: deck.new_cards,
+            existing_cards: &deck.existing_cards,
+        })
+        .collect::<Vec<_>>();
+    let results = run_decks(config, &simulated_decks, config.seed);
+    let learn_span = config.learn_span;
+    let mut total = SimulationResult {
+        review_cnt_per_day: vec![0; learn_span],
+        learn_cnt_per_day: vec![0; learn_span],
+        time_per_day: vec![0.0; learn_span],
+        memorized_cnt_per_day: vec![0.0; learn_span],
+    };
+    for result in &results {
+        for day in 0..learn_span {
+            total.review_cnt_per_day[day] += result.review_cnt_per_day[day];
+            total.learn_cnt_per_day[day] += result.learn_cnt_per_day[day];
+            total.time_per_day[day] += result.time_per_day[day];
+            total.memorized_cnt_per_day[day] += result.memorized_cnt_per_day[day];
+        }
+    }
+    Ok(JointSimulationResult {
+        decks: results,
+        total,
+    })
+}
+
+/// The workload of studying at a given desired retention, averaged over the simulated days.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorkloadSummary {
+    pub desired_retention: f64,
+    pub reviews_per_day: f64,
+    pub minutes_per_day: f64,
+    /// Cards remembered at the end of the simulation.
+    pub memorized: f64,
+}
+
+impl WorkloadSummary {
+    fn new(result: &SimulationResult, desired_retention: f64) -> Self {
+        let days = result.time_per_day.len() as f64;
+        Self {
+            desired_retention,
+            reviews_per_day: result.review_cnt_per_day.iter().sum::<usize>() as f64 / days,
+            minutes_per_day: result.time_per_day.iter().sum::<f64>() / 60.0 / days,
+            memorized: *result.memorized_cnt_per_day.last().unwrap(),
+        }
+    }
+}
+
+/// Simulate studying at a single desired retention, for comparing the cost of a few choices
+/// without searching for the optimal one.
+pub fn expected_workload(
+    config: &SimulatorConfig,
+    weights: &Weights,
+    desired_retention: f64,
+) -> Result<WorkloadSummary> {
+    let result = simulate(config, weights, desired_retention, None)?;
+    Ok(WorkloadSummary::new(&result, desired_retention))
+}
+
+/// Simulate `steps` evenly spaced desired retentions from `min_retention` to `max_retention`, so
+/// the tradeoff between study time and knowledge can be shown rather than a single optimum.
+pub fn retention_tradeoff(
+    config: &SimulatorConfig,
+    weights: &Weights,
+    min_retention: f64,
+    max_retention: f64,
+    steps: usize,
+) -> Result<Vec<WorkloadSummary>> {
+    config.validate()?;
+    check_retention(min_retention)?;
+    check_retention(max_retention)?;
+    let weights = simulator_weights(weights)?;
+    Ok(with_thread_limit(config.max_threads, || {
+        (0..steps)
+            .into_par_iter()
+            .map(|i| {
+                let t = if steps == 1 {
+                    0.0
+                } else {
+                    i as f64 / (steps - 1) as f64
+                };
+                let desired_retention = min_retention * (1.0 - t) + max_retention * t;
+                let result = run_simulation(config, &weights, desired_retention, config.seed);
+                WorkloadSummary::new(&result, desired_retention)
+            })
+            .collect()
+    }))
+}
+
+/// A card that has already been studied, for [SimulatorConfig::existing_cards].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ExistingCard {
+    pub memory: MemoryState,
+    /// Days since the card was last reviewed.
+    pub elapsed_days: u32,
+    /// Days until the card is due; negative if it is overdue.
+    pub due_in_days: i32,
+}
+
+/// Forecast the workload of an existing collection over the next `days` days, if its cards are
+/// scheduled at `desired_retention` from now on. `config.deck_size` gives the number of new cards
+/// still to be learnt alongside the existing ones, which replace `config.existing_cards`.
+pub fn forecast_workload(
+    config: &SimulatorConfig,
+    weights: &Weights,
+    desired_retention: f64,
+    cards: &[ExistingCard],
+    days: usize,
+) -> Result<SimulationResult> {
+    check(days > 0, "days")?;
+    check_retention(desired_retention)?;
+    let weights = simulator_weights(weights)?;
+    let config = SimulatorConfig {
+        learn_span: days,
+        existing_cards: cards.to_vec(),
+        ..config.clone()
+    };
+    config.validate()?;
+    Ok(run_simulation(
+        &config,
+        &weights,
+        desired_retention,
+        config.seed,
+    ))
+}
+
+fn simulator_weights(weights: &Weights) -> Result<Vec<f64>> {
+    let weights = if weights.is_empty() {
+        DEFAULT_WEIGHTS
+    } else if !matches!(weights.len(), 17 | 19) {
+        return Err(FSRSError::InvalidWeights);
+    } else {
+        weights
+    };
+    Ok(weights.iter().map(|v| *v as f64).collect())
+}
+
+fn run_simulation(
+    config: &SimulatorConfig,
+    w: &[f64],
+    request_retention: f64,
+    seed: u64,
+) -> SimulationResult {
+    let deck = SimulatedDeck {
+        w,
+        request_retention,
+        new_cards: config.deck_size,
+        existing_cards: &config.existing_cards,
+    };
+    run_decks(config, &[deck], seed).remove(0)
+}
+
+/// The parts of a simulation that differ between decks studied together.
+struct SimulatedDeck<'a> {
+    w: &'a [f64],
+    request_retention: f64,
+    new_cards: usize,
+    existing_cards: &'a [ExistingCard],
+}
+
+/// Simulate several decks sharing the daily limits, returning the statistics of each deck.
+fn run_decks(
+    config: &SimulatorConfig,
+    decks: &[SimulatedDeck],
+    seed: u64,
+) -> Vec<SimulationResult> {
     let SimulatorConfig {
-        deck_size,
+        deck_size: _,
         learn_span,
         max_cost_perday,
         max_ivl,
-        recall_costs,
-        forget_cost,
+        max_reviews_per_day,
+        max_new_per_day,
+        new_card_ramp,
+        review_order,
+        weekday_load,
+        review_costs,
         learn_cost,
+        relearning_cost,
+        leech_threshold,
+        leech_suspend_prob,
         first_rating_prob,
         review_rating_prob,
         loss_aversion,
+        existing_cards: _,
+        objective: _,
+        seed: _,
+        max_threads: _,
     } = config.clone();
+    // existing cards come first, followed by the new cards, taken from each deck in turn so that
+    // no deck is learnt ahead of the others
+    let mut deck_of = vec![];
+    let mut existing_cards = vec![];
+    for (deck_idx, deck) in decks.iter().enumerate() {
+        deck_of.extend(std::iter::repeat(deck_idx).take(deck.existing_cards.len()));
+        existing_cards.extend(deck.existing_cards);
+    }
+    let max_new_cards = decks.iter().map(|deck| deck.new_cards).max().unwrap_or(0);
+    for i in 0..max_new_cards {
+        for (deck_idx, deck) in decks.iter().enumerate() {
+            if i < deck.new_cards {
+                deck_of.push(deck_idx);
+            }
+        }
+    }
+    let deck_of = Array1::from(deck_of);
+    let interval_factors = decks
+        .iter()
+        .map(|deck| (1.0 / deck.request_retention).powf(-1.0 / DECAY as f64) - 1.0)
+        .collect::<Vec<_>>();
+    let deck_size = deck_of.len();
     let mut card_table = Array2::<f64>::zeros((Column::COUNT, deck_size));
+    // new cards are never due for review
     card_table
         .slice_mut(s![Column::Due, ..])
-        .fill(learn_span as f64);
+        .fill(f64::INFINITY);
     card_table.slice_mut(s![Column::Difficulty, ..]).fill(1e-10);
     card_table.slice_mut(s![Column::Stability, ..]).fill(1e-10);
+    for (i, card) in existing_cards.iter().enumerate() {
+        card_table[[Column::Stability as usize, i]] = card.memory.stability.into();
+        card_table[[Column::Difficulty as usize, i]] = card.memory.difficulty.into();
+        card_table[[Column::LastDate as usize, i]] = -f64::from(card.elapsed_days);
+        card_table[[Column::Due as usize, i]] = card.due_in_days.into();
+        card_table[[Column::Interval as usize, i]] =
+            f64::from(card.elapsed_days) + f64::from(card.due_in_days);
+    }
 
-    // let mut review_cnt_per_day = Array1::<f64>::zeros(learn_span);
-    // let mut learn_cnt_per_day = Array1::<f64>::zeros(learn_span);
-    let mut memorized_cnt_per_day = Array1::<f64>::zeros(learn_span);
+    let mut results = vec![
+        SimulationResult {
+            review_cnt_per_day: vec![0; learn_span],
+            learn_cnt_per_day: vec![0; learn_span],
+            time_per_day: vec![0.0; learn_span],
+            memorized_cnt_per_day: vec![0.0; learn_span],
+        };
+        decks.len()
+    ];
 
     let first_rating_choices = [0, 1, 2, 3];
     let first_rating_dist = WeightedIndex::new(first_rating_prob).unwrap();
@@ -119,10 +885,17 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
     let review_rating_choices = [1, 2, 3];
     let review_rating_dist = WeightedIndex::new(review_rating_prob).unwrap();
 
-    let mut rng = StdRng::seed_from_u64(seed.unwrap_or(42));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut learnt = 0;
 
     // Main simulation loop
     for today in 0..learn_span {
+        let load = weekday_load[today % 7];
+        let max_cost_today = max_cost_perday * load;
+        let max_reviews_today = scaled_limit(max_reviews_per_day, load);
+        let ramp_today = ramp_limit(&new_card_ramp, learnt);
+        let max_new_today = scaled_limit(max_new_per_day.min(ramp_today), load).min(ramp_today);
+
         let old_stability = card_table.slice(s![Column::Stability, ..]);
         let has_learned = old_stability.mapv(|x| x > 1e-9);
         let old_last_date = card_table.slice(s![Column::LastDate, ..]);
@@ -143,7 +916,7 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
         izip!(&mut retrievability, &delta_t, &old_stability, &has_learned)
             .filter(|(.., &has_learned_flag)| has_learned_flag)
             .for_each(|(retrievability, &delta_t, &stability, ..)| {
-                *retrievability = (1.0 + delta_t / (9.0 * stability)).powf(-1.0)
+                *retrievability = (1.0 + delta_t / (FACTOR as f64 * stability)).powf(DECAY as f64)
             });
 
         // Set 'cost' column to 0
@@ -188,27 +961,47 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
             .filter(|(_, &need_review_flag, _, _)| need_review_flag)
             .for_each(|(cost, _, &forget_flag, &rating)| {
                 *cost = if forget_flag {
-                    forget_cost * loss_aversion
+                    (review_costs[0] + relearning_cost) * loss_aversion
                 } else {
-                    recall_costs[rating - 1]
+                    review_costs[rating]
                 }
             });
 
-        // Calculate cumulative sum of 'cost'
+        // Order the cards for review
+        let mut order: Vec<usize> = (0..deck_size).collect();
+        match review_order {
+            ReviewOrder::Added => {}
+            ReviewOrder::DueDate => order.sort_by(|&a, &b| old_due[a].total_cmp(&old_due[b])),
+            ReviewOrder::Random => order.shuffle(&mut rng),
+            ReviewOrder::DifficultyAscending => {
+                let difficulty = card_table.slice(s![Column::Difficulty, ..]);
+                order.sort_by(|&a, &b| difficulty[a].total_cmp(&difficulty[b]));
+            }
+        }
+
+        // Calculate cumulative sum of 'cost', in review order
         let mut cum_sum = Array1::<f64>::zeros(deck_size);
-        for i in 1..deck_size {
-            cum_sum[i] = cum_sum[i - 1] + cost[i];
+        for pair in order.windows(2) {
+            cum_sum[pair[1]] = cum_sum[pair[0]] + cost[pair[1]];
         }
 
-        // Create 'true_review' mask based on 'need_review' and 'cum_sum'
-        let true_review =
-            Zip::from(&need_review)
-                .and(&cum_sum)
-                .map_collect(|&need_review_flag, &cum_cost| {
-                    need_review_flag && (cum_cost <= max_cost_perday)
-                });
+        // Create 'true_review' mask based on 'need_review', 'cum_sum' and the review limit.
+        // Reviews skipped because of the limit take no time.
+        let mut review_cnt = 0;
+        let mut true_review = Array1::from_elem(deck_size, false);
+        for &i in &order {
+            if !need_review[i] || cum_sum[i] > max_cost_today {
+                continue;
+            }
+            if review_cnt == max_reviews_today {
+                cost[i] = 0.0;
+                continue;
+            }
+            review_cnt += 1;
+            true_review[i] = true;
+        }
 
-        let need_learn = old_due.mapv(|x| x == learn_span as f64);
+        let need_learn = old_due.mapv(|x| x == f64::INFINITY);
         // Update 'cost' column based on 'need_learn'
         izip!(&mut cost, &need_learn)
             .filter(|(_, &need_learn_flag)| need_learn_flag)
@@ -223,12 +1016,17 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
         // dbg!(&cum_sum);
 
         // Create 'true_learn' mask based on 'need_learn' and 'cum_sum'
+        let mut learn_cnt = 0;
         let true_learn =
             Zip::from(&need_learn)
                 .and(&cum_sum)
                 .map_collect(|&need_learn_flag, &cum_cost| {
-                    need_learn_flag && (cum_cost <= max_cost_perday)
+                    let learn =
+                        need_learn_flag && cum_cost <= max_cost_today && learn_cnt < max_new_today;
+                    learn_cnt += learn as usize;
+                    learn
                 });
+        learnt += learn_cnt;
 
         // Sample 'rating' for 'true_learn' entries
         izip!(&mut ratings, &true_learn)
@@ -245,11 +1043,12 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
             &old_stability,
             &retrievability,
             &old_difficulty,
+            &deck_of,
             &(&true_review & &forget)
         )
         .filter(|(.., &condition)| condition)
-        .for_each(|(new_stab, &stab, &retr, &diff, ..)| {
-            *new_stab = stability_after_failure(w, stab, retr, diff);
+        .for_each(|(new_stab, &stab, &retr, &diff, &deck_idx, _)| {
+            *new_stab = stability_after_failure(decks[deck_idx].w, stab, retr, diff);
         });
 
         // Iterate over slices and apply stability_after_success function
@@ -259,22 +1058,30 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
             &old_stability,
             &retrievability,
             &old_difficulty,
+            &deck_of,
             &(&true_review & !&forget)
         )
         .filter(|(.., &condition)| condition)
-        .for_each(|(new_stab, &rating, &stab, &retr, &diff, _)| {
-            *new_stab = stability_after_success(w, stab, retr, diff, rating);
+        .for_each(|(new_stab, &rating, &stab, &retr, &diff, &deck_idx, _)| {
+            *new_stab = stability_after_success(decks[deck_idx].w, stab, retr, diff, rating);
         });
 
         // Initialize a new Array1 to store updated difficulty values
         let mut new_difficulty = old_difficulty.to_owned();
 
         // Update the difficulty values based on the condition 'true_review & forget'
-        izip!(&mut new_difficulty, &old_difficulty, &true_review, &forget)
-            .filter(|(.., &true_rev, &frgt)| true_rev && frgt)
-            .for_each(|(new_diff, &old_diff, ..)| {
-                *new_diff = (old_diff + 2.0 * w[6]).max(1.0).min(10.0);
-            });
+        izip!(
+            &mut new_difficulty,
+            &old_difficulty,
+            &deck_of,
+            &true_review,
+            &forget
+        )
+        .filter(|(.., &true_rev, &frgt)| true_rev && frgt)
+        .for_each(|(new_diff, &old_diff, &deck_idx, ..)| {
+            let w = decks[deck_idx].w;
+            *new_diff = (old_diff + 2.0 * w[6]).max(1.0).min(10.0);
+        });
 
         // Update 'last_date' column where 'true_review' or 'true_learn' is true
         let mut new_last_date = old_last_date.to_owned();
@@ -288,32 +1095,65 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
             &mut new_stability,
             &mut new_difficulty,
             &ratings,
+            &deck_of,
             &true_learn
         )
         .filter(|(.., &true_learn_flag)| true_learn_flag)
-        .for_each(|(new_stab, new_diff, &rating, _)| {
+        .for_each(|(new_stab, new_diff, &rating, &deck_idx, _)| {
+            let w = decks[deck_idx].w;
             *new_stab = w[rating];
             *new_diff = w[4] - w[5] * (rating as f64 - 3.0);
         });
         let old_interval = card_table.slice(s![Column::Interval, ..]);
         let mut new_interval = old_interval.to_owned();
-        izip!(&mut new_interval, &new_stability, &true_review, &true_learn)
-            .filter(|(.., &true_review_flag, &true_learn_flag)| true_review_flag || true_learn_flag)
-            .for_each(|(new_ivl, &new_stab, ..)| {
-                *new_ivl = (9.0 * new_stab * (1.0 / request_retention - 1.0))
-                    .round()
-                    .min(max_ivl)
-                    .max(1.0);
-            });
+        izip!(
+            &mut new_interval,
+            &new_stability,
+            &deck_of,
+            &true_review,
+            &true_learn
+        )
+        .filter(|(.., &true_review_flag, &true_learn_flag)| true_review_flag || true_learn_flag)
+        .for_each(|(new_ivl, &new_stab, &deck_idx, ..)| {
+            *new_ivl = (FACTOR as f64 * new_stab * interval_factors[deck_idx])
+                .round()
+                .min(max_ivl)
+                .max(1.0);
+        });
 
         let old_due = card_table.slice(s![Column::Due, ..]);
         let mut new_due = old_due.to_owned();
         izip!(&mut new_due, &new_interval, &true_review, &true_learn)
             .filter(|(.., &true_review_flag, &true_learn_flag)| true_review_flag || true_learn_flag)
             .for_each(|(new_due, &new_ivl, ..)| {
-                *new_due = today as f64 + new_ivl;
+                *new_due = avoid_rest_days(
+                    today as f64 + new_ivl,
+                    today,
+                    today as f64 + max_ivl,
+                    &weekday_load,
+                );
             });
 
+        // Count lapses, and suspend leeches, which are then never due and never recalled
+        let lapsed = &true_review & &forget;
+        let mut new_lapses = card_table.slice(s![Column::Lapses, ..]).to_owned();
+        izip!(&mut new_lapses, &lapsed)
+            .filter(|(_, &lapsed_flag)| lapsed_flag)
+            .for_each(|(lapses, _)| *lapses += 1.0);
+        if let Some(leech_threshold) = leech_threshold {
+            let suspend_dist = Uniform::new(0.0, 1.0);
+            izip!(&mut new_due, &mut new_stability, &new_lapses, &lapsed)
+                .filter(|(.., &lapses, &lapsed_flag)| {
+                    lapsed_flag && lapses >= leech_threshold as f64
+                })
+                .for_each(|(new_due, new_stab, ..)| {
+                    if suspend_dist.sample(&mut rng) < leech_suspend_prob {
+                        *new_due = f64::NAN;
+                        *new_stab = 1e-10;
+                    }
+                });
+        }
+
         // Update the card_table with the new values
         card_table
             .slice_mut(s![Column::Difficulty, ..])
@@ -325,22 +1165,95 @@ fn simulate(config: &SimulatorConfig, w: &[f64], request_retention: f64, seed: O
             .slice_mut(s![Column::LastDate, ..])
             .assign(&new_last_date);
         card_table.slice_mut(s![Column::Due, ..]).assign(&new_due);
+        card_table
+            .slice_mut(s![Column::Lapses, ..])
+            .assign(&new_lapses);
         card_table
             .slice_mut(s![Column::Interval, ..])
             .assign(&new_interval);
 
-        // Update the review_cnt_per_day, learn_cnt_per_day and memorized_cnt_per_day
-        // review_cnt_per_day[today] = true_review.iter().filter(|&&x| x).count() as f64;
-        // learn_cnt_per_day[today] = true_learn.iter().filter(|&&x| x).count() as f64;
-        memorized_cnt_per_day[today] = retrievability.sum();
+        // Update the review_cnt_per_day, learn_cnt_per_day, time_per_day and memorized_cnt_per_day
+        // of each deck. Loss aversion only affects which cards fit in the day, not the time they
+        // take.
+        let time_spent = izip!(&true_review, &true_learn, &forget, &ratings).map(
+            |(&true_review_flag, &true_learn_flag, &forget_flag, &rating)| {
+                if true_learn_flag {
+                    learn_cost
+                } else if !true_review_flag {
+                    0.0
+                } else if forget_flag {
+                    review_costs[0] + relearning_cost
+                } else {
+                    review_costs[rating]
+                }
+            },
+        );
+        for (&deck_idx, &true_review_flag, &true_learn_flag, time) in
+            izip!(&deck_of, &true_review, &true_learn, time_spent)
+        {
+            let result = &mut results[deck_idx];
+            result.review_cnt_per_day[today] += true_review_flag as usize;
+            result.learn_cnt_per_day[today] += true_learn_flag as usize;
+            result.time_per_day[today] += time;
+        }
+        for (deck_idx, result) in results.iter_mut().enumerate() {
+            result.memorized_cnt_per_day[today] = Zip::from(&retrievability)
+                .and(&deck_of)
+                .map_collect(|&r, &deck| if deck == deck_idx { r } else { 0.0 })
+                .sum();
+        }
     }
 
-    memorized_cnt_per_day[memorized_cnt_per_day.len() - 1]
+    results
+}
+
+/// Run `f`, which may use rayon, on at most `max_threads` threads.
+pub(crate) fn with_thread_limit<T: Send>(
+    max_threads: Option<usize>,
+    f: impl FnOnce() -> T + Send,
+) -> T {
+    match max_threads {
+        Some(threads) => ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("failed to create thread pool")
+            .install(f),
+        None => f(),
+    }
+}
+
+fn scaled_limit(limit: usize, load: f64) -> usize {
+    if limit == usize::MAX && load > 0.0 {
+        limit
+    } else {
+        (limit as f64 * load) as usize
+    }
+}
+
+/// Move a due date off a rest day, preferring an earlier day so the card isn't reviewed late.
+fn avoid_rest_days(due: f64, today: usize, latest: f64, weekday_load: &[f64; 7]) -> f64 {
+    let is_rest_day = |day: f64| weekday_load[day as usize % 7] == 0.0;
+    if !is_rest_day(due) {
+        return due;
+    }
+    (1..7)
+        .map(|offset| due - offset as f64)
+        .filter(|&day| day > today as f64)
+        .chain(
+            (1..7)
+                .map(|offset| due + offset as f64)
+                .filter(|&day| day <= latest),
+        )
+        .find(|&day| !is_rest_day(day))
+        .unwrap_or(due)
 }
 
 impl<B: Backend> FSRS<B> {
     /// For the given simulator parameters and weights, determine the suggested `desired_retention`
-    /// value.
+    /// value, using a golden-section search over retentions from 0.75 to 0.95. The simulations
+    /// for each step of the search run in parallel. `progress` is called after each step, and
+    /// returning false cancels the search with [FSRSError::Interrupted].
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub fn optimal_retention<F>(
         &self,
         config: &SimulatorConfig,
@@ -350,52 +1263,88 @@ impl<B: Backend> FSRS<B> {
     where
         F: FnMut(ItemProgress) -> bool,
     {
-        let weights = if weights.is_empty() {
-            DEFAULT_WEIGHTS
-        } else if weights.len() != 17 {
-            return Err(FSRSError::InvalidWeights);
-        } else {
-            weights
-        }
-        .iter()
-        .map(|v| *v as f64)
-        .collect_vec();
+        config.validate()?;
+        let weights = simulator_weights(weights)?;
         let mut low = 0.75;
         let mut high = 0.95;
-        let mut optimal_retention = 0.85;
         let epsilon = 0.01;
-        let mut iter = 0;
+        let samples = 5;
+        // golden-section search: each step shrinks the interval by the same ratio, and reuses
+        // one of the two points from the previous step, so only one new point is simulated
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let iterations = ((epsilon / (high - low)).ln() / ratio.ln()).ceil() as usize;
         let mut progress_info = ItemProgress {
             current: 0,
-            total: 10,
-        };
-        while high - low > epsilon && iter < 10 {
-            iter += 1;
-            progress_info.current += 1;
-            let mid1 = low + (high - low) / 3.0;
-            let mid2 = high - (high - low) / 3.0;
-            let sample_several = |n, mid| {
-                (0..n)
-                    .map(|i| simulate(config, &weights, mid, Some((i + 42).try_into().unwrap())))
-                    .sum::<f64>()
-                    / n as f64
-            };
-            let memorization1 = sample_several(5, mid1);
-            let memorization2 = sample_several(5, mid2);
+            total: (iterations + 2) * samples,
+        };
+        // the mean score of several runs at each retention
+        let mut evaluate = |retentions: &[f64]| {
+            let results: Vec<SimulationResult> = with_thread_limit(config.max_threads, || {
+                (0..retentions.len() * samples)
+                    .into_par_iter()
+                    .map(|i| {
+                        let seed = config.seed.wrapping_add((i % samples) as u64);
+                        run_simulation(config, &weights, retentions[i / samples], seed)
+                    })
+                    .collect()
+            });
+            progress_info.current += results.len();
+            if !progress(progress_info) {
+                return Err(FSRSError::Interrupted);
+            }
+            Ok(results
+                .chunks(samples)
+                .map(|results| {
+                    let mut memorized = 0.0;
+                    let mut seconds = 0.0;
+                    for result in results {
+                        memorized += result.memorized_cnt_per_day.last().unwrap();
+                        seconds += result.time_per_day.iter().sum::<f64>();
+                    }
+                    config
+                        .objective
+                        .score(memorized / samples as f64, seconds / samples as f64)
+                })
+                .collect::<Vec<_>>())
+        };
 
-            if memorization1 > memorization2 {
+        let mut mid1 = high - ratio * (high - low);
+        let mut mid2 = low + ratio * (high - low);
+        let scores = evaluate(&[mid1, mid2])?;
+        let (mut score1, mut score2) = (scores[0], scores[1]);
+        for _ in 0..iterations {
+            if score1 > score2 {
                 high = mid2;
+                (mid2, score2) = (mid1, score1);
+                mid1 = high - ratio * (high - low);
+                score1 = evaluate(&[mid1])?[0];
             } else {
                 low = mid1;
-            }
-
-            optimal_retention = (high + low) / 2.0;
-            // dbg!(iter, optimal_retention);
-            if !(progress(progress_info)) {
-                return Err(FSRSError::Interrupted);
+                (mid1, score1) = (mid2, score2);
+                mid2 = low + ratio * (high - low);
+                score2 = evaluate(&[mid2])?[0];
             }
         }
-        Ok(optimal_retention)
+        Ok((high + low) / 2.0)
+    }
+
+    /// Like [FSRS::optimal_retention], but reports progress through a shared [ProgressState], as
+    /// [FSRS::compute_weights] does. The search is treated as a single epoch, and setting
+    /// `want_abort` cancels it with [FSRSError::Interrupted].
+    pub fn optimal_retention_with_progress(
+        &self,
+        config: &SimulatorConfig,
+        weights: &Weights,
+        progress: Arc<Mutex<ProgressState>>,
+    ) -> Result<f64> {
+        self.optimal_retention(config, weights, |item_progress| {
+            let mut info = progress.lock().unwrap();
+            info.epoch = 1;
+            info.epoch_total = 1;
+            info.items_processed = item_progress.current;
+            info.items_total = item_progress.total;
+            !info.want_abort
+        })
     }
 }
 
@@ -407,13 +1356,568 @@ mod tests {
     #[test]
     fn simulator() {
         let config = SimulatorConfig::default();
-        let memorization = simulate(
+        let result = simulate(&config, DEFAULT_WEIGHTS, 0.9, None).unwrap();
+        assert_eq!(
+            *result.memorized_cnt_per_day.last().unwrap(),
+            2542.50223082592
+        );
+        assert_eq!(result.review_cnt_per_day.len(), config.learn_span);
+        assert!(result.learn_cnt_per_day.iter().sum::<usize>() <= config.deck_size);
+        assert_eq!(result.review_cnt_per_day[0], 0);
+        assert_eq!(
+            result.time_per_day[0],
+            result.learn_cnt_per_day[0] as f64 * config.learn_cost
+        );
+        assert_eq!(
+            result,
+            simulate(&config, DEFAULT_WEIGHTS, 0.9, None).unwrap()
+        );
+        assert_eq!(
+            result,
+            simulate(&config, DEFAULT_WEIGHTS, 0.9, Some(42)).unwrap()
+        );
+        let reseeded = SimulatorConfig {
+            seed: 7,
+            ..config.clone()
+        };
+        assert_ne!(
+            result,
+            simulate(&reseeded, DEFAULT_WEIGHTS, 0.9, None).unwrap()
+        );
+        assert!(simulate(&config, &[1.], 0.9, None).is_err());
+    }
+
+    #[test]
+    fn tradeoff() -> Result<()> {
+        let config = SimulatorConfig {
+            deck_size: 1000,
+            learn_span: 90,
+            max_cost_perday: f64::INFINITY,
+            ..Default::default()
+        };
+        let points = retention_tradeoff(&config, &[], 0.7, 0.97, 4)?;
+        assert_eq!(points.len(), 4);
+        assert_eq!(points[0].desired_retention, 0.7);
+        assert_eq!(points[3].desired_retention, 0.97);
+        assert!(points[0].minutes_per_day < points[3].minutes_per_day);
+        assert!(points[0].memorized < points[3].memorized);
+        assert_eq!(
+            points[2].memorized,
+            *simulate(&config, &[], points[2].desired_retention, None)?
+                .memorized_cnt_per_day
+                .last()
+                .unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn joint_simulation() -> Result<()> {
+        let config = SimulatorConfig {
+            learn_span: 60,
+            max_cost_perday: 600.0,
+            ..Default::default()
+        };
+        let deck = |desired_retention| DeckConfig {
+            weights: vec![],
+            desired_retention,
+            new_cards: 500,
+            existing_cards: vec![],
+        };
+        let result = simulate_decks(&config, &[deck(0.9), deck(0.8)])?;
+        assert_eq!(result.decks.len(), 2);
+        for day in 0..60 {
+            assert_eq!(
+                result.total.review_cnt_per_day[day],
+                result.decks[0].review_cnt_per_day[day] + result.decks[1].review_cnt_per_day[day]
+            );
+        }
+        // both decks are learnt at the same pace
+        assert!(
+            result.decks[0].learn_cnt_per_day[0].abs_diff(result.decks[1].learn_cnt_per_day[0])
+                <= 1
+        );
+        assert!(
+            result.decks[0].review_cnt_per_day.iter().sum::<usize>()
+                > result.decks[1].review_cnt_per_day.iter().sum::<usize>()
+        );
+
+        // a single deck gives the same results as simulate()
+        let single = simulate_decks(&config, &[deck(0.9)])?;
+        let config = SimulatorConfig {
+            deck_size: 500,
+            ..config
+        };
+        assert_eq!(single.total, simulate(&config, &[], 0.9, None)?);
+        assert!(simulate_decks(
             &config,
-            &DEFAULT_WEIGHTS.iter().map(|v| *v as f64).collect_vec(),
+            &[DeckConfig {
+                weights: vec![1.0],
+                ..deck(0.9)
+            }]
+        )
+        .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(&values, 0.0), 1.0);
+        assert_eq!(percentile(&values, 0.5), 3.0);
+        assert_eq!(percentile(&values, 0.1), 1.4);
+        assert_eq!(percentile(&values, 1.0), 5.0);
+        assert_eq!(percentile(&[2.0], 0.9), 2.0);
+    }
+
+    #[test]
+    fn uncertainty() -> Result<()> {
+        let config = SimulatorConfig {
+            deck_size: 1000,
+            learn_span: 60,
+            ..Default::default()
+        };
+        let bands = simulate_with_uncertainty(&config, &[], 0.9, 8, 0.8)?;
+        assert_eq!(bands.reviews.mean.len(), 60);
+        for band in [&bands.reviews, &bands.time, &bands.memorized] {
+            for day in 0..60 {
+                assert!(band.lower[day] <= band.mean[day] + 1e-9);
+                assert!(band.mean[day] <= band.upper[day] + 1e-9);
+            }
+        }
+        assert!(bands.reviews.lower[59] < bands.reviews.upper[59]);
+
+        let single = simulate_with_uncertainty(&config, &[], 0.9, 1, 0.8)?;
+        let result = simulate(&config, &[], 0.9, None)?;
+        assert_eq!(single.memorized.mean, result.memorized_cnt_per_day);
+        assert_eq!(single.time.lower, result.time_per_day);
+        Ok(())
+    }
+
+    #[test]
+    fn workload_summary() -> Result<()> {
+        let config = SimulatorConfig {
+            deck_size: 1000,
+            learn_span: 60,
+            ..Default::default()
+        };
+        let summary = expected_workload(&config, &[], 0.9)?;
+        let result = simulate(&config, &[], 0.9, None)?;
+        assert_eq!(summary.desired_retention, 0.9);
+        assert_eq!(
+            summary.reviews_per_day,
+            result.review_cnt_per_day.iter().sum::<usize>() as f64 / 60.0
+        );
+        assert_eq!(
+            summary.memorized,
+            *result.memorized_cnt_per_day.last().unwrap()
+        );
+        assert_eq!(
+            Some(&summary),
+            retention_tradeoff(&config, &[], 0.9, 0.9, 1)?.first()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn knowledge_curve() {
+        let result = SimulationResult {
+            review_cnt_per_day: vec![0, 2, 1],
+            learn_cnt_per_day: vec![3, 0, 0],
+            time_per_day: vec![60.0, 30.0, 90.0],
+            memorized_cnt_per_day: vec![0.0, 2.7, 2.5],
+        };
+        assert_eq!(
+            result.knowledge_curve(),
+            vec![(0.0, 0.0), (1.0, 2.7), (1.5, 2.5)]
+        );
+        let result = simulate(&SimulatorConfig::default(), &[], 0.9, None).unwrap();
+        assert!(result
+            .knowledge_curve()
+            .windows(2)
+            .all(|pair| pair[0].0 <= pair[1].0));
+    }
+
+    #[test]
+    fn daily_limits() {
+        let config = SimulatorConfig {
+            learn_span: 100,
+            max_cost_perday: f64::INFINITY,
+            max_reviews_per_day: 50,
+            max_new_per_day: 20,
+            ..Default::default()
+        };
+        let result = simulate(&config, &[], 0.9, None).unwrap();
+        assert!(result.review_cnt_per_day.iter().all(|&cnt| cnt <= 50));
+        assert!(result.review_cnt_per_day.contains(&50));
+        assert_eq!(result.learn_cnt_per_day, vec![20; 100]);
+    }
+
+    #[test]
+    fn new_card_ramp() {
+        let stage = |after_learnt, max_new_per_day| NewCardStage {
+            after_learnt,
+            max_new_per_day,
+        };
+        let ramp = [stage(0, 10), stage(50, 30), stage(110, 0)];
+        assert_eq!(ramp_limit(&[], 5), usize::MAX);
+        assert_eq!(ramp_limit(&ramp[1..], 20), 30);
+        assert_eq!(ramp_limit(&ramp, 20), 10);
+        assert_eq!(ramp_limit(&ramp, 45), 5);
+        assert_eq!(ramp_limit(&ramp, 100), 10);
+        assert_eq!(ramp_limit(&ramp, 110), 0);
+
+        let config = SimulatorConfig {
+            learn_span: 20,
+            max_cost_perday: f64::INFINITY,
+            new_card_ramp: ramp.to_vec(),
+            ..Default::default()
+        };
+        let result = simulate(&config, &[], 0.9, None).unwrap();
+        assert_eq!(
+            result.learn_cnt_per_day[..8],
+            [10, 10, 10, 10, 10, 30, 30, 0]
+        );
+        assert_eq!(result.learn_cnt_per_day.iter().sum::<usize>(), 110);
+
+        let unordered = SimulatorConfig {
+            new_card_ramp: vec![stage(50, 30), stage(0, 10)],
+            ..config
+        };
+        assert!(unordered.validate().is_err());
+    }
+
+    #[test]
+    fn review_order() {
+        let card = |difficulty, due_in_days| ExistingCard {
+            memory: MemoryState {
+                stability: 5.0,
+                difficulty,
+            },
+            elapsed_days: 10,
+            due_in_days,
+        };
+        let config = SimulatorConfig {
+            deck_size: 1,
+            learn_span: 30,
+            max_cost_perday: f64::INFINITY,
+            max_reviews_per_day: 50,
+            existing_cards: (0..300)
+                .map(|i| card(if i % 2 == 0 { 9.0 } else { 2.0 }, -(i % 7)))
+                .collect(),
+            ..Default::default()
+        };
+        let results = [
+            ReviewOrder::Added,
+            ReviewOrder::DueDate,
+            ReviewOrder::Random,
+            ReviewOrder::DifficultyAscending,
+        ]
+        .map(|review_order| {
+            simulate(
+                &SimulatorConfig {
+                    review_order,
+                    ..config.clone()
+                },
+                &[],
+                0.9,
+                None,
+            )
+            .unwrap()
+        });
+        for result in &results {
+            assert!(result.review_cnt_per_day.iter().all(|&cnt| cnt <= 50));
+            assert_eq!(result.review_cnt_per_day[0], 50);
+        }
+        assert_ne!(results[0], results[2]);
+        assert_ne!(results[0], results[3]);
+    }
+
+    #[test]
+    fn existing_cards() -> Result<()> {
+        let card = ExistingCard {
+            memory: MemoryState {
+                stability: 5.0,
+                difficulty: 5.0,
+            },
+            elapsed_days: 5,
+            due_in_days: 0,
+        };
+        let config = SimulatorConfig {
+            deck_size: 100,
+            learn_span: 30,
+            existing_cards: vec![card; 500],
+            ..Default::default()
+        };
+        let result = simulate(&config, &[], 0.9, None)?;
+        assert!(result.review_cnt_per_day[0] > 0);
+        assert!(result.memorized_cnt_per_day[0] > 400.0);
+        let fsrs = FSRS::new(None)?;
+        let optimal_retention = fsrs.optimal_retention(&config, &[], |_| true)?;
+        assert!((0.75..=0.95).contains(&optimal_retention));
+        Ok(())
+    }
+
+    #[test]
+    fn forecast() {
+        let config = SimulatorConfig {
+            deck_size: 0,
+            max_cost_perday: f64::INFINITY,
+            ..Default::default()
+        };
+        let card = |due_in_days| ExistingCard {
+            memory: MemoryState {
+                stability: 10.0,
+                difficulty: 5.0,
+            },
+            elapsed_days: 10,
+            due_in_days,
+        };
+        let cards = [card(-3), card(0), card(2), card(60)];
+        let result = forecast_workload(&config, &[], 0.9, &cards, 30).unwrap();
+        assert_eq!(result.review_cnt_per_day.len(), 30);
+        assert_eq!(result.review_cnt_per_day[0], 2);
+        assert!(result.review_cnt_per_day.iter().sum::<usize>() >= 3);
+        assert!(result.learn_cnt_per_day.iter().all(|&cnt| cnt == 0));
+        assert!(result.memorized_cnt_per_day[0] > 3.0);
+        assert!(forecast_workload(&config, &[], 0.8, &cards, 30).is_ok());
+    }
+
+    #[test]
+    fn relearning_cost() {
+        let config = SimulatorConfig {
+            deck_size: 1000,
+            learn_span: 60,
+            max_cost_perday: f64::INFINITY,
+            ..Default::default()
+        };
+        let result = simulate(&config, &[], 0.9, None).unwrap();
+        let relearning = simulate(
+            &SimulatorConfig {
+                relearning_cost: 30.0,
+                ..config
+            },
+            &[],
+            0.9,
+            None,
+        )
+        .unwrap();
+        assert_eq!(result.review_cnt_per_day, relearning.review_cnt_per_day);
+        let total_time = |result: &SimulationResult| result.time_per_day.iter().sum::<f64>();
+        assert!(total_time(&relearning) > total_time(&result));
+    }
+
+    #[test]
+    fn leeches() -> Result<()> {
+        let config = SimulatorConfig {
+            deck_size: 1000,
+            learn_span: 120,
+            max_cost_perday: f64::INFINITY,
+            ..Default::default()
+        };
+        let result = simulate(&config, &[], 0.9, None)?;
+        let suspended = simulate(
+            &SimulatorConfig {
+                leech_threshold: Some(1),
+                leech_suspend_prob: 1.0,
+                ..config
+            },
+            &[],
+            0.9,
+            None,
+        )?;
+        let total_reviews =
+            |result: &SimulationResult| result.review_cnt_per_day.iter().sum::<usize>();
+        assert!(total_reviews(&suspended) < total_reviews(&result));
+        assert!(
+            suspended.memorized_cnt_per_day.last().unwrap()
+                < result.memorized_cnt_per_day.last().unwrap()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn max_interval() -> Result<()> {
+        let config = SimulatorConfig {
+            deck_size: 1000,
+            learn_span: 365,
+            max_cost_perday: f64::INFINITY,
+            ..Default::default()
+        };
+        let result = simulate(&config, &[], 0.9, None)?;
+        let capped = simulate(
+            &SimulatorConfig {
+                max_ivl: 30.0,
+                ..config
+            },
+            &[],
             0.9,
             None,
+        )?;
+        let total_reviews =
+            |result: &SimulationResult| result.review_cnt_per_day.iter().sum::<usize>();
+        assert!(total_reviews(&capped) > total_reviews(&result));
+        // with every interval at most 30 days, each card is reviewed at least once a month
+        assert!(capped.review_cnt_per_day[335..].iter().sum::<usize>() >= 1000);
+        Ok(())
+    }
+
+    #[test]
+    fn rest_days() {
+        let mut weekday_load = [1.0; 7];
+        weekday_load[6] = 0.0;
+        assert_eq!(avoid_rest_days(13.0, 0, 100.0, &weekday_load), 12.0);
+        assert_eq!(avoid_rest_days(6.0, 5, 100.0, &weekday_load), 7.0);
+        assert_eq!(avoid_rest_days(8.0, 5, 100.0, &weekday_load), 8.0);
+        assert_eq!(avoid_rest_days(6.0, 5, 6.0, &weekday_load), 6.0);
+
+        let config = SimulatorConfig {
+            learn_span: 60,
+            weekday_load,
+            ..Default::default()
+        };
+        let result = simulate(&config, &[], 0.9, None).unwrap();
+        for day in (6..60).step_by(7) {
+            assert_eq!(result.review_cnt_per_day[day], 0);
+            assert_eq!(result.learn_cnt_per_day[day], 0);
+        }
+        assert!(result.learn_cnt_per_day[0] > 0);
+    }
+
+    #[test]
+    fn builder() {
+        let config = SimulatorConfig::builder()
+            .deck_size(500)
+            .review_costs([30.0, 12.0, 8.0, 5.0])
+            .build()
+            .unwrap();
+        assert_eq!(config.deck_size, 500);
+        assert_eq!(config.review_costs, [30.0, 12.0, 8.0, 5.0]);
+        assert_eq!(config.learn_span, SimulatorConfig::default().learn_span);
+
+        let invalid_field = |builder: SimulatorConfigBuilder| match builder.build() {
+            Err(FSRSError::InvalidInput { field }) => field,
+            _ => panic!("expected an invalid input error"),
+        };
+        assert_eq!(
+            invalid_field(SimulatorConfig::builder().learn_span(0)),
+            "learn_span"
+        );
+        assert_eq!(
+            invalid_field(SimulatorConfig::builder().learn_cost(-1.0)),
+            "learn_cost"
+        );
+        assert_eq!(
+            invalid_field(SimulatorConfig::builder().review_rating_prob([0.0; 3])),
+            "review_rating_prob"
+        );
+        assert_eq!(
+            invalid_field(SimulatorConfig::builder().weekday_load([0.0; 7])),
+            "weekday_load"
+        );
+        assert!(matches!(
+            simulate(&SimulatorConfig::default(), &[], 0.5, None),
+            Err(FSRSError::InvalidInput {
+                field: "desired_retention"
+            })
+        ));
+    }
+
+    #[test]
+    fn invalid_configs() -> Result<()> {
+        let invalid_field = |result: Result<()>| match result {
+            Err(FSRSError::InvalidInput { field }) => field,
+            _ => panic!("expected an invalid input error"),
+        };
+        let no_ratings = SimulatorConfig {
+            first_rating_prob: [0.0; 4],
+            ..Default::default()
+        };
+        assert_eq!(
+            invalid_field(simulate(&no_ratings, &[], 0.9, None).map(|_| ())),
+            "first_rating_prob"
+        );
+        assert_eq!(
+            invalid_field(expected_workload(&no_ratings, &[], 0.9).map(|_| ())),
+            "first_rating_prob"
+        );
+        let no_days = SimulatorConfig {
+            learn_span: 0,
+            ..Default::default()
+        };
+        assert_eq!(
+            invalid_field(retention_tradeoff(&no_days, &[], 0.8, 0.9, 2).map(|_| ())),
+            "learn_span"
+        );
+        assert_eq!(
+            invalid_field(simulate_with_uncertainty(&no_days, &[], 0.9, 2, 0.8).map(|_| ())),
+            "learn_span"
+        );
+        assert_eq!(
+            invalid_field(
+                FSRS::new(None)?
+                    .optimal_retention(&no_days, &[], |_| true)
+                    .map(|_| ())
+            ),
+            "learn_span"
+        );
+        assert_eq!(
+            invalid_field(
+                forecast_workload(&SimulatorConfig::default(), &[], 0.9, &[], 0).map(|_| ())
+            ),
+            "days"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn estimate_costs() {
+        let review = |rating, first_review, seconds| ReviewDuration {
+            rating,
+            first_review,
+            seconds,
+        };
+        let mut config = SimulatorConfig::default();
+        config.estimate_costs(&[
+            review(3, true, 30.0),
+            review(1, false, 20.0),
+            review(1, false, 40.0),
+            review(1, false, 300.0),
+            review(3, false, 8.0),
+            review(3, false, 5.0),
+        ]);
+        assert_eq!(config.learn_cost, 30.0);
+        assert_eq!(config.review_costs, [40.0, 14.0, 6.5, 6.0]);
+    }
+
+    #[test]
+    fn estimate_costs_from_items() {
+        let review = |rating, delta_t, duration_ms| FSRSReview {
+            rating,
+            delta_t,
+            duration_ms,
+        };
+        // one card learnt and reviewed three times, including a same-day relearning step
+        let history = vec![
+            review(3, 0.0, Some(30000)),
+            review(1, 3.0, Some(20000)),
+            review(3, 0.0, Some(9000)),
+            review(3, 2.0, None),
+        ];
+        let items: Vec<_> = (2..=history.len())
+            .map(|len| FSRSItem {
+                reviews: history[..len].to_vec(),
+                group: None,
+            })
+            .collect();
+        let mut config = SimulatorConfig::default();
+        config.estimate_costs_from_items(&items);
+        assert_eq!(config.learn_cost, 30.0);
+        assert_eq!(
+            config.review_costs,
+            [20.0, 14.0, 10.0, 6.0],
+            "same-day and untimed reviews are skipped"
         );
-        assert_eq!(memorization, 2542.50223082592)
     }
 
     #[test]
@@ -421,8 +1925,85 @@ mod tests {
         let config = SimulatorConfig::default();
         let fsrs = FSRS::new(None)?;
         let optimal_retention = fsrs.optimal_retention(&config, &[], |_v| true).unwrap();
-        assert_eq!(optimal_retention, 0.8687319006249048);
+        assert!((optimal_retention - 0.8687).abs() < 0.02);
         assert!(fsrs.optimal_retention(&config, &[1.], |_v| true).is_err());
         Ok(())
     }
+
+    #[test]
+    fn retention_objectives() -> Result<()> {
+        let objective = RetentionObjective::MinTimeForTarget { target: 100.0 };
+        assert!(objective.score(100.0, 600.0) > objective.score(150.0, 900.0));
+        assert!(objective.score(100.0, 900.0) > objective.score(90.0, 600.0));
+        assert!(
+            RetentionObjective::MemorizedPerMinute.score(100.0, 600.0)
+                > RetentionObjective::MemorizedPerMinute.score(150.0, 1200.0)
+        );
+
+        let fsrs = FSRS::new(None)?;
+        for objective in [
+            RetentionObjective::MemorizedPerMinute,
+            RetentionObjective::MinTimeForTarget { target: 500.0 },
+        ] {
+            let config = SimulatorConfig {
+                deck_size: 1000,
+                learn_span: 60,
+                objective,
+                ..Default::default()
+            };
+            let optimal_retention = fsrs.optimal_retention(&config, &[], |_| true)?;
+            assert!((0.75..=0.95).contains(&optimal_retention));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn thread_limit() -> Result<()> {
+        let config = SimulatorConfig {
+            deck_size: 1000,
+            learn_span: 60,
+            ..Default::default()
+        };
+        let fsrs = FSRS::new(None)?;
+        let single_threaded = SimulatorConfig {
+            max_threads: Some(1),
+            ..config.clone()
+        };
+        assert_eq!(
+            fsrs.optimal_retention(&config, &[], |_| true)?,
+            fsrs.optimal_retention(&single_threaded, &[], |_| true)?
+        );
+        assert_eq!(
+            retention_tradeoff(&config, &[], 0.8, 0.9, 3)?,
+            retention_tradeoff(&single_threaded, &[], 0.8, 0.9, 3)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn optimal_retention_progress() -> Result<()> {
+        let config = SimulatorConfig {
+            learn_span: 30,
+            ..Default::default()
+        };
+        let fsrs = FSRS::new(None)?;
+        let mut calls = 0;
+        let result = fsrs.optimal_retention(&config, &[], |progress| {
+            calls += 1;
+            assert_eq!(progress.current, 5 + calls * 5);
+            assert_eq!(progress.total, 45);
+            calls < 3
+        });
+        assert!(matches!(result, Err(FSRSError::Interrupted)));
+        assert_eq!(calls, 3);
+
+        let progress = ProgressState::new_shared();
+        progress.lock().unwrap().want_abort = true;
+        assert!(matches!(
+            fsrs.optimal_retention_with_progress(&config, &[], progress.clone()),
+            Err(FSRSError::Interrupted)
+        ));
+        assert_eq!(progress.lock().unwrap().items_processed, 10);
+        Ok(())
+    }
 }
@@ -0,0 +1,134 @@
+use crate::inference::MemoryState;
+
+/// A scheduled card, for [cards_to_postpone] and [cards_to_advance].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DueCard {
+    pub memory: MemoryState,
+    /// Days since the card's last review, as of today.
+    pub days_elapsed: f32,
+    /// Days from the card's last review to its due date. The card is due today or overdue when
+    /// `days_elapsed` is at least this.
+    pub interval: f32,
+}
+
+/// A proposed change to when a card is reviewed, with its effect on the chance of recalling it
+/// at that review.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CardMove {
+    /// The card's position in the slice it was picked from.
+    pub index: usize,
+    /// The retrievability at the review if the card is not moved.
+    pub retrievability_before: f32,
+    /// The retrievability at the review once the card is moved.
+    pub retrievability_after: f32,
+}
+
+impl CardMove {
+    /// Negative when postponing, as the card is more likely to be forgotten, and positive when
+    /// advancing. Summed over the moves, this is the expected change in cards recalled.
+    pub fn retention_change(&self) -> f32 {
+        self.retrievability_after - self.retrievability_before
+    }
+}
+
+/// Pick up to `count` cards due today whose review can be postponed by `days` with the least
+/// loss of retrievability, such as to lighten a day the user is overloaded. The cards are
+/// returned from safest to postpone.
+pub fn cards_to_postpone(cards: &[DueCard], days: f32, count: usize) -> Vec<CardMove> {
+    pick(
+        cards
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| card.days_elapsed >= card.interval)
+            .map(|(index, card)| CardMove {
+                index,
+                retrievability_before: card.memory.retrievability(card.days_elapsed),
+                retrievability_after: card.memory.retrievability(card.days_elapsed + days),
+            }),
+        count,
+    )
+}
+
+/// Pick up to `count` cards that are not yet due to review today instead, such as to get ahead
+/// before a break. Reviewing a card early wastes some of the review, as the card is more likely
+/// to be recalled and so gains less stability; the cards that gain the least retrievability by
+/// being reviewed today are returned first.
+pub fn cards_to_advance(cards: &[DueCard], count: usize) -> Vec<CardMove> {
+    pick(
+        cards
+            .iter()
+            .enumerate()
+            .filter(|(_, card)| card.days_elapsed < card.interval)
+            .map(|(index, card)| CardMove {
+                index,
+                retrievability_before: card.memory.retrievability(card.interval),
+                retrievability_after: card.memory.retrievability(card.days_elapsed),
+            }),
+        count,
+    )
+}
+
+/// The `count` moves that change retrievability the least, in that order.
+fn pick(moves: impl Iterator<Item = CardMove>, count: usize) -> Vec<CardMove> {
+    let mut moves: Vec<_> = moves.collect();
+    moves.sort_by(|a, b| {
+        a.retention_change()
+            .abs()
+            .total_cmp(&b.retention_change().abs())
+    });
+    moves.truncate(count);
+    moves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(stability: f32, days_elapsed: f32, interval: f32) -> DueCard {
+        DueCard {
+            memory: MemoryState {
+                stability,
+                difficulty: 5.0,
+            },
+            days_elapsed,
+            interval,
+        }
+    }
+
+    #[test]
+    fn postpone() {
+        let cards = [
+            card(2.0, 3.0, 3.0),
+            // not due yet
+            card(50.0, 10.0, 60.0),
+            card(100.0, 100.0, 90.0),
+            card(10.0, 10.0, 9.0),
+        ];
+        let moves = cards_to_postpone(&cards, 2.0, 2);
+        assert_eq!(
+            moves.iter().map(|m| m.index).collect::<Vec<_>>(),
+            [2, 3],
+            "stable cards lose the least"
+        );
+        assert!(moves.iter().all(|m| m.retention_change() < 0.0));
+        assert_eq!(
+            moves[0].retrievability_after,
+            cards[2].memory.retrievability(102.0)
+        );
+        assert_eq!(cards_to_postpone(&cards, 2.0, 10).len(), 3);
+    }
+
+    #[test]
+    fn advance() {
+        let cards = [
+            card(2.0, 3.0, 3.0),
+            card(50.0, 10.0, 60.0),
+            card(50.0, 55.0, 60.0),
+            card(5.0, 2.0, 6.0),
+        ];
+        let moves = cards_to_advance(&cards, 3);
+        assert_eq!(moves.iter().map(|m| m.index).collect::<Vec<_>>(), [2, 3, 1]);
+        assert!(moves.iter().all(|m| m.retention_change() > 0.0));
+        assert!(cards_to_advance(&cards, 0).is_empty());
+    }
+}
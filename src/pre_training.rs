@@ -1,4 +1,5 @@
 use crate::error::{FSRSError, Result};
+use crate::inference::FACTOR;
 use crate::FSRSItem;
 use itertools::Itertools;
 use ndarray::Array1;
@@ -6,20 +7,130 @@ use std::collections::HashMap;
 
 static R_S0_DEFAULT_ARRAY: &[(u32, f32); 4] = &[(1, 0.4), (2, 0.6), (3, 2.4), (4, 5.8)];
 
+/// Settings for [pretrain_with_config].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PretrainConfig {
+    /// The smallest initial stability, in days, that the curve fit will consider.
+    pub min_stability: f32,
+    /// The largest initial stability, in days, that the curve fit will consider.
+    pub max_stability: f32,
+    /// How many reviews the prior is worth. Each rating's fit is shrunk towards its default
+    /// stability by adding this many pseudo-reviews, recalled at `prior_retention` after that
+    /// many days. Raise it for small collections, or lower it to trust a large one's data.
+    pub prior_count: f32,
+    pub prior_retention: f32,
+}
+
+impl Default for PretrainConfig {
+    fn default() -> Self {
+        Self {
+            min_stability: 0.1,
+            max_stability: 100.0,
+            prior_count: 16.0,
+            prior_retention: 0.9,
+        }
+    }
+}
+
+/// The outcome of [pretrain_with_config], along with the settings that produced it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PretrainResult {
+    /// The initial stability after a first review rated Again, Hard, Good and Easy.
+    pub initial_stability: [f32; 4],
+    pub config: PretrainConfig,
+    /// The data behind each first rating that was seen, in order of rating, for plotting the
+    /// fitted forgetting curves.
+    pub groups: Vec<PretrainGroup>,
+}
+
+/// The cards whose first review had the same rating.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PretrainGroup {
+    pub first_rating: u32,
+    /// The number of cards with a second review.
+    pub count: u32,
+    /// The stability that best fits `points`, before ratings are adjusted so that a better
+    /// rating never gives a lower stability.
+    pub stability: f32,
+    /// Recall at the second review, for each interval after the first, excluding the prior.
+    pub points: Vec<AverageRecall>,
+}
+
+/// Estimate the initial stability after a first review rated Again, Hard, Good and Easy, from
+/// how often each card was recalled at its second review. Only items with exactly two reviews
+/// are used. Ratings with no data are filled in from the others, so at least one first rating
+/// must have been seen, or [FSRSError::NotEnoughData] is returned.
 pub fn pretrain(fsrs_items: Vec<FSRSItem>) -> Result<[f32; 4]> {
+    pretrain_items(&fsrs_items)
+}
+
+/// Like [pretrain], but borrows the items, so they can be used again afterwards.
+pub(crate) fn pretrain_items(fsrs_items: &[FSRSItem]) -> Result<[f32; 4]> {
+    fit_initial_stability(fsrs_items, PretrainConfig::default())
+        .map(|result| result.initial_stability)
+}
+
+/// Like [pretrain], but with the curve fit adjusted by `config`, and reporting the data used
+/// for each first rating. Stabilities filled in for ratings with no data may fall outside the
+/// configured range.
+pub fn pretrain_with_config(
+    fsrs_items: Vec<FSRSItem>,
+    config: PretrainConfig,
+) -> Result<PretrainResult> {
+    fit_initial_stability(&fsrs_items, config)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+fn fit_initial_stability(
+    fsrs_items: &[FSRSItem],
+    config: PretrainConfig,
+) -> Result<PretrainResult> {
+    if !(config.min_stability > 0.0 && config.min_stability < config.max_stability) {
+        return Err(FSRSError::InvalidInput {
+            field: "min_stability",
+        });
+    }
+    if !(0.0..).contains(&config.prior_count) {
+        return Err(FSRSError::InvalidInput {
+            field: "prior_count",
+        });
+    }
+    if !(config.prior_retention > 0.0 && config.prior_retention < 1.0) {
+        return Err(FSRSError::InvalidInput {
+            field: "prior_retention",
+        });
+    }
     let pretrainset = create_pretrain_data(fsrs_items);
     let rating_count = total_rating_count(&pretrainset);
-    let rating_stability = search_parameters(pretrainset);
-    smooth_and_fill(&mut rating_stability.clone(), &rating_count)
+    let points: HashMap<_, _> = pretrainset
+        .iter()
+        .map(|(&first_rating, data)| (first_rating, data.clone()))
+        .collect();
+    let rating_stability = search_parameters(pretrainset, &config);
+    let groups = points
+        .into_iter()
+        .sorted_by_key(|(first_rating, _)| *first_rating)
+        .map(|(first_rating, points)| PretrainGroup {
+            first_rating,
+            count: rating_count[&first_rating],
+            stability: rating_stability[&first_rating],
+            points,
+        })
+        .collect();
+    Ok(PretrainResult {
+        initial_stability: smooth_and_fill(&mut rating_stability.clone(), &rating_count)?,
+        config,
+        groups,
+    })
 }
 
 type FirstRating = u32;
 type Count = u32;
 
-fn create_pretrain_data(fsrs_items: Vec<FSRSItem>) -> HashMap<FirstRating, Vec<AverageRecall>> {
+fn create_pretrain_data(fsrs_items: &[FSRSItem]) -> HashMap<FirstRating, Vec<AverageRecall>> {
     // filter FSRSItem instances with exactly 2 reviews.
     let items: Vec<_> = fsrs_items
-        .into_iter()
+        .iter()
         .filter(|item| item.reviews.len() == 2)
         .collect();
 
@@ -30,7 +141,7 @@ fn create_pretrain_data(fsrs_items: Vec<FSRSItem>) -> HashMap<FirstRating, Vec<A
 
     for item in items {
         let first_rating = item.reviews[0].rating;
-        let second_delta_t = item.reviews[1].delta_t;
+        let second_delta_t = item.reviews[1].delta_t.to_bits();
         let second_label = if item.reviews[1].rating == 1 { 0 } else { 1 };
 
         let inner_map = groups.entry(first_rating).or_insert_with(HashMap::new);
@@ -47,7 +158,7 @@ fn create_pretrain_data(fsrs_items: Vec<FSRSItem>) -> HashMap<FirstRating, Vec<A
         for (second_delta_t, ratings) in inner_map {
             let avg = ratings.iter().map(|&x| x as f64).sum::<f64>() / ratings.len() as f64;
             data.push(AverageRecall {
-                delta_t: *second_delta_t as f32,
+                delta_t: f32::from_bits(*second_delta_t),
                 recall: avg as f32,
                 count: ratings.len() as f32,
             })
@@ -62,10 +173,11 @@ fn create_pretrain_data(fsrs_items: Vec<FSRSItem>) -> HashMap<FirstRating, Vec<A
 }
 
 /// The average pass rate & count for a single delta_t for a given first rating.
-struct AverageRecall {
-    delta_t: f32,
-    recall: f32,
-    count: f32,
+#[derive(Debug, Clone, PartialEq)]
+pub struct AverageRecall {
+    pub delta_t: f32,
+    pub recall: f32,
+    pub count: f32,
 }
 
 fn total_rating_count(
@@ -80,7 +192,7 @@ fn total_rating_count(
 }
 
 fn power_forgetting_curve(t: &Array1<f32>, s: f32) -> Array1<f32> {
-    1.0 / (1.0 + t / (9.0 * s))
+    1.0 / (1.0 + t / (FACTOR * s))
 }
 
 fn loss(
@@ -100,16 +212,19 @@ fn loss(
     logloss + l1
 }
 
-fn append_default_point(data: &mut Vec<AverageRecall>, default_s0: f32) {
-    data.push(AverageRecall {
-        delta_t: default_s0,
-        recall: 0.9,
-        count: 16.0,
-    });
+fn append_default_point(data: &mut Vec<AverageRecall>, default_s0: f32, config: &PretrainConfig) {
+    if config.prior_count > 0.0 {
+        data.push(AverageRecall {
+            delta_t: default_s0,
+            recall: config.prior_retention,
+            count: config.prior_count,
+        });
+    }
 }
 
 fn search_parameters(
     mut pretrainset: HashMap<FirstRating, Vec<AverageRecall>>,
+    config: &PretrainConfig,
 ) -> HashMap<u32, f32> {
     let mut optimal_stabilities = HashMap::new();
     let epsilon = f32::EPSILON;
@@ -118,14 +233,14 @@ fn search_parameters(
         let r_s0_default: HashMap<u32, f32> = R_S0_DEFAULT_ARRAY.iter().cloned().collect();
         let default_s0 = r_s0_default[first_rating];
 
-        append_default_point(data, default_s0);
+        append_default_point(data, default_s0, config);
 
         let delta_t = Array1::from_iter(data.iter().map(|d| d.delta_t));
         let recall = Array1::from_iter(data.iter().map(|d| d.recall));
         let count = Array1::from_iter(data.iter().map(|d| d.count));
 
-        let mut low = 0.1;
-        let mut high = 100.0;
+        let mut low = config.min_stability;
+        let mut high = config.max_stability;
         let mut optimal_s = 1.0;
 
         let mut iter = 0;
@@ -327,6 +442,7 @@ fn smooth_and_fill(
 #[cfg(test)]
 mod tests {
     use crate::dataset::split_data;
+    use crate::FSRSReview;
 
     use super::*;
 
@@ -377,7 +493,7 @@ mod tests {
                 },
             ],
         )]);
-        let actual = search_parameters(pretrainset);
+        let actual = search_parameters(pretrainset, &PretrainConfig::default());
         let expected = [(4, 1.2444091)].into_iter().collect();
         assert_eq!(actual, expected);
     }
@@ -392,6 +508,98 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_pretrain_with_config() {
+        use crate::convertor_tests::anki21_sample_file_converted_to_fsrs;
+        let pretrainset = split_data(anki21_sample_file_converted_to_fsrs()).0;
+        let config = PretrainConfig {
+            min_stability: 2.0,
+            max_stability: 5.0,
+            ..Default::default()
+        };
+        let result = pretrain_with_config(pretrainset, config).unwrap();
+        assert_eq!(result.config, config);
+        assert_eq!(
+            result
+                .groups
+                .iter()
+                .map(|group| group.first_rating)
+                .collect_vec(),
+            [1, 2, 3, 4]
+        );
+        for group in &result.groups {
+            assert_eq!(
+                group.points.iter().map(|point| point.count).sum::<f32>(),
+                group.count as f32
+            );
+            assert!(group.points.iter().all(|point| point.count > 0.0));
+        }
+        assert!(result
+            .initial_stability
+            .iter()
+            .all(|s| (2.0..=5.0).contains(s)));
+
+        let inverted = PretrainConfig {
+            min_stability: 5.0,
+            max_stability: 2.0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            pretrain_with_config(vec![], inverted),
+            Err(FSRSError::InvalidInput { .. })
+        ));
+    }
+
+    #[test]
+    fn test_pretrain_prior() {
+        let item = |second_rating| FSRSItem {
+            reviews: vec![
+                FSRSReview {
+                    rating: 3,
+                    delta_t: 0.0,
+                    duration_ms: None,
+                },
+                FSRSReview {
+                    rating: second_rating,
+                    delta_t: 20.0,
+                    duration_ms: None,
+                },
+            ],
+            group: None,
+        };
+        let items: Vec<_> = [1, 3, 3].into_iter().cycle().take(30).map(item).collect();
+        let good_stability = |prior_count| {
+            let config = PretrainConfig {
+                prior_count,
+                ..Default::default()
+            };
+            pretrain_with_config(items.clone(), config)
+                .unwrap()
+                .initial_stability[2]
+        };
+        let default_s0 = R_S0_DEFAULT_ARRAY[2].1;
+        assert!(
+            (good_stability(1000.0) - default_s0).abs() < (good_stability(1.0) - default_s0).abs()
+        );
+        assert_eq!(good_stability(16.0), pretrain(items.clone()).unwrap()[2]);
+    }
+
+    #[test]
+    fn test_pretrain_not_enough_data() {
+        let item = FSRSItem {
+            reviews: vec![FSRSReview {
+                rating: 3,
+                delta_t: 0.0,
+                duration_ms: None,
+            }],
+            group: None,
+        };
+        assert!(matches!(
+            pretrain(vec![item]),
+            Err(FSRSError::NotEnoughData)
+        ));
+    }
+
     #[test]
     fn test_smooth_and_fill() {
         let mut rating_stability = HashMap::from([(1, 0.4), (3, 2.4), (4, 5.8)]);
@@ -0,0 +1,404 @@
+//! Python bindings, built as the `fsrs_rs` extension module with maturin. Review histories are
+//! passed as lists of `(rating, delta_t)` tuples.
+
+use crate::{
+    FSRSError, FSRSItem, FSRSReview, ItemState, MemoryState, ModelEvaluation, NewCardStage,
+    NextStates, SimulatorConfig, FSRS,
+};
+use pyo3::exceptions::{PyInterruptedError, PyValueError};
+use pyo3::prelude::*;
+
+fn to_py_err(err: FSRSError) -> PyErr {
+    match err {
+        FSRSError::Interrupted => PyInterruptedError::new_err(err.to_string()),
+        _ => PyValueError::new_err(err.to_string()),
+    }
+}
+
+fn item_from_tuples(reviews: Vec<(u32, f32)>) -> FSRSItem {
+    FSRSItem {
+        reviews: reviews
+            .into_iter()
+            .map(|(rating, delta_t)| FSRSReview {
+                rating,
+                delta_t,
+                duration_ms: None,
+            })
+            .collect(),
+        group: None,
+    }
+}
+
+#[pyclass(name = "MemoryState")]
+#[derive(Debug, Clone, Copy)]
+pub struct PyMemoryState {
+    #[pyo3(get, set)]
+    pub stability: f32,
+    #[pyo3(get, set)]
+    pub difficulty: f32,
+}
+
+#[pymethods]
+impl PyMemoryState {
+    #[new]
+    fn new(stability: f32, difficulty: f32) -> Self {
+        Self {
+            stability,
+            difficulty,
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "MemoryState(stability={}, difficulty={})",
+            self.stability, self.difficulty
+        )
+    }
+}
+
+impl From<MemoryState> for PyMemoryState {
+    fn from(state: MemoryState) -> Self {
+        Self::new(state.stability, state.difficulty)
+    }
+}
+
+impl From<PyMemoryState> for MemoryState {
+    fn from(state: PyMemoryState) -> Self {
+        MemoryState {
+            stability: state.stability,
+            difficulty: state.difficulty,
+        }
+    }
+}
+
+#[pyclass(name = "ItemState")]
+#[derive(Debug, Clone, Copy)]
+pub struct PyItemState {
+    #[pyo3(get)]
+    pub memory: PyMemoryState,
+    #[pyo3(get)]
+    pub interval: u32,
+    #[pyo3(get)]
+    pub retrievability: f32,
+    #[pyo3(get)]
+    pub next_day_retrievability: f32,
+}
+
+impl From<ItemState> for PyItemState {
+    fn from(state: ItemState) -> Self {
+        Self {
+            memory: state.memory.into(),
+            interval: state.interval,
+            retrievability: state.retrievability,
+            next_day_retrievability: state.next_day_retrievability,
+        }
+    }
+}
+
+#[pyclass(name = "NextStates")]
+#[derive(Debug, Clone, Copy)]
+pub struct PyNextStates {
+    #[pyo3(get)]
+    pub again: PyItemState,
+    #[pyo3(get)]
+    pub hard: PyItemState,
+    #[pyo3(get)]
+    pub good: PyItemState,
+    #[pyo3(get)]
+    pub easy: PyItemState,
+}
+
+impl From<NextStates> for PyNextStates {
+    fn from(states: NextStates) -> Self {
+        Self {
+            again: states.again.into(),
+            hard: states.hard.into(),
+            good: states.good.into(),
+            easy: states.easy.into(),
+        }
+    }
+}
+
+#[pyclass(name = "ModelEvaluation")]
+#[derive(Debug, Clone, Copy)]
+pub struct PyModelEvaluation {
+    #[pyo3(get)]
+    pub log_loss: f32,
+    #[pyo3(get)]
+    pub rmse_bins: f32,
+    #[pyo3(get)]
+    pub auc: f32,
+    #[pyo3(get)]
+    pub brier: f32,
+}
+
+impl From<ModelEvaluation> for PyModelEvaluation {
+    fn from(evaluation: ModelEvaluation) -> Self {
+        Self {
+            log_loss: evaluation.log_loss,
+            rmse_bins: evaluation.rmse_bins,
+            auc: evaluation.auc,
+            brier: evaluation.brier,
+        }
+    }
+}
+
+#[pyclass(name = "SimulatorConfig")]
+#[derive(Debug, Clone, Default)]
+pub struct PySimulatorConfig(SimulatorConfig);
+
+#[pymethods]
+impl PySimulatorConfig {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+
+    #[getter]
+    fn deck_size(&self) -> usize {
+        self.0.deck_size
+    }
+
+    #[setter]
+    fn set_deck_size(&mut self, value: usize) {
+        self.0.deck_size = value;
+    }
+
+    #[getter]
+    fn learn_span(&self) -> usize {
+        self.0.learn_span
+    }
+
+    #[setter]
+    fn set_learn_span(&mut self, value: usize) {
+        self.0.learn_span = value;
+    }
+
+    #[getter]
+    fn max_cost_perday(&self) -> f64 {
+        self.0.max_cost_perday
+    }
+
+    #[setter]
+    fn set_max_cost_perday(&mut self, value: f64) {
+        self.0.max_cost_perday = value;
+    }
+
+    #[getter]
+    fn max_ivl(&self) -> f64 {
+        self.0.max_ivl
+    }
+
+    #[setter]
+    fn set_max_ivl(&mut self, value: f64) {
+        self.0.max_ivl = value;
+    }
+
+    #[getter]
+    fn max_reviews_per_day(&self) -> usize {
+        self.0.max_reviews_per_day
+    }
+
+    #[setter]
+    fn set_max_reviews_per_day(&mut self, value: usize) {
+        self.0.max_reviews_per_day = value;
+    }
+
+    #[getter]
+    fn max_new_per_day(&self) -> usize {
+        self.0.max_new_per_day
+    }
+
+    #[setter]
+    fn set_max_new_per_day(&mut self, value: usize) {
+        self.0.max_new_per_day = value;
+    }
+
+    /// `(after_learnt, max_new_per_day)` stages, changing the new card limit as the deck is
+    /// learnt.
+    #[getter]
+    fn new_card_ramp(&self) -> Vec<(usize, usize)> {
+        self.0
+            .new_card_ramp
+            .iter()
+            .map(|stage| (stage.after_learnt, stage.max_new_per_day))
+            .collect()
+    }
+
+    #[setter]
+    fn set_new_card_ramp(&mut self, value: Vec<(usize, usize)>) {
+        self.0.new_card_ramp = value
+            .into_iter()
+            .map(|(after_learnt, max_new_per_day)| NewCardStage {
+                after_learnt,
+                max_new_per_day,
+            })
+            .collect();
+    }
+
+    /// Multipliers for the daily limits on each day of the week, starting from the first
+    /// simulated day.
+    #[getter]
+    fn weekday_load(&self) -> [f64; 7] {
+        self.0.weekday_load
+    }
+
+    #[setter]
+    fn set_weekday_load(&mut self, value: [f64; 7]) {
+        self.0.weekday_load = value;
+    }
+
+    /// Seconds taken by a review rated Again, Hard, Good and Easy.
+    #[getter]
+    fn review_costs(&self) -> [f64; 4] {
+        self.0.review_costs
+    }
+
+    #[setter]
+    fn set_review_costs(&mut self, value: [f64; 4]) {
+        self.0.review_costs = value;
+    }
+
+    #[getter]
+    fn learn_cost(&self) -> f64 {
+        self.0.learn_cost
+    }
+
+    #[setter]
+    fn set_learn_cost(&mut self, value: f64) {
+        self.0.learn_cost = value;
+    }
+
+    #[getter]
+    fn leech_threshold(&self) -> Option<usize> {
+        self.0.leech_threshold
+    }
+
+    #[setter]
+    fn set_leech_threshold(&mut self, value: Option<usize>) {
+        self.0.leech_threshold = value;
+    }
+
+    #[getter]
+    fn leech_suspend_prob(&self) -> f64 {
+        self.0.leech_suspend_prob
+    }
+
+    #[setter]
+    fn set_leech_suspend_prob(&mut self, value: f64) {
+        self.0.leech_suspend_prob = value;
+    }
+
+    #[getter]
+    fn seed(&self) -> u64 {
+        self.0.seed
+    }
+
+    #[setter]
+    fn set_seed(&mut self, value: u64) {
+        self.0.seed = value;
+    }
+
+    #[getter]
+    fn max_threads(&self) -> Option<usize> {
+        self.0.max_threads
+    }
+
+    #[setter]
+    fn set_max_threads(&mut self, value: Option<usize>) {
+        self.0.max_threads = value;
+    }
+
+    #[getter]
+    fn relearning_cost(&self) -> f64 {
+        self.0.relearning_cost
+    }
+
+    #[setter]
+    fn set_relearning_cost(&mut self, value: f64) {
+        self.0.relearning_cost = value;
+    }
+}
+
+#[pyclass(name = "FSRS")]
+pub struct PyFSRS {
+    inner: FSRS,
+}
+
+#[pymethods]
+impl PyFSRS {
+    /// Weights may be omitted or empty to use the default values.
+    #[new]
+    #[pyo3(signature = (weights = None))]
+    fn new(weights: Option<Vec<f32>>) -> PyResult<Self> {
+        Ok(Self {
+            inner: FSRS::new(Some(weights.unwrap_or_default().as_slice())).map_err(to_py_err)?,
+        })
+    }
+
+    fn memory_state(&self, reviews: Vec<(u32, f32)>) -> PyMemoryState {
+        self.inner.memory_state(item_from_tuples(reviews)).into()
+    }
+
+    #[pyo3(signature = (
+        current_memory_state,
+        desired_retention,
+        days_elapsed,
+        maximum_interval = 36500
+    ))]
+    fn next_states(
+        &self,
+        current_memory_state: Option<PyMemoryState>,
+        desired_retention: f32,
+        days_elapsed: f32,
+        maximum_interval: u32,
+    ) -> PyNextStates {
+        self.inner
+            .next_states(
+                current_memory_state.map(Into::into),
+                desired_retention,
+                days_elapsed,
+                maximum_interval,
+            )
+            .into()
+    }
+
+    /// Training releases the GIL, so other Python threads keep running.
+    fn compute_weights(&self, py: Python<'_>, items: Vec<Vec<(u32, f32)>>) -> PyResult<Vec<f32>> {
+        let items: Vec<FSRSItem> = items.into_iter().map(item_from_tuples).collect();
+        py.allow_threads(|| self.inner.compute_weights(items, None))
+            .map_err(to_py_err)
+    }
+
+    fn evaluate(&self, py: Python<'_>, items: Vec<Vec<(u32, f32)>>) -> PyResult<PyModelEvaluation> {
+        let items: Vec<FSRSItem> = items.into_iter().map(item_from_tuples).collect();
+        py.allow_threads(|| self.inner.evaluate(items, |_| true))
+            .map(Into::into)
+            .map_err(to_py_err)
+    }
+
+    /// Run the simulator to find the desired retention that maximizes the number of cards
+    /// remembered, for the given weights.
+    #[pyo3(signature = (weights, config = None))]
+    fn optimal_retention(
+        &self,
+        py: Python<'_>,
+        weights: Vec<f32>,
+        config: Option<PySimulatorConfig>,
+    ) -> PyResult<f64> {
+        let config = config.unwrap_or_default().0;
+        py.allow_threads(|| self.inner.optimal_retention(&config, &weights, |_| true))
+            .map_err(to_py_err)
+    }
+}
+
+#[pymodule]
+fn fsrs_rs(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PyFSRS>()?;
+    m.add_class::<PyMemoryState>()?;
+    m.add_class::<PyItemState>()?;
+    m.add_class::<PyNextStates>()?;
+    m.add_class::<PyModelEvaluation>()?;
+    m.add_class::<PySimulatorConfig>()?;
+    Ok(())
+}
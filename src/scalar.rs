@@ -0,0 +1,324 @@
+use crate::dataset::is_same_day;
+use crate::error::{FSRSError, Result};
+use crate::inference::{ForgettingCurve, ItemState, MemoryState, NextStates, Weights};
+use crate::memory_model::MemoryModel;
+use crate::weight_clipper::clip_weights;
+use crate::{FSRSItem, DEFAULT_WEIGHTS};
+
+/// A scheduler that mirrors the inference methods of [crate::FSRS], computed one card at a time
+/// in double precision instead of with f32 tensors. Rounding intervals from f32 results can land
+/// on different days depending on the platform's math routines; the extra precision makes this
+/// much less likely. A forgetting curve or interval multipliers given to [crate::FSRS] must be
+/// given to this too, with [ScalarFSRS::with_forgetting_curve] and
+/// [ScalarFSRS::with_interval_multipliers], for the two to schedule alike.
+#[derive(Debug, Clone)]
+pub struct ScalarFSRS {
+    w: Vec<f64>,
+    curve: ForgettingCurve,
+    interval_multipliers: [f32; 4],
+}
+
+impl ScalarFSRS {
+    /// Weights may be an empty slice to use the default values instead.
+    pub fn new(weights: &Weights) -> Result<Self> {
+        let weights = if weights.is_empty() {
+            DEFAULT_WEIGHTS
+        } else if matches!(weights.len(), 17 | 19) {
+            weights
+        } else {
+            return Err(FSRSError::InvalidWeights);
+        };
+        Ok(Self {
+            w: clip_weights(weights, false)
+                .into_iter()
+                .map(f64::from)
+                .collect(),
+            curve: ForgettingCurve::default(),
+            interval_multipliers: [1.0; 4],
+        })
+    }
+
+    /// Use a different forgetting curve from the default, as [crate::FSRS::with_forgetting_curve]
+    /// does.
+    pub fn with_forgetting_curve(mut self, curve: ForgettingCurve) -> Result<Self> {
+        curve.validate()?;
+        self.curve = curve;
+        Ok(self)
+    }
+
+    pub fn forgetting_curve(&self) -> ForgettingCurve {
+        self.curve
+    }
+
+    /// Scale the interval scheduled for each rating, from Again to Easy, as
+    /// [crate::FSRS::with_interval_multipliers] does.
+    pub fn with_interval_multipliers(mut self, multipliers: [f32; 4]) -> Result<Self> {
+        if multipliers.iter().any(|m| !m.is_finite() || *m <= 0.0) {
+            return Err(FSRSError::InvalidInput {
+                field: "interval_multipliers",
+            });
+        }
+        self.interval_multipliers = multipliers;
+        Ok(self)
+    }
+
+    pub fn interval_multipliers(&self) -> [f32; 4] {
+        self.interval_multipliers
+    }
+
+    /// Calculate the current memory state for a given card's history of reviews.
+    pub fn memory_state(&self, item: &FSRSItem) -> Result<MemoryState> {
+        item.check_reviews(0)?;
+        let mut state = None;
+        for review in &item.reviews {
+            state = Some(self.step(state, review.delta_t.into(), review.rating));
+        }
+        let (stability, difficulty) = state.expect("checked above");
+        Ok(MemoryState {
+            stability: stability as f32,
+            difficulty: difficulty as f32,
+        })
+    }
+
+    /// Calculate the next memory state and interval for each possible rating, as
+    /// [crate::FSRS::next_states] does.
+    pub fn next_states(
+        &self,
+        current_memory_state: Option<MemoryState>,
+        desired_retention: f32,
+        days_elapsed: f32,
+        maximum_interval: u32,
+    ) -> NextStates {
+        let current = current_memory_state
+            .map(|state| (f64::from(state.stability), f64::from(state.difficulty)));
+        let get_next_state = |rating| {
+            let memory = match current_memory_state {
+                // the state is unchanged on a same-day review, unless same-day reviews
+                // are modelled
+                Some(state) if is_same_day(days_elapsed) && !self.has_short_term_weights() => state,
+                _ => {
+                    let (stability, difficulty) = self.step(current, days_elapsed.into(), rating);
+                    MemoryState {
+                        stability: stability as f32,
+                        difficulty: difficulty as f32,
+                    }
+                }
+            };
+            let multiplier = self.interval_multipliers[rating as usize - 1];
+            let interval = (self.next_interval(memory.stability.into(), desired_retention.into())
+                * f64::from(multiplier))
+            .round()
+            .max(1.0)
+            .min(maximum_interval as f64) as u32;
+            ItemState::new(memory, interval, self.curve)
+        };
+        NextStates {
+            again: get_next_state(1),
+            hard: get_next_state(2),
+            good: get_next_state(3),
+            easy: get_next_state(4),
+        }
+    }
+
+    fn has_short_term_weights(&self) -> bool {
+        self.w.len() >= 19
+    }
+
+    fn retrievability_at(&self, stability: f64, delta_t: f64) -> f64 {
+        let ForgettingCurve { decay, factor } = self.curve;
+        (1.0 + delta_t / (f64::from(factor) * stability)).powf(decay.into())
+    }
+
+    fn next_interval(&self, stability: f64, desired_retention: f64) -> f64 {
+        let ForgettingCurve { decay, factor } = self.curve;
+        f64::from(factor)
+            * stability
+            * ((1.0 / desired_retention).powf(-1.0 / f64::from(decay)) - 1.0)
+    }
+
+    fn step(&self, state: Option<(f64, f64)>, delta_t: f64, rating: u32) -> (f64, f64) {
+        let w = &self.w;
+        let rating_offset = rating as f64 - 3.0;
+        let (stability, difficulty) = match state {
+            None => (
+                w[rating as usize - 1],
+                (w[4] - w[5] * rating_offset).clamp(1.0, 10.0),
+            ),
+            Some((last_s, last_d)) => {
+                let r = self.retrievability_at(last_s, delta_t);
+                let next_d = last_d - w[6] * rating_offset;
+                let difficulty = (w[7] * (w[4] - next_d) + next_d).clamp(1.0, 10.0);
+                let stability = if is_same_day(delta_t as f32) && self.has_short_term_weights() {
+                    last_s * (w[17] * (rating_offset + w[18])).exp()
+                } else if rating == 1 {
+                    w[11]
+                        * difficulty.powf(-w[12])
+                        * ((last_s + 1.0).powf(w[13]) - 1.0)
+                        * ((1.0 - r) * w[14]).exp()
+                } else {
+                    let hard_penalty = if rating == 2 { w[15] } else { 1.0 };
+                    let easy_bonus = if rating == 4 { w[16] } else { 1.0 };
+                    last_s
+                        * (w[8].exp()
+                            * (11.0 - difficulty)
+                            * last_s.powf(-w[9])
+                            * (((1.0 - r) * w[10]).exp() - 1.0)
+                            * hard_penalty
+                            * easy_bonus
+                            + 1.0)
+                };
+                (stability, difficulty)
+            }
+        };
+        (stability.clamp(0.1, 36500.0), difficulty)
+    }
+}
+
+impl MemoryModel for ScalarFSRS {
+    fn initial_state(&self, rating: u32) -> MemoryState {
+        let (stability, difficulty) = self.step(None, 0.0, rating);
+        MemoryState {
+            stability: stability as f32,
+            difficulty: difficulty as f32,
+        }
+    }
+
+    fn next_state(&self, state: MemoryState, delta_t: f32, rating: u32) -> MemoryState {
+        let current = (f64::from(state.stability), f64::from(state.difficulty));
+        let (stability, difficulty) = self.step(Some(current), delta_t.into(), rating);
+        MemoryState {
+            stability: stability as f32,
+            difficulty: difficulty as f32,
+        }
+    }
+
+    fn retrievability(&self, state: MemoryState, delta_t: f32) -> f32 {
+        self.retrievability_at(state.stability.into(), delta_t.into()) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "training")]
+    use crate::{convertor_tests::anki21_sample_file_converted_to_fsrs, FSRS};
+
+    #[cfg(feature = "training")]
+    fn assert_close(a: MemoryState, b: MemoryState) {
+        assert!((a.stability - b.stability).abs() <= b.stability * 1e-4);
+        assert!((a.difficulty - b.difficulty).abs() <= 1e-4);
+    }
+
+    #[test]
+    #[cfg(feature = "training")]
+    fn matches_tensor_implementation() -> Result<()> {
+        let curve = ForgettingCurve::with_decay(-0.5);
+        let multipliers = [1.0, 0.8, 1.0, 1.3];
+        for (fsrs, scalar) in [
+            (FSRS::new(Some(&[]))?, ScalarFSRS::new(&[])?),
+            (
+                FSRS::new(Some(&[]))?
+                    .with_forgetting_curve(curve)?
+                    .with_interval_multipliers(multipliers)?,
+                ScalarFSRS::new(&[])?
+                    .with_forgetting_curve(curve)?
+                    .with_interval_multipliers(multipliers)?,
+            ),
+        ] {
+            for item in anki21_sample_file_converted_to_fsrs()
+                .into_iter()
+                .step_by(53)
+            {
+                let state = scalar.memory_state(&item)?;
+                assert_close(state, fsrs.memory_state(item));
+                let expected = fsrs.next_states(Some(state), 0.9, 21.0, 36500);
+                let actual = scalar.next_states(Some(state), 0.9, 21.0, 36500);
+                for (a, b) in [
+                    (actual.again, expected.again),
+                    (actual.hard, expected.hard),
+                    (actual.good, expected.good),
+                    (actual.easy, expected.easy),
+                ] {
+                    assert_close(a.memory, b.memory);
+                    // f32 and f64 may round an interval near a half day differently
+                    assert!(a.interval.abs_diff(b.interval) <= 1);
+                    assert!((a.retrievability - b.retrievability).abs() <= 1e-4);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn next_states() -> Result<()> {
+        let scalar = ScalarFSRS::new(&[])?;
+        let new_card = scalar.next_states(None, 0.9, 0.0, 36500);
+        assert_eq!(new_card.again.memory.stability, DEFAULT_WEIGHTS[0]);
+        assert_eq!(new_card.easy.memory.stability, DEFAULT_WEIGHTS[3]);
+        let state = new_card.good.memory;
+        assert_eq!(
+            scalar.next_states(Some(state), 0.9, 0.0, 36500).good.memory,
+            state
+        );
+        let intervals = scalar.next_states(Some(state), 0.9, 4.0, 10);
+        assert!(intervals.again.interval < intervals.good.interval);
+        assert_eq!(intervals.easy.interval, 10);
+        Ok(())
+    }
+
+    #[test]
+    fn memory_model() -> Result<()> {
+        let scalar = ScalarFSRS::new(&[])?;
+        let item = FSRSItem {
+            reviews: vec![
+                crate::FSRSReview {
+                    rating: 3,
+                    delta_t: 0.0,
+                    duration_ms: None,
+                },
+                crate::FSRSReview {
+                    rating: 3,
+                    delta_t: 5.0,
+                    duration_ms: None,
+                },
+            ],
+            group: None,
+        };
+        let replayed = scalar
+            .replay(item.reviews.iter().map(|r| (r.rating, r.delta_t)))
+            .unwrap();
+        assert_eq!(replayed, scalar.memory_state(&item)?);
+        let r = MemoryModel::retrievability(&scalar, replayed, replayed.stability);
+        assert!((r - 0.9).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_weights() {
+        assert!(ScalarFSRS::new(&[]).is_ok());
+        assert!(ScalarFSRS::new(&[1.0]).is_err());
+    }
+
+    #[test]
+    fn invalid_settings() -> Result<()> {
+        let scalar = ScalarFSRS::new(&[])?;
+        assert!(matches!(
+            scalar.memory_state(&FSRSItem {
+                reviews: vec![],
+                group: None,
+            }),
+            Err(FSRSError::InvalidItem {
+                reason: "no reviews",
+                ..
+            })
+        ));
+        assert!(scalar
+            .clone()
+            .with_forgetting_curve(ForgettingCurve::with_decay(0.5))
+            .is_err());
+        assert!(scalar
+            .with_interval_multipliers([1.0, 0.0, 1.0, 1.0])
+            .is_err());
+        Ok(())
+    }
+}
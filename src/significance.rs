@@ -0,0 +1,215 @@
+use crate::error::{FSRSError, Result};
+use crate::inference::{review_log_loss, Weights};
+use crate::{FSRSItem, FSRS};
+use burn::tensor::backend::Backend;
+
+/// The result of [FSRS::paired_t_test].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignificanceTest {
+    /// The number of items compared.
+    pub count: usize,
+    /// The mean per-item log loss of the other weights, minus that of the current weights.
+    /// Negative when the other weights fit better.
+    pub mean_difference: f32,
+    /// The mean difference divided by the standard deviation of the differences (Cohen's d
+    /// for paired samples), which doesn't grow with the number of items as the t statistic
+    /// does.
+    pub effect_size: f32,
+    pub t_statistic: f32,
+    /// The two-sided p-value: the probability of a difference at least this large if both sets
+    /// of weights fit equally well.
+    pub p_value: f32,
+}
+
+impl SignificanceTest {
+    /// True if the other weights fit better, and the difference is significant at the given
+    /// level, such as 0.05.
+    pub fn other_is_better(&self, significance_level: f32) -> bool {
+        self.mean_difference < 0.0 && self.p_value < significance_level
+    }
+}
+
+impl<B: Backend> FSRS<B> {
+    /// Run a paired t-test on the per-item log loss of the weights this instance was created
+    /// with and of `other_weights`, on the same items, so a claim that newly optimized weights
+    /// are better can be gated on the difference being significant rather than noise. Items
+    /// are treated as independent, although items from the same card are not quite.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn paired_t_test(
+        &self,
+        items: &[FSRSItem],
+        other_weights: &Weights,
+    ) -> Result<SignificanceTest> {
+        if items.len() < 2 {
+            return Err(FSRSError::NotEnoughData);
+        }
+        let other = self.with_other_weights(other_weights)?;
+        let differences: Vec<f64> = self
+            .predict(items)?
+            .into_iter()
+            .zip(other.predict(items)?)
+            .map(|((current, outcome), (other, _))| {
+                f64::from(review_log_loss(other, outcome) - review_log_loss(current, outcome))
+            })
+            .collect();
+
+        let n = differences.len() as f64;
+        let mean = differences.iter().sum::<f64>() / n;
+        let variance = differences.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let sd = variance.sqrt();
+        let (effect_size, t_statistic) = if sd > 0.0 {
+            (mean / sd, mean / (sd / n.sqrt()))
+        } else if mean == 0.0 {
+            (0.0, 0.0)
+        } else {
+            (mean.signum() * f64::INFINITY, mean.signum() * f64::INFINITY)
+        };
+        Ok(SignificanceTest {
+            count: differences.len(),
+            mean_difference: mean as f32,
+            effect_size: effect_size as f32,
+            t_statistic: t_statistic as f32,
+            p_value: t_test_p_value(t_statistic, n - 1.0) as f32,
+        })
+    }
+}
+
+/// The two-sided p-value of Student's t distribution with `df` degrees of freedom.
+fn t_test_p_value(t: f64, df: f64) -> f64 {
+    regularized_incomplete_beta(df / (df + t * t), df / 2.0, 0.5)
+}
+
+/// `I_x(a, b)`, evaluated with a continued fraction, as in Numerical Recipes.
+fn regularized_incomplete_beta(x: f64, a: f64, b: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // the continued fraction converges quickly only on this side of the mean
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(x, a, b) / a
+    } else {
+        1.0 - front * beta_continued_fraction(1.0 - x, b, a) / b
+    }
+}
+
+fn beta_continued_fraction(x: f64, a: f64, b: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    let mut c = 1.0;
+    let mut d = 1.0 - (a + b) * x / (a + 1.0);
+    if d.abs() < TINY {
+        d = TINY;
+    }
+    d = 1.0 / d;
+    let mut h = d;
+    // the number of terms needed grows with the square root of the larger of a and b
+    for m in 1..10_000 {
+        let m = m as f64;
+        for numerator in [
+            m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m)),
+            -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0)),
+        ] {
+            d = 1.0 + numerator * d;
+            if d.abs() < TINY {
+                d = TINY;
+            }
+            c = 1.0 + numerator / c;
+            if c.abs() < TINY {
+                c = TINY;
+            }
+            d = 1.0 / d;
+            h *= d * c;
+        }
+        if (d * c - 1.0).abs() < 1e-12 {
+            break;
+        }
+    }
+    h
+}
+
+/// The natural log of the gamma function, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let series = COEFFICIENTS
+        .iter()
+        .enumerate()
+        .fold(1.000000000190015, |sum, (i, c)| {
+            sum + c / (x + 1.0 + i as f64)
+        });
+    -tmp + ((2.0 * std::f64::consts::PI).sqrt() * series / x).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::convertor_tests::anki21_sample_file_converted_to_fsrs;
+    use crate::DEFAULT_WEIGHTS;
+
+    #[test]
+    fn t_distribution() {
+        assert!((ln_gamma(5.0) - 24f64.ln()).abs() < 1e-10);
+        assert!((t_test_p_value(2.0, 10.0) - 0.07338803).abs() < 1e-6);
+        assert!((t_test_p_value(-2.0, 10.0) - 0.07338803).abs() < 1e-6);
+        assert!((t_test_p_value(1.9623391, 1000.0) - 0.05).abs() < 1e-5);
+        assert_eq!(t_test_p_value(0.0, 10.0), 1.0);
+        assert_eq!(t_test_p_value(f64::INFINITY, 10.0), 0.0);
+    }
+
+    #[test]
+    fn paired_t_test() -> Result<()> {
+        let items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(5)
+            .collect();
+        let weights = [
+            0.81497127,
+            1.5411042,
+            4.007436,
+            9.045982,
+            4.9264183,
+            1.039322,
+            0.93803364,
+            0.0,
+            1.5530516,
+            0.10299722,
+            0.9981442,
+            2.210701,
+            0.018248068,
+            0.3422524,
+            1.3384504,
+            0.22278537,
+            2.6646678,
+        ];
+        let fsrs = FSRS::new(Some(&weights))?;
+
+        let same = fsrs.paired_t_test(&items, &weights)?;
+        assert_eq!(same.count, items.len());
+        assert_eq!(same.mean_difference, 0.0);
+        assert_eq!(same.p_value, 1.0);
+        assert!(!same.other_is_better(0.05));
+
+        let test = fsrs.paired_t_test(&items, DEFAULT_WEIGHTS)?;
+        assert!((0.0..=1.0).contains(&test.p_value));
+        assert_eq!(test.mean_difference.signum(), test.t_statistic.signum());
+        assert_eq!(test.effect_size.signum(), test.t_statistic.signum());
+
+        assert!(matches!(
+            fsrs.paired_t_test(&items[..1], DEFAULT_WEIGHTS),
+            Err(FSRSError::NotEnoughData)
+        ));
+        Ok(())
+    }
+}
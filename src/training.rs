@@ -1,17 +1,24 @@
-use crate::batch_shuffle::BatchShuffledDataset;
+use crate::batch_shuffle::{BatchShuffle, BatchShuffledDataset};
 use crate::cosine_annealing::CosineAnnealingLR;
-use crate::dataset::{split_data, FSRSBatch, FSRSBatcher, FSRSDataset, FSRSItem};
+use crate::dataset::{
+    group_items, split_checked_data, FSRSBatch, FSRSBatcher, FSRSDataset, FSRSItem, FilterConfig,
+    SameDayReviews,
+};
+use crate::disk_dataset::DiskDataset;
 use crate::error::Result;
-use crate::model::{Model, ModelConfig};
-use crate::pre_training::pretrain;
+use crate::inference::{ItemProgress, ModelEvaluation, Weights};
+use crate::model::{weights_to_model, Model, ModelConfig};
+use crate::optimal_retention::{percentile, with_thread_limit};
+use crate::pre_training::{pretrain, pretrain_items};
 use crate::weight_clipper::weight_clipper;
 use crate::{FSRSError, FSRS};
 use burn::autodiff::ADBackendDecorator;
+use burn::data::dataset::Dataset;
 use burn::module::Module;
 use burn::optim::AdamConfig;
 use burn::record::{FullPrecisionSettings, PrettyJsonFileRecorder, Recorder};
 use burn::tensor::backend::Backend;
-use burn::tensor::{Int, Tensor};
+use burn::tensor::{Data, Int, Shape, Tensor};
 use burn::train::metric::dashboard::{DashboardMetricState, DashboardRenderer, TrainingProgress};
 use burn::train::{ClassificationOutput, TrainOutput, TrainStep, TrainingInterrupter, ValidStep};
 use burn::{
@@ -20,8 +27,15 @@ use burn::{
 };
 use core::marker::PhantomData;
 use log::info;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
+use std::pin::Pin;
 use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
 
 pub struct BCELoss<B: Backend> {
     backend: PhantomData<B>,
@@ -79,7 +93,20 @@ impl<B: ADBackend> TrainStep<FSRSBatch<B>, ClassificationOutput<B>> for Model<B>
             batch.delta_ts,
             batch.labels,
         );
-        let mut gradients = item.loss.backward();
+        let loss = match &self.config.prior {
+            Some(prior) => {
+                let prior = Tensor::from_floats(Data::new(
+                    prior.clone(),
+                    Shape {
+                        dims: [prior.len()],
+                    },
+                ));
+                let distance = (self.w.val() - prior).powf(2.0).sum();
+                item.loss.clone() + distance * self.config.prior_strength
+            }
+            None => item.loss.clone(),
+        };
+        let mut gradients = loss.backward();
 
         if self.config.freeze_stability {
             gradients = self.freeze_initial_stability(gradients);
@@ -96,7 +123,7 @@ impl<B: ADBackend> TrainStep<FSRSBatch<B>, ClassificationOutput<B>> for Model<B>
         Self: burn::module::ADModule<B1>,
     {
         let mut model = optim.step(lr, self, grads);
-        model.w = Param::from(weight_clipper(model.w.val()));
+        model.w = Param::from(weight_clipper(model.w.val(), model.config.trainable_decay));
         model
     }
 }
@@ -121,10 +148,24 @@ pub struct ProgressState {
     pub want_abort: bool,
 }
 
+/// Events passed to the callback given to [FSRS::compute_weights_with_callback].
+#[derive(Debug, Clone)]
+pub enum TrainingEvent {
+    /// Items processed so far, counted across all epochs.
+    Progress(ItemProgress),
+    /// An epoch has been completed.
+    EpochFinished { epoch: usize, epoch_total: usize },
+    /// Training has completed. Contains how well the new weights fit the provided items.
+    Finished(ModelEvaluation),
+}
+
+pub(crate) type TrainingCallback = Box<dyn FnMut(TrainingEvent) -> bool + Send>;
+
 #[derive(Clone, Default)]
 pub struct ProgressCollector {
     pub state: Arc<Mutex<ProgressState>>,
     pub interrupter: TrainingInterrupter,
+    pub callback: Option<Arc<Mutex<TrainingCallback>>>,
 }
 
 impl ProgressCollector {
@@ -157,6 +198,8 @@ impl DashboardRenderer for ProgressCollector {
 
     fn render_train(&mut self, item: TrainingProgress) {
         let mut info = self.state.lock().unwrap();
+        let previous_epoch = info.epoch;
+        trace_epoch(previous_epoch, &item);
         info.epoch = item.epoch;
         info.epoch_total = item.epoch_total;
         info.items_processed = item.progress.items_processed;
@@ -164,11 +207,99 @@ impl DashboardRenderer for ProgressCollector {
         if info.want_abort {
             self.interrupter.stop();
         }
+        if let Some(callback) = &self.callback {
+            let mut callback = callback.lock().unwrap();
+            let mut keep_going = true;
+            if previous_epoch != 0 && previous_epoch != item.epoch {
+                keep_going &= (*callback)(TrainingEvent::EpochFinished {
+                    epoch: previous_epoch,
+                    epoch_total: info.epoch_total,
+                });
+            }
+            keep_going &= (*callback)(TrainingEvent::Progress(ItemProgress {
+                current: info.current(),
+                total: info.total(),
+            }));
+            if !keep_going {
+                self.interrupter.stop();
+            }
+        }
     }
 
     fn render_valid(&mut self, _item: TrainingProgress) {}
 }
 
+/// Settings for how weights are trained, set with [FSRS::with_training_options].
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrainingOptions {
+    pub shuffle: BatchShuffle,
+    /// How items with two reviews are filtered before pretraining.
+    pub filter: FilterConfig,
+    pub same_day_reviews: SameDayReviews,
+    /// If set, only the last this many reviews before each review are trained on, treating the
+    /// first of them as the card's first review. This bounds the padding needed for cards with
+    /// very long histories, at a small cost in accuracy.
+    pub max_history: Option<usize>,
+    /// Seeds the shuffling and the model's initialization, so the same items always train to
+    /// the same weights.
+    pub seed: u64,
+    /// Caps the threads used for training, including preparing and batching the items, such as
+    /// to limit heat on mobile devices. All available cores are used if None.
+    pub max_threads: Option<usize>,
+    /// Keep the training items in a temporary file rather than in memory while training, so
+    /// large collections can be trained on devices with little memory. Items are read back as
+    /// each batch is built, which makes training somewhat slower.
+    pub on_disk: bool,
+}
+
+impl Default for TrainingOptions {
+    fn default() -> Self {
+        Self {
+            shuffle: BatchShuffle::default(),
+            filter: FilterConfig::default(),
+            same_day_reviews: SameDayReviews::default(),
+            max_history: None,
+            seed: 42,
+            max_threads: None,
+            on_disk: false,
+        }
+    }
+}
+
+/// Settings for [FSRS::fine_tune_weights].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FineTuneConfig {
+    /// How strongly weights are kept near the ones being fine-tuned. The penalty is divided
+    /// by the number of training items, so it dominates for small collections and fades as
+    /// reviews accumulate; 0 trains without it.
+    pub strength: f32,
+}
+
+impl Default for FineTuneConfig {
+    fn default() -> Self {
+        Self { strength: 100.0 }
+    }
+}
+
+/// Settings for [FSRS::update_weights].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpdateConfig {
+    /// Passes over the new items.
+    pub num_epochs: usize,
+    /// Kept well below the rate used for full training, so a day of reviews shifts the weights
+    /// only slightly.
+    pub learning_rate: f64,
+}
+
+impl Default for UpdateConfig {
+    fn default() -> Self {
+        Self {
+            num_epochs: 2,
+            learning_rate: 1e-3,
+        }
+    }
+}
+
 #[derive(Config)]
 pub(crate) struct TrainingConfig {
     pub model: ModelConfig,
@@ -187,35 +318,509 @@ pub(crate) struct TrainingConfig {
 
 impl<B: Backend> FSRS<B> {
     /// Calculate appropriate weights for the provided review history.
+    ///
+    /// Items may come from any iterator, such as a database cursor; they are split into the
+    /// pretraining and training sets as they are consumed.
+    ///
+    /// If this was created with 19 weights, the two short-term weights are trained too, so
+    /// same-day reviews (those with a `delta_t` under 1) get their own stability update. If this
+    /// was created with [FSRS::new_with_trainable_decay], the forgetting curve's decay is
+    /// trained too, and returned as the final weight.
     pub fn compute_weights(
         &self,
-        items: Vec<FSRSItem>,
+        items: impl IntoIterator<Item = FSRSItem>,
         progress: Option<Arc<Mutex<ProgressState>>>,
     ) -> Result<Vec<f32>> {
-        let (pre_trainset, trainset) = split_data(items);
+        self.compute_weights_inner(items, progress.map(ProgressCollector::new))
+    }
+
+    /// Like [FSRS::compute_weights], but reports progress by invoking `callback` instead of
+    /// updating a shared [ProgressState]. Once training is done, the weights are evaluated on
+    /// the items and reported with [TrainingEvent::Finished]. Returning false from the callback,
+    /// for any event, cancels the computation with [FSRSError::Interrupted].
+    pub fn compute_weights_with_callback<F>(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem>,
+        callback: F,
+    ) -> Result<Vec<f32>>
+    where
+        F: FnMut(TrainingEvent) -> bool + Send + 'static,
+    {
+        let callback: Arc<Mutex<TrainingCallback>> = Arc::new(Mutex::new(Box::new(callback)));
+        let collector = ProgressCollector {
+            callback: Some(callback.clone()),
+            ..Default::default()
+        };
+        let state = collector.state.clone();
+        let (pre_trainset, trainset) = self.split_items(items)?;
+        let initial_stability = pretrain_items(&pre_trainset)?;
+        if trainset.is_empty() {
+            return Err(FSRSError::NotEnoughData);
+        }
+        // shared with the training data, so the items can be evaluated without a copy
+        let trainset: Arc<[FSRSItem]> = trainset.into();
+        let weights = self.train_from(
+            trainset.clone(),
+            self.training_config(initial_stability),
+            None,
+            Some(collector),
+        )?;
+        let epoch_total = state.lock().unwrap().epoch_total;
+        let mut callback = callback.lock().unwrap();
+        if !(*callback)(TrainingEvent::EpochFinished {
+            epoch: epoch_total,
+            epoch_total,
+        }) {
+            return Err(FSRSError::Interrupted);
+        }
+        let evaluation = self
+            .with_other_weights(&weights)?
+            .evaluate_parts(&[pre_trainset.as_slice(), &trainset[..]], |_| true)?;
+        if !(*callback)(TrainingEvent::Finished(evaluation)) {
+            return Err(FSRSError::Interrupted);
+        }
+        Ok(weights)
+    }
+
+    fn compute_weights_inner(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem>,
+        progress: Option<ProgressCollector>,
+    ) -> Result<Vec<f32>> {
+        let (pre_trainset, trainset) = self.split_items(items)?;
         let initial_stability = pretrain(pre_trainset)?;
-        let config = TrainingConfig::new(
+        if trainset.is_empty() {
+            return Err(FSRSError::NotEnoughData);
+        }
+        self.train_weights(trainset, initial_stability, progress)
+    }
+
+    /// Like [FSRS::compute_weights], but instead of failing with [FSRSError::NotEnoughData], falls
+    /// back to [DEFAULT_WEIGHTS](crate::DEFAULT_WEIGHTS) for any weights that could not be
+    /// fitted, along with the starting values of any short-term weights or trainable decay this
+    /// was created with, so the result has the same layout as a full optimization. When there are
+    /// first reviews but fewer than [MIN_TRAINING_REVIEWS] later reviews, only the initial
+    /// stabilities are estimated, as with [FSRS::compute_initial_stability].
+    pub fn compute_weights_with_fallback(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem>,
+        progress: Option<Arc<Mutex<ProgressState>>>,
+    ) -> Result<ComputedWeights> {
+        let (pre_trainset, trainset) = self.split_items(items)?;
+        let initial_stability = match pretrain(pre_trainset) {
+            Ok(initial_stability) => initial_stability,
+            Err(FSRSError::NotEnoughData) => {
+                return Ok(ComputedWeights {
+                    weights: self.default_weights(None),
+                    fitted: FittedWeights::None,
+                })
+            }
+            Err(err) => return Err(err),
+        };
+        if trainset.len() < MIN_TRAINING_REVIEWS {
+            return Ok(ComputedWeights {
+                weights: self.default_weights(Some(initial_stability)),
+                fitted: FittedWeights::InitialStability,
+            });
+        }
+        Ok(ComputedWeights {
+            weights: self.train_weights(
+                trainset,
+                initial_stability,
+                progress.map(ProgressCollector::new),
+            )?,
+            fitted: FittedWeights::All,
+        })
+    }
+
+    /// Train separate weights for each [FSRSItem::group], as with
+    /// [FSRS::compute_weights_with_fallback], so groups with too few reviews get default or
+    /// partly fitted weights rather than failing. Use [FSRS::compute_weights] on all the items
+    /// for pooled weights.
+    pub fn compute_weights_by_group(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem>,
+    ) -> Result<BTreeMap<Option<u64>, ComputedWeights>> {
+        group_items(items)
+            .into_iter()
+            .map(|(group, items)| Ok((group, self.compute_weights_with_fallback(items, None)?)))
+            .collect()
+    }
+
+    /// Like [FSRS::compute_weights], but also refits the weights on `resamples` bootstrap
+    /// resamples of the items, drawn with replacement, to report the range each weight falls in
+    /// across them. A narrow range means the weight is well determined by the history, while a
+    /// wide one means it would swing between optimizations, as is common for small
+    /// collections. `coverage` is the fraction of resamples each range covers; 0.9 gives the
+    /// 5th and 95th percentiles. Each resample is a full optimization, so this takes about
+    /// `resamples + 1` times as long as [FSRS::compute_weights].
+    pub fn compute_weights_with_intervals(
+        &self,
+        items: Vec<FSRSItem>,
+        resamples: usize,
+        coverage: f32,
+    ) -> Result<Vec<WeightInterval>> {
+        if resamples == 0 {
+            return Err(FSRSError::InvalidInput { field: "resamples" });
+        }
+        if coverage.is_nan() || coverage <= 0.0 || coverage >= 1.0 {
+            return Err(FSRSError::InvalidInput { field: "coverage" });
+        }
+        let estimate = self.compute_weights(items.clone(), None)?;
+        let mut rng = StdRng::seed_from_u64(self.training_options().seed);
+        let mut fits = vec![];
+        for _ in 0..resamples {
+            let resample: Vec<_> = (0..items.len())
+                .map(|_| items[rng.gen_range(0..items.len())].clone())
+                .collect();
+            fits.push(self.compute_weights(resample, None)?);
+        }
+        Ok(estimate
+            .into_iter()
+            .enumerate()
+            .map(|(index, estimate)| {
+                let mut values: Vec<f64> = fits.iter().map(|fit| fit[index].into()).collect();
+                values.sort_by(|a, b| a.total_cmp(b));
+                WeightInterval {
+                    estimate,
+                    lower: percentile(&values, (1.0 - f64::from(coverage)) / 2.0) as f32,
+                    upper: percentile(&values, (1.0 + f64::from(coverage)) / 2.0) as f32,
+                }
+            })
+            .collect())
+    }
+
+    /// Estimate only the four initial stabilities, taking the other weights from
+    /// [DEFAULT_WEIGHTS](crate::DEFAULT_WEIGHTS), as [FSRS::compute_weights_with_fallback] does.
+    /// This is much quicker than full training, and needs only a card's first two reviews, so it
+    /// suits collections too small for [FSRS::compute_weights].
+    pub fn compute_initial_stability(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem>,
+    ) -> Result<ComputedWeights> {
+        let (pre_trainset, _) = self.split_items(items)?;
+        Ok(ComputedWeights {
+            weights: self.default_weights(Some(pretrain(pre_trainset)?)),
+            fitted: FittedWeights::InitialStability,
+        })
+    }
+
+    fn split_items(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem>,
+    ) -> Result<(Vec<FSRSItem>, Vec<FSRSItem>)> {
+        let options = self.training_options();
+        if options.max_threads == Some(0) {
+            return Err(FSRSError::InvalidInput {
+                field: "max_threads",
+            });
+        }
+        split_checked_data(items, &options.filter, self.collapse_same_day())
+    }
+
+    /// The weights training starts from, in the layout this was created with, with the given
+    /// initial stabilities if any.
+    fn default_weights(&self, initial_stability: Option<[f32; 4]>) -> Vec<f32> {
+        Model::<B>::new(ModelConfig {
+            initial_stability,
+            short_term: self.has_short_term_weights(),
+            trainable_decay: self.has_trainable_decay(),
+            ..Default::default()
+        })
+        .w
+        .val()
+        .to_data()
+        .convert()
+        .value
+    }
+
+    fn collapse_same_day(&self) -> bool {
+        match self.training_options().same_day_reviews {
+            SameDayReviews::Auto => !self.has_short_term_weights(),
+            SameDayReviews::Keep => false,
+            SameDayReviews::Collapse => true,
+        }
+    }
+
+    /// Nudge the current weights towards reviews done since they were last computed, with a
+    /// few low-learning-rate passes over `new_items` instead of a full retrain over the whole
+    /// history. This keeps weights fresh between full optimizations, such as daily, at a
+    /// fraction of the cost. Items are whole histories of cards reviewed since, as for
+    /// [FSRS::compute_weights]; the initial stabilities are kept, as too few first reviews are
+    /// expected to refit them.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn update_weights(
+        &self,
+        new_items: impl IntoIterator<Item = FSRSItem>,
+        config: UpdateConfig,
+    ) -> Result<Vec<f32>> {
+        if config.num_epochs == 0 {
+            return Err(FSRSError::InvalidInput {
+                field: "num_epochs",
+            });
+        }
+        if config.learning_rate.is_nan() || config.learning_rate <= 0.0 {
+            return Err(FSRSError::InvalidInput {
+                field: "learning_rate",
+            });
+        }
+        let weights: Vec<f32> = self.model().w.val().to_data().convert().value;
+        let (_, trainset) = self.split_items(new_items)?;
+        if trainset.is_empty() {
+            return Err(FSRSError::NotEnoughData);
+        }
+        let initial_stability = [weights[0], weights[1], weights[2], weights[3]];
+        let training_config = self
+            .training_config(initial_stability)
+            .with_num_epochs(config.num_epochs)
+            .with_learning_rate(config.learning_rate);
+        self.train_from(trainset.into(), training_config, Some(&weights), None)
+    }
+
+    /// Fine-tune the current weights on the provided review history, pulling them towards
+    /// their current values rather than training from scratch. Create this with weights
+    /// trained on many users, such as [DEFAULT_WEIGHTS](crate::DEFAULT_WEIGHTS), the median of
+    /// weights optimized on public datasets, and the result starts from those and moves only as
+    /// far as the collection supports, which behaves much better than [FSRS::compute_weights] on
+    /// small collections. Unlike full training, the initial stabilities are fine-tuned too.
+    /// Weights must have been provided when calling FSRS::new().
+    pub fn fine_tune_weights(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem>,
+        config: FineTuneConfig,
+    ) -> Result<Vec<f32>> {
+        if !config.strength.is_finite() || config.strength < 0.0 {
+            return Err(FSRSError::InvalidInput { field: "strength" });
+        }
+        let prior: Vec<f32> = self.model().w.val().to_data().convert().value;
+        let (pre_trainset, trainset) = self.split_items(items)?;
+        // without the initial stabilities frozen, first reviews inform them too
+        let trainset: Vec<_> = pre_trainset.into_iter().chain(trainset).collect();
+        if trainset.is_empty() {
+            return Err(FSRSError::NotEnoughData);
+        }
+        let initial_stability = [prior[0], prior[1], prior[2], prior[3]];
+        let mut training_config = self.training_config(initial_stability);
+        training_config.model.freeze_stability = false;
+        training_config.model.prior_strength = config.strength / trainset.len() as f32;
+        training_config.model.prior = Some(prior.clone());
+        self.train_from(trainset.into(), training_config, Some(&prior), None)
+    }
+
+    fn training_config(&self, initial_stability: [f32; 4]) -> TrainingConfig {
+        TrainingConfig::new(
             ModelConfig {
                 freeze_stability: true,
                 initial_stability: Some(initial_stability),
-            },
+                short_term: self.has_short_term_weights(),
+                trainable_decay: self.has_trainable_decay(),
+                ..Default::default()
+            }
+            .with_curve(self.forgetting_curve()),
             AdamConfig::new(),
-        );
+        )
+        .with_seed(self.training_options().seed)
+    }
 
-        let model = train::<ADBackendDecorator<B>>(
-            trainset,
-            &config,
-            self.device(),
-            progress.map(ProgressCollector::new),
-        );
+    fn train_weights(
+        &self,
+        trainset: Vec<FSRSItem>,
+        initial_stability: [f32; 4],
+        progress: Option<ProgressCollector>,
+    ) -> Result<Vec<f32>> {
+        let config = self.training_config(initial_stability);
+        self.train_from(trainset.into(), config, None, progress)
+    }
+
+    fn train_from(
+        &self,
+        trainset: Arc<[FSRSItem]>,
+        config: TrainingConfig,
+        initial_weights: Option<&Weights>,
+        progress: Option<ProgressCollector>,
+    ) -> Result<Vec<f32>> {
+        let options = self.training_options();
+        if options.max_history == Some(0) {
+            return Err(FSRSError::InvalidInput {
+                field: "max_history",
+            });
+        }
+        let device = self.device();
+        let model = with_thread_limit(options.max_threads, || {
+            let trainset = match options.max_history {
+                Some(max_history) => trainset
+                    .par_iter()
+                    .map(|item| item.keep_last(max_history + 1))
+                    .collect::<Vec<_>>()
+                    .into(),
+                None => trainset,
+            };
+            train::<ADBackendDecorator<B>>(
+                trainset,
+                &config,
+                initial_weights,
+                options.shuffle,
+                options.on_disk,
+                device,
+                progress,
+            )
+        });
 
         Ok(model?.w.val().to_data().convert().value)
     }
 }
 
+/// With fewer reviews than this, besides each card's first, [FSRS::compute_weights_with_fallback]
+/// only estimates the initial stabilities, as the other weights would be overfitted.
+pub const MIN_TRAINING_REVIEWS: usize = 400;
+
+/// Weights returned by [FSRS::compute_weights_with_fallback].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedWeights {
+    pub weights: Vec<f32>,
+    /// Which of the weights were fitted to the review history. The rest are taken from
+    /// [DEFAULT_WEIGHTS](crate::DEFAULT_WEIGHTS).
+    pub fitted: FittedWeights,
+}
+
+/// A weight fitted to all the items, and the range it fell in when refitted on resamples of
+/// them, as returned by [FSRS::compute_weights_with_intervals].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightInterval {
+    pub estimate: f32,
+    pub lower: f32,
+    pub upper: f32,
+}
+
+impl WeightInterval {
+    pub fn width(&self) -> f32 {
+        self.upper - self.lower
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FittedWeights {
+    /// Every weight was optimized.
+    All,
+    /// Only the four initial stabilities were estimated, from the first review of each card.
+    InitialStability,
+    /// There was not enough data to fit anything.
+    None,
+}
+
+/// A handle to weights being computed on a background thread, returned by
+/// [FSRS::compute_weights_async]. It implements [Future], so it can be awaited from any
+/// async runtime without tying this crate to a particular executor. If the computation panics,
+/// the panic is resumed when the handle is polled. Dropping the handle before it completes sets
+/// [ProgressState::want_abort], so training stops at its next progress update.
+pub struct ComputeWeightsHandle {
+    shared: Arc<Mutex<AsyncComputeState>>,
+    progress: Arc<Mutex<ProgressState>>,
+    done: bool,
+}
+
+#[derive(Default)]
+struct AsyncComputeState {
+    result: Option<std::thread::Result<Result<Vec<f32>>>>,
+    waker: Option<Waker>,
+}
+
+impl ComputeWeightsHandle {
+    fn spawn(
+        progress: Arc<Mutex<ProgressState>>,
+        compute: impl FnOnce(Arc<Mutex<ProgressState>>) -> Result<Vec<f32>> + Send + 'static,
+    ) -> Self {
+        let shared = Arc::new(Mutex::new(AsyncComputeState::default()));
+        let thread_shared = shared.clone();
+        let thread_progress = progress.clone();
+        std::thread::spawn(move || {
+            // a panic is resumed by the handle, so no state it left broken is used afterwards
+            let result = panic::catch_unwind(AssertUnwindSafe(|| compute(thread_progress)));
+            let mut state = thread_shared.lock().unwrap();
+            state.result = Some(result);
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        });
+        Self {
+            shared,
+            progress,
+            done: false,
+        }
+    }
+}
+
+impl Future for ComputeWeightsHandle {
+    type Output = Result<Vec<f32>>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let mut shared = this.shared.lock().unwrap();
+        match shared.result.take() {
+            Some(result) => {
+                this.done = true;
+                match result {
+                    Ok(result) => Poll::Ready(result),
+                    Err(payload) => panic::resume_unwind(payload),
+                }
+            }
+            None => {
+                shared.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for ComputeWeightsHandle {
+    fn drop(&mut self) {
+        if !self.done {
+            // a poisoned lock means the computation has already panicked
+            if let Ok(mut progress) = self.progress.lock() {
+                progress.want_abort = true;
+            }
+        }
+    }
+}
+
+impl<B: Backend> FSRS<B> {
+    /// Like [FSRS::compute_weights], but runs the optimization on a dedicated thread, so that
+    /// async applications can await the result without blocking their runtime. If `progress`
+    /// is not provided, a new state is used, so dropping the handle still stops training.
+    pub fn compute_weights_async(
+        &self,
+        items: impl IntoIterator<Item = FSRSItem> + Send + 'static,
+        progress: Option<Arc<Mutex<ProgressState>>>,
+    ) -> ComputeWeightsHandle {
+        let fsrs = self.clone();
+        ComputeWeightsHandle::spawn(
+            progress.unwrap_or_else(ProgressState::new_shared),
+            move |progress| fsrs.compute_weights(items, Some(progress)),
+        )
+    }
+}
+
+fn shuffled<D: Dataset<FSRSItem>>(
+    dataset: D,
+    config: &TrainingConfig,
+    shuffle: BatchShuffle,
+) -> BatchShuffledDataset<D, FSRSItem> {
+    BatchShuffledDataset::with_strategy(
+        dataset,
+        config.batch_size,
+        config.seed,
+        shuffle,
+        |item: &FSRSItem| item.reviews.len(),
+    )
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(items = items.len())))]
 fn train<B: ADBackend>(
-    items: Vec<FSRSItem>,
+    items: Arc<[FSRSItem]>,
     config: &TrainingConfig,
+    initial_weights: Option<&Weights>,
+    shuffle: BatchShuffle,
+    on_disk: bool,
     device: B::Device,
     progress: Option<ProgressCollector>,
 ) -> Result<Model<B>> {
@@ -224,13 +829,16 @@ fn train<B: ADBackend>(
     // Training data
     let iterations = (items.len() / config.batch_size + 1) * config.num_epochs;
     let batcher_train = FSRSBatcher::<B>::new(device.clone());
-    let dataloader_train = DataLoaderBuilder::new(batcher_train)
-        .batch_size(config.batch_size)
-        .build(BatchShuffledDataset::with_seed(
-            FSRSDataset::from(items),
-            config.batch_size,
-            config.seed,
-        ));
+    let dataloader_train = DataLoaderBuilder::new(batcher_train).batch_size(config.batch_size);
+    let dataloader_train = if on_disk {
+        dataloader_train.build(shuffled(
+            DiskDataset::new(items.iter().cloned())?,
+            config,
+            shuffle,
+        ))
+    } else {
+        dataloader_train.build(shuffled(FSRSDataset::from(items), config, shuffle))
+    };
 
     // We don't use any validation data
     let batcher_valid = FSRSBatcher::<B::InnerBackend>::new(device.clone());
@@ -251,7 +859,7 @@ fn train<B: ADBackend>(
         builder = builder.renderer(progress);
     } else {
         // comment out if you want to see text interface
-        builder = builder.renderer(NoProgress {});
+        builder = builder.renderer(NoProgress::default());
         // builder = builder
         //     .metric_train_plot(AccuracyMetric::new())
         //     .metric_valid_plot(AccuracyMetric::new())
@@ -265,11 +873,11 @@ fn train<B: ADBackend>(
             .with_file_checkpointer(10, PrettyJsonFileRecorder::<FullPrecisionSettings>::new());
     }
 
-    let learner = builder.build(
-        config.model.init::<B>(),
-        config.optimizer.init(),
-        lr_scheduler,
-    );
+    let model = match initial_weights {
+        Some(weights) => weights_to_model(weights, config.model.clone()),
+        None => config.model.init::<B>(),
+    };
+    let learner = builder.build(model, config.optimizer.init(), lr_scheduler);
 
     let mut model_trained = learner.fit(dataloader_train, dataloader_valid);
 
@@ -278,7 +886,10 @@ fn train<B: ADBackend>(
     }
 
     info!("trained weights: {}", &model_trained.w.val());
-    model_trained.w = Param::from(weight_clipper(model_trained.w.val()));
+    model_trained.w = Param::from(weight_clipper(
+        model_trained.w.val(),
+        model_trained.config.trainable_decay,
+    ));
     info!("clipped weights: {}", &model_trained.w.val());
 
     if let Ok(path) = artifact_dir {
@@ -293,14 +904,35 @@ fn train<B: ADBackend>(
     Ok(model_trained)
 }
 
-struct NoProgress {}
+/// Emit an event at the start of each epoch, so the time spent in each can be seen.
+#[cfg(feature = "tracing")]
+fn trace_epoch(previous_epoch: usize, item: &TrainingProgress) {
+    if previous_epoch != item.epoch {
+        tracing::debug!(
+            epoch = item.epoch,
+            epoch_total = item.epoch_total,
+            "starting epoch"
+        );
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+fn trace_epoch(_previous_epoch: usize, _item: &TrainingProgress) {}
+
+#[derive(Default)]
+struct NoProgress {
+    epoch: usize,
+}
 
 impl DashboardRenderer for NoProgress {
     fn update_train(&mut self, _state: DashboardMetricState) {}
 
     fn update_valid(&mut self, _state: DashboardMetricState) {}
 
-    fn render_train(&mut self, _item: TrainingProgress) {}
+    fn render_train(&mut self, item: TrainingProgress) {
+        trace_epoch(self.epoch, &item);
+        self.epoch = item.epoch;
+    }
 
     fn render_valid(&mut self, _item: TrainingProgress) {}
 }
@@ -309,9 +941,32 @@ impl DashboardRenderer for NoProgress {
 mod tests {
     use super::*;
     use crate::convertor_tests::anki21_sample_file_converted_to_fsrs;
+    use crate::dataset::split_data;
     use crate::pre_training::pretrain;
+    use crate::DEFAULT_WEIGHTS;
     use burn::backend::ndarray::NdArrayDevice;
     use burn::backend::NdArrayAutodiffBackend;
+    use std::task::Wake;
+
+    struct ThreadWaker(std::thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let mut future = std::pin::pin!(future);
+        let waker: Waker = Arc::new(ThreadWaker(std::thread::current())).into();
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            match future.as_mut().poll(&mut cx) {
+                Poll::Ready(output) => return output,
+                Poll::Pending => std::thread::park(),
+            }
+        }
+    }
 
     #[test]
     fn training() {
@@ -327,11 +982,299 @@ mod tests {
             ModelConfig {
                 freeze_stability: true,
                 initial_stability: Some(initial_stability),
+                ..Default::default()
             },
             AdamConfig::new(),
         );
 
-        let _model_trained =
-            train::<NdArrayAutodiffBackend>(trainset, &config, device, None).unwrap();
+        let _model_trained = train::<NdArrayAutodiffBackend>(
+            trainset.into(),
+            &config,
+            None,
+            BatchShuffle::default(),
+            false,
+            device,
+            None,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn update_weights() {
+        if std::env::var("SKIP_TRAINING").is_ok() {
+            println!("Skipping test in CI");
+            return;
+        }
+        let fsrs = FSRS::new(Some(&[])).unwrap();
+        let new_items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(5)
+            .collect();
+        let updated = fsrs
+            .update_weights(new_items.clone(), UpdateConfig::default())
+            .unwrap();
+        assert_eq!(updated.len(), 17);
+        assert_eq!(updated[..4], DEFAULT_WEIGHTS[..4]);
+        assert_ne!(updated, DEFAULT_WEIGHTS);
+        assert!(updated
+            .iter()
+            .zip(DEFAULT_WEIGHTS)
+            .all(|(updated, default)| (updated - default).abs() < 0.1));
+
+        assert!(matches!(
+            fsrs.update_weights(vec![], UpdateConfig::default()),
+            Err(FSRSError::NotEnoughData)
+        ));
+        let no_epochs = UpdateConfig {
+            num_epochs: 0,
+            ..Default::default()
+        };
+        assert!(matches!(
+            fsrs.update_weights(new_items, no_epochs),
+            Err(FSRSError::InvalidInput {
+                field: "num_epochs"
+            })
+        ));
+    }
+
+    #[test]
+    fn fine_tune_weights() {
+        if std::env::var("SKIP_TRAINING").is_ok() {
+            println!("Skipping test in CI");
+            return;
+        }
+        let fsrs = FSRS::new(Some(&[])).unwrap();
+        let items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(20)
+            .collect();
+        let distance = |weights: &[f32]| -> f32 {
+            weights
+                .iter()
+                .zip(DEFAULT_WEIGHTS)
+                .map(|(w, d)| (w - d).powi(2))
+                .sum()
+        };
+        let tuned = fsrs
+            .fine_tune_weights(items.clone(), FineTuneConfig::default())
+            .unwrap();
+        assert_eq!(tuned.len(), 17);
+        assert_ne!(tuned, DEFAULT_WEIGHTS);
+        let loose = fsrs
+            .fine_tune_weights(items.clone(), FineTuneConfig { strength: 0.0 })
+            .unwrap();
+        assert!(distance(&tuned) < distance(&loose));
+
+        assert!(matches!(
+            fsrs.fine_tune_weights(items, FineTuneConfig { strength: -1.0 }),
+            Err(FSRSError::InvalidInput { field: "strength" })
+        ));
+    }
+
+    #[test]
+    fn compute_weights_with_intervals() {
+        if std::env::var("SKIP_TRAINING").is_ok() {
+            println!("Skipping test in CI");
+            return;
+        }
+        let fsrs = FSRS::new(None).unwrap();
+        let items: Vec<_> = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .step_by(2)
+            .collect();
+        let intervals = fsrs
+            .compute_weights_with_intervals(items.clone(), 3, 0.9)
+            .unwrap();
+        assert_eq!(intervals.len(), 17);
+        assert!(intervals.iter().all(|i| i.width() >= 0.0));
+        assert!(intervals.iter().any(|i| i.width() > 0.0));
+
+        assert!(matches!(
+            fsrs.compute_weights_with_intervals(items.clone(), 0, 0.9),
+            Err(FSRSError::InvalidInput { field: "resamples" })
+        ));
+        assert!(matches!(
+            fsrs.compute_weights_with_intervals(items, 3, 1.0),
+            Err(FSRSError::InvalidInput { field: "coverage" })
+        ));
+    }
+
+    #[test]
+    fn compute_weights_with_fallback() {
+        let fsrs = FSRS::new(None).unwrap();
+        let computed = fsrs.compute_weights_with_fallback(vec![], None).unwrap();
+        assert_eq!(computed.fitted, FittedWeights::None);
+        assert_eq!(computed.weights, DEFAULT_WEIGHTS);
+
+        let mut short_term_weights = DEFAULT_WEIGHTS.to_vec();
+        short_term_weights.extend([0.5, 0.5]);
+        let computed = FSRS::new(Some(&short_term_weights))
+            .unwrap()
+            .compute_weights_with_fallback(vec![], None)
+            .unwrap();
+        assert_eq!(computed.weights.len(), 19);
+        assert_eq!(computed.weights[..17], DEFAULT_WEIGHTS[..]);
+
+        let first_reviews = anki21_sample_file_converted_to_fsrs()
+            .into_iter()
+            .filter(|item| item.reviews.len() == 2);
+        let computed = fsrs
+            .compute_weights_with_fallback(first_reviews, None)
+            .unwrap();
+        assert_eq!(computed.fitted, FittedWeights::InitialStability);
+        assert_eq!(computed.weights.len(), 17);
+        assert_eq!(computed.weights[4..], DEFAULT_WEIGHTS[4..]);
+
+        let items = anki21_sample_file_converted_to_fsrs();
+        let with_len = |len| {
+            items
+                .iter()
+                .filter(move |item| item.reviews.len() == len)
+                .take(300)
+                .cloned()
+        };
+        let few_reviews = with_len(2).chain(with_len(3));
+        let computed = fsrs
+            .compute_weights_with_fallback(few_reviews, None)
+            .unwrap();
+        assert_eq!(computed.fitted, FittedWeights::InitialStability);
+    }
+
+    #[test]
+    fn compute_weights_by_group() {
+        let items = anki21_sample_file_converted_to_fsrs();
+        let first_reviews = items
+            .iter()
+            .filter(|item| item.reviews.len() == 2)
+            .map(|item| FSRSItem {
+                group: Some(1),
+                ..item.clone()
+            });
+        let long_item = items.iter().find(|item| item.reviews.len() > 2).unwrap();
+        let grouped = FSRS::new(None)
+            .unwrap()
+            .compute_weights_by_group(first_reviews.chain([long_item.clone()]))
+            .unwrap();
+        assert_eq!(grouped.len(), 2);
+        assert_eq!(grouped[&Some(1)].fitted, FittedWeights::InitialStability);
+        assert_eq!(grouped[&None].fitted, FittedWeights::None);
+    }
+
+    #[test]
+    fn compute_initial_stability() {
+        let fsrs = FSRS::new(None).unwrap();
+        let items = anki21_sample_file_converted_to_fsrs();
+        let computed = fsrs.compute_initial_stability(items.clone()).unwrap();
+        assert_eq!(computed.fitted, FittedWeights::InitialStability);
+        assert_eq!(
+            computed.weights[..4],
+            pretrain(split_data(items.clone()).0).unwrap()
+        );
+        assert_eq!(computed.weights[4..], DEFAULT_WEIGHTS[4..]);
+        assert!(matches!(
+            fsrs.compute_initial_stability(vec![]),
+            Err(FSRSError::NotEnoughData)
+        ));
+
+        let single_threaded = FSRS::new(None)
+            .unwrap()
+            .with_training_options(TrainingOptions {
+                max_threads: Some(1),
+                ..Default::default()
+            });
+        assert_eq!(
+            single_threaded
+                .compute_initial_stability(items.clone())
+                .unwrap(),
+            computed
+        );
+        let no_threads = FSRS::new(None)
+            .unwrap()
+            .with_training_options(TrainingOptions {
+                max_threads: Some(0),
+                ..Default::default()
+            });
+        assert!(matches!(
+            no_threads.compute_initial_stability(items),
+            Err(FSRSError::InvalidInput {
+                field: "max_threads"
+            })
+        ));
+    }
+
+    #[test]
+    fn compute_short_term_weights() {
+        if std::env::var("SKIP_TRAINING").is_ok() {
+            println!("Skipping test in CI");
+            return;
+        }
+        let weights: Vec<f32> = DEFAULT_WEIGHTS.iter().copied().chain([0.5, 0.3]).collect();
+        let fsrs = FSRS::new(Some(&weights)).unwrap();
+        let weights = fsrs
+            .compute_weights(anki21_sample_file_converted_to_fsrs(), None)
+            .unwrap();
+        assert_eq!(weights.len(), 19);
+    }
+
+    #[test]
+    fn compute_weights_async() {
+        if std::env::var("SKIP_TRAINING").is_ok() {
+            println!("Skipping test in CI");
+            return;
+        }
+        let fsrs = FSRS::new(None).unwrap();
+        let handle = fsrs.compute_weights_async(anki21_sample_file_converted_to_fsrs(), None);
+        let weights = block_on(handle).unwrap();
+        assert_eq!(weights.len(), 17);
+
+        let progress = ProgressState::new_shared();
+        let handle = fsrs.compute_weights_async(
+            anki21_sample_file_converted_to_fsrs(),
+            Some(progress.clone()),
+        );
+        drop(handle);
+        assert!(progress.lock().unwrap().want_abort);
+    }
+
+    #[test]
+    fn compute_weights_async_panic() {
+        let handle = ComputeWeightsHandle::spawn(ProgressState::new_shared(), |_| panic!("failed"));
+        let payload = panic::catch_unwind(AssertUnwindSafe(|| block_on(handle))).unwrap_err();
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"failed"));
+    }
+
+    #[test]
+    fn compute_weights_with_callback() {
+        if std::env::var("SKIP_TRAINING").is_ok() {
+            println!("Skipping test in CI");
+            return;
+        }
+        let fsrs = FSRS::new(None).unwrap();
+        let events = Arc::new(Mutex::new(vec![]));
+        let events_inner = events.clone();
+        let weights = fsrs
+            .compute_weights_with_callback(anki21_sample_file_converted_to_fsrs(), move |event| {
+                events_inner.lock().unwrap().push(event);
+                true
+            })
+            .unwrap();
+        assert_eq!(weights.len(), 17);
+        let events = events.lock().unwrap();
+        assert!(matches!(events.last(), Some(TrainingEvent::Finished(_))));
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, TrainingEvent::EpochFinished { .. }))
+                .count(),
+            16
+        );
+
+        // declining the final event still cancels
+        assert!(matches!(
+            fsrs.compute_weights_with_callback(anki21_sample_file_converted_to_fsrs(), |event| {
+                !matches!(event, TrainingEvent::Finished(_))
+            }),
+            Err(FSRSError::Interrupted)
+        ));
     }
 }
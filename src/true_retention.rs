@@ -0,0 +1,198 @@
+use crate::dataset::{is_same_day, FSRSItem};
+use std::collections::BTreeMap;
+
+/// Reviews with at least this many days elapsed are of mature cards, as in Anki's statistics.
+pub const MATURE_DAYS: f32 = 21.0;
+
+/// The length of the periods [true_retention] reports on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionPeriod {
+    Day,
+    /// Weeks starting on Monday.
+    Week,
+    /// Calendar months.
+    Month,
+}
+
+/// Passed and failed reviews, where a review rated Again is a failure.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RetentionCount {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+impl RetentionCount {
+    /// The fraction of reviews passed, or None if there were no reviews.
+    pub fn retention(&self) -> Option<f32> {
+        let total = self.passed + self.failed;
+        (total > 0).then(|| self.passed as f32 / total as f32)
+    }
+
+    fn add(&mut self, rating: u32) {
+        if rating == 1 {
+            self.failed += 1;
+        } else {
+            self.passed += 1;
+        }
+    }
+}
+
+/// The observed retention in one period, as returned by [true_retention].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetentionStats {
+    /// The first day of the period, in days since the Unix epoch.
+    pub start: i64,
+    /// Reviews made after fewer than [MATURE_DAYS] days.
+    pub young: RetentionCount,
+    pub mature: RetentionCount,
+}
+
+impl RetentionStats {
+    pub fn total(&self) -> RetentionCount {
+        RetentionCount {
+            passed: self.young.passed + self.mature.passed,
+            failed: self.young.failed + self.mature.failed,
+        }
+    }
+}
+
+/// The retention the user actually achieved in each period, for comparing with their desired
+/// retention. Items are given with the day of their last review, in days since the Unix epoch
+/// in the user's timezone, and each item counts its last review, so items should hold an item
+/// for each review of each card, as training does. First reviews and same-day reviews, such as
+/// learning steps, are not counted. Periods without reviews are left out.
+pub fn true_retention<'a>(
+    items: impl IntoIterator<Item = (i64, &'a FSRSItem)>,
+    period: RetentionPeriod,
+) -> Vec<RetentionStats> {
+    let mut periods: BTreeMap<i64, RetentionStats> = BTreeMap::new();
+    for (day, item) in items {
+        let [_, .., review] = item.reviews.as_slice() else {
+            continue;
+        };
+        if is_same_day(review.delta_t) {
+            continue;
+        }
+        let start = period_start(day, period);
+        let stats = periods.entry(start).or_insert(RetentionStats {
+            start,
+            young: RetentionCount::default(),
+            mature: RetentionCount::default(),
+        });
+        if review.delta_t >= MATURE_DAYS {
+            stats.mature.add(review.rating);
+        } else {
+            stats.young.add(review.rating);
+        }
+    }
+    periods.into_values().collect()
+}
+
+fn period_start(day: i64, period: RetentionPeriod) -> i64 {
+    match period {
+        RetentionPeriod::Day => day,
+        // the epoch was a Thursday
+        RetentionPeriod::Week => day - (day + 3).rem_euclid(7),
+        RetentionPeriod::Month => {
+            let (_, _, day_of_month) = civil_from_days(day);
+            day - (day_of_month - 1)
+        }
+    }
+}
+
+/// The (year, month, day) of a day since the Unix epoch, in the proleptic Gregorian calendar.
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn civil_from_days(day: i64) -> (i64, i64, i64) {
+    let z = day + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dataset::FSRSReview;
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(19723), (2024, 1, 1));
+        assert_eq!(civil_from_days(19782), (2024, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        // 2024-01-01 was a Monday
+        assert_eq!(period_start(19723 + 6, RetentionPeriod::Week), 19723);
+        assert_eq!(period_start(19782, RetentionPeriod::Month), 19754);
+    }
+
+    #[test]
+    fn retention_by_period() {
+        let item = |ratings_and_delays: &[(u32, f32)]| FSRSItem {
+            reviews: ratings_and_delays
+                .iter()
+                .map(|&(rating, delta_t)| FSRSReview {
+                    rating,
+                    delta_t,
+                    duration_ms: None,
+                })
+                .collect(),
+            group: None,
+        };
+        let young_pass = item(&[(3, 0.0), (3, 2.0)]);
+        let young_fail = item(&[(3, 0.0), (1, 2.0)]);
+        let mature_pass = item(&[(3, 0.0), (3, 30.0)]);
+        let learning_step = item(&[(1, 0.0), (3, 0.0)]);
+        let first_review = item(&[(3, 0.0)]);
+        // a Monday, and the Sunday and Monday after it
+        let items = [
+            (19723, &young_pass),
+            (19723, &young_fail),
+            (19723, &learning_step),
+            (19729, &mature_pass),
+            (19729, &first_review),
+            (19730, &young_pass),
+        ];
+
+        let daily = true_retention(items, RetentionPeriod::Day);
+        assert_eq!(
+            daily.iter().map(|s| s.start).collect::<Vec<_>>(),
+            [19723, 19729, 19730]
+        );
+        assert_eq!(
+            daily[0].young,
+            RetentionCount {
+                passed: 1,
+                failed: 1
+            }
+        );
+        assert_eq!(daily[0].young.retention(), Some(0.5));
+        assert_eq!(daily[0].mature.retention(), None);
+
+        let weekly = true_retention(items, RetentionPeriod::Week);
+        assert_eq!(weekly.len(), 2);
+        assert_eq!(
+            weekly[0].total(),
+            RetentionCount {
+                passed: 2,
+                failed: 1
+            }
+        );
+        assert_eq!(weekly[0].mature.passed, 1);
+
+        let monthly = true_retention(items, RetentionPeriod::Month);
+        assert_eq!(monthly.len(), 1);
+        assert_eq!(
+            monthly[0].total(),
+            RetentionCount {
+                passed: 3,
+                failed: 1
+            }
+        );
+    }
+}
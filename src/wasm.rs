@@ -0,0 +1,186 @@
+use crate::{FSRSItem, FSRSReview, MemoryState, NextStates, TrainingEvent, FSRS};
+use wasm_bindgen::prelude::*;
+
+/// JavaScript bindings for [FSRS]. Review histories are passed as flat arrays, with `lengths`
+/// giving the number of reviews in each item.
+#[wasm_bindgen(js_name = FSRS)]
+pub struct WasmFSRS {
+    inner: FSRS,
+}
+
+#[wasm_bindgen(js_class = FSRS)]
+impl WasmFSRS {
+    /// Weights may be omitted or empty to use the default values.
+    #[wasm_bindgen(constructor)]
+    pub fn new(weights: Option<Vec<f32>>) -> Result<WasmFSRS, JsError> {
+        Ok(Self {
+            inner: FSRS::new(Some(weights.unwrap_or_default().as_slice()))?,
+        })
+    }
+
+    #[wasm_bindgen(js_name = memoryState)]
+    pub fn memory_state(&self, ratings: Vec<u32>, delta_ts: Vec<f32>) -> WasmMemoryState {
+        let item = items_from_arrays(&ratings, &delta_ts, &[ratings.len() as u32]).remove(0);
+        self.inner.memory_state(item).into()
+    }
+
+    /// Pass no memory state for a new card.
+    #[wasm_bindgen(js_name = nextStates)]
+    pub fn next_states(
+        &self,
+        current_memory_state: Option<WasmMemoryState>,
+        desired_retention: f32,
+        days_elapsed: f32,
+        maximum_interval: u32,
+    ) -> WasmNextStates {
+        self.inner
+            .next_states(
+                current_memory_state.map(Into::into),
+                desired_retention,
+                days_elapsed,
+                maximum_interval,
+            )
+            .into()
+    }
+
+    /// Train weights on the given items. If provided, `progress` is called with the number of
+    /// items processed and the total, and training is aborted if it returns false.
+    #[wasm_bindgen(js_name = computeWeights)]
+    pub fn compute_weights(
+        &self,
+        ratings: Vec<u32>,
+        delta_ts: Vec<f32>,
+        lengths: Vec<u32>,
+        progress: Option<js_sys::Function>,
+    ) -> Result<Vec<f32>, JsError> {
+        let items = items_from_arrays(&ratings, &delta_ts, &lengths);
+        let progress = progress.map(SingleThreaded);
+        Ok(self
+            .inner
+            .compute_weights_with_callback(items, move |event| match (&progress, event) {
+                (Some(progress), TrainingEvent::Progress(info)) => !matches!(
+                    progress
+                        .0
+                        .call2(&JsValue::NULL, &info.current.into(), &info.total.into())
+                        .map(|ret| ret.as_bool()),
+                    Ok(Some(false))
+                ),
+                _ => true,
+            })?)
+    }
+}
+
+/// wasm32 has no threads, so a JS value can never be used from a thread other than the one
+/// that created it, and training invokes the callback synchronously.
+struct SingleThreaded(js_sys::Function);
+
+unsafe impl Send for SingleThreaded {}
+
+#[wasm_bindgen(js_name = MemoryState)]
+#[derive(Debug, Clone, Copy)]
+pub struct WasmMemoryState {
+    pub stability: f32,
+    pub difficulty: f32,
+}
+
+#[wasm_bindgen(js_class = MemoryState)]
+impl WasmMemoryState {
+    #[wasm_bindgen(constructor)]
+    pub fn new(stability: f32, difficulty: f32) -> Self {
+        Self {
+            stability,
+            difficulty,
+        }
+    }
+}
+
+impl From<MemoryState> for WasmMemoryState {
+    fn from(state: MemoryState) -> Self {
+        Self::new(state.stability, state.difficulty)
+    }
+}
+
+impl From<WasmMemoryState> for MemoryState {
+    fn from(state: WasmMemoryState) -> Self {
+        MemoryState {
+            stability: state.stability,
+            difficulty: state.difficulty,
+        }
+    }
+}
+
+#[wasm_bindgen(js_name = ItemState)]
+#[derive(Debug, Clone, Copy)]
+pub struct WasmItemState {
+    pub memory: WasmMemoryState,
+    pub interval: u32,
+    pub retrievability: f32,
+    #[wasm_bindgen(js_name = nextDayRetrievability)]
+    pub next_day_retrievability: f32,
+}
+
+#[wasm_bindgen(js_name = NextStates)]
+#[derive(Debug, Clone, Copy)]
+pub struct WasmNextStates {
+    pub again: WasmItemState,
+    pub hard: WasmItemState,
+    pub good: WasmItemState,
+    pub easy: WasmItemState,
+}
+
+impl From<NextStates> for WasmNextStates {
+    fn from(states: NextStates) -> Self {
+        let convert = |state: crate::ItemState| WasmItemState {
+            memory: state.memory.into(),
+            interval: state.interval,
+            retrievability: state.retrievability,
+            next_day_retrievability: state.next_day_retrievability,
+        };
+        Self {
+            again: convert(states.again),
+            hard: convert(states.hard),
+            good: convert(states.good),
+            easy: convert(states.easy),
+        }
+    }
+}
+
+fn items_from_arrays(ratings: &[u32], delta_ts: &[f32], lengths: &[u32]) -> Vec<FSRSItem> {
+    let mut reviews = ratings
+        .iter()
+        .zip(delta_ts)
+        .map(|(&rating, &delta_t)| FSRSReview {
+            rating,
+            delta_t,
+            duration_ms: None,
+        });
+    lengths
+        .iter()
+        .map(|&len| FSRSItem {
+            reviews: reviews.by_ref().take(len as usize).collect(),
+            group: None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn items_from_flat_arrays() {
+        let items = items_from_arrays(&[3, 3, 1, 4], &[0.0, 1.0, 0.0, 5.0], &[1, 3]);
+        assert_eq!(items.len(), 2);
+        assert_eq!(
+            items[0].reviews,
+            vec![FSRSReview {
+                rating: 3,
+                delta_t: 0.0,
+                duration_ms: None
+            }]
+        );
+        assert_eq!(items[1].reviews.len(), 3);
+        assert_eq!(items[1].reviews[2].rating, 4);
+        assert_eq!(items[1].reviews[2].delta_t, 5.0);
+    }
+}
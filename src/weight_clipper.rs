@@ -1,42 +1,402 @@
+use crate::error::{FSRSError, Result};
 use crate::inference::Weights;
+#[cfg(feature = "training")]
 use burn::tensor::{backend::Backend, Data, Tensor};
 
-pub(crate) fn weight_clipper<B: Backend>(weights: Tensor<B, 1>) -> Tensor<B, 1> {
-    let val = clip_weights(&weights.to_data().convert().value);
+#[cfg(feature = "training")]
+pub(crate) fn weight_clipper<B: Backend>(
+    weights: Tensor<B, 1>,
+    trainable_decay: bool,
+) -> Tensor<B, 1> {
+    let val = clip_weights(&weights.to_data().convert().value, trainable_decay);
     Tensor::from_data(Data::new(val, weights.shape()).convert())
 }
 
-pub(crate) fn clip_weights(weights: &Weights) -> Vec<f32> {
-    // https://regex101.com/r/21mXNI/1
-    const CLAMPS: [(f32, f32); 17] = [
-        (0.1, 100.0),
-        (0.1, 100.0),
-        (0.1, 100.0),
-        (0.1, 100.0),
-        (1.0, 10.0),
-        (0.1, 5.0),
-        (0.1, 5.0),
-        (0.0, 0.5),
-        (0.0, 3.0),
-        (0.1, 0.8),
-        (0.01, 2.5),
-        (0.5, 5.0),
-        (0.01, 0.2),
-        (0.01, 0.9),
-        (0.01, 2.0),
-        (0.0, 1.0),
-        (1.0, 10.0),
-    ];
+/// A description of one weight, for displaying or editing weights.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightInfo {
+    /// A stable identifier, which won't change between releases.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The smallest value the weight is clipped to.
+    pub min: f32,
+    /// The largest value the weight is clipped to.
+    pub max: f32,
+}
+
+const fn info(name: &'static str, description: &'static str, min: f32, max: f32) -> WeightInfo {
+    WeightInfo {
+        name,
+        description,
+        min,
+        max,
+    }
+}
+
+static WEIGHT_INFO: [WeightInfo; 19] = [
+    info(
+        "initial_stability_again",
+        "Stability after a first review rated Again.",
+        0.1,
+        100.0,
+    ),
+    info(
+        "initial_stability_hard",
+        "Stability after a first review rated Hard.",
+        0.1,
+        100.0,
+    ),
+    info(
+        "initial_stability_good",
+        "Stability after a first review rated Good.",
+        0.1,
+        100.0,
+    ),
+    info(
+        "initial_stability_easy",
+        "Stability after a first review rated Easy.",
+        0.1,
+        100.0,
+    ),
+    info(
+        "initial_difficulty",
+        "Difficulty after a first review rated Good, which later difficulties revert towards.",
+        1.0,
+        10.0,
+    ),
+    info(
+        "initial_difficulty_rating_factor",
+        "How much each step of the first rating away from Good changes the initial difficulty.",
+        0.1,
+        5.0,
+    ),
+    info(
+        "difficulty_delta",
+        "How much each step of a later rating away from Good changes difficulty.",
+        0.1,
+        5.0,
+    ),
+    info(
+        "mean_reversion",
+        "How strongly difficulty is pulled back towards the initial difficulty on each review.",
+        0.0,
+        0.5,
+    ),
+    info(
+        "recall_stability_factor",
+        "Scales how much stability grows after a successful review, as e to this power.",
+        0.0,
+        3.0,
+    ),
+    info(
+        "recall_stability_decay",
+        "How much less stability grows after a successful review as it gets larger.",
+        0.1,
+        0.8,
+    ),
+    info(
+        "recall_retrievability_factor",
+        "How much more stability grows when a card is recalled at a lower retrievability.",
+        0.01,
+        2.5,
+    ),
+    info(
+        "forget_stability_factor",
+        "Scales the stability after a lapse.",
+        0.5,
+        5.0,
+    ),
+    info(
+        "forget_difficulty_exponent",
+        "How much a higher difficulty lowers the stability after a lapse.",
+        0.01,
+        0.2,
+    ),
+    info(
+        "forget_stability_exponent",
+        "How much of the previous stability is kept after a lapse.",
+        0.01,
+        0.9,
+    ),
+    info(
+        "forget_retrievability_factor",
+        "How much more stability is kept when a card is forgotten at a lower retrievability.",
+        0.01,
+        2.0,
+    ),
+    info(
+        "hard_penalty",
+        "Multiplies the growth in stability after a review rated Hard.",
+        0.0,
+        1.0,
+    ),
+    info(
+        "easy_bonus",
+        "Multiplies the growth in stability after a review rated Easy.",
+        1.0,
+        10.0,
+    ),
+    info(
+        "short_term_factor",
+        "Scales the change in stability from a review on the same day as the previous one.",
+        0.0,
+        2.0,
+    ),
+    info(
+        "short_term_offset",
+        "Shifts which ratings raise stability on a review on the same day as the previous one.",
+        0.0,
+        2.0,
+    ),
+];
+
+static DECAY_INFO: WeightInfo = info(
+    "decay",
+    "The magnitude of the forgetting curve's decay. Larger values forget slower at first, and \
+     faster later.",
+    0.1,
+    1.5,
+);
+
+/// Describe each weight in a set of `len` weights, in order. If `trainable_decay` is set, the
+/// set ends with a decay, as used by [crate::FSRS::new_with_trainable_decay]. Fails with
+/// [FSRSError::InvalidWeights] if no such set of weights has that length.
+pub fn weight_info(len: usize, trainable_decay: bool) -> Result<Vec<WeightInfo>> {
+    let len = len
+        .checked_sub(usize::from(trainable_decay))
+        .filter(|len| matches!(len, 17 | 19))
+        .ok_or(FSRSError::InvalidWeights)?;
+    let decay = trainable_decay.then_some(DECAY_INFO);
+    Ok(WEIGHT_INFO[..len].iter().copied().chain(decay).collect())
+}
+
+/// How one weight differs between two sets, as found by [compare_weights].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeightChange {
+    /// The weight's position in the new set.
+    pub index: usize,
+    pub info: WeightInfo,
+    pub old: f32,
+    pub new: f32,
+}
+
+impl WeightChange {
+    /// The change as a fraction of the range the weight is clipped to, so changes to weights
+    /// of different scales can be compared. Negative when the weight decreased.
+    pub fn relative_change(&self) -> f32 {
+        (self.new - self.old) / (self.info.max - self.info.min)
+    }
+}
+
+/// A sentence describing the change, such as "initial stability for Good increased from 2.10 to
+/// 3.40 days", for showing to users after re-optimization.
+impl std::fmt::Display for WeightChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let direction = if self.new > self.old {
+            "increased"
+        } else {
+            "decreased"
+        };
+        let (old, new) = (self.old, self.new);
+        match self.info.name {
+            "decay" => {
+                let speed = if new > old { "slower" } else { "faster" };
+                write!(
+                    f,
+                    "forgetting decay {direction} from {old:.2} to {new:.2}, so cards are \
+                     forgotten {speed} at first"
+                )
+            }
+            name => match name.strip_prefix("initial_stability_") {
+                Some(rating) => {
+                    let rating = match rating {
+                        "again" => "Again",
+                        "hard" => "Hard",
+                        "good" => "Good",
+                        _ => "Easy",
+                    };
+                    write!(
+                        f,
+                        "initial stability for {rating} {direction} from {old:.2} to {new:.2} days"
+                    )
+                }
+                None => write!(
+                    f,
+                    "{} {direction} from {old:.2} to {new:.2}",
+                    name.replace('_', " ")
+                ),
+            },
+        }
+    }
+}
+
+/// Find the weights that differ between `old` and `new` by at least `min_change` of the range
+/// they are clipped to, largest change first, so apps can explain what changed after
+/// re-optimization. Weights are matched by name, so the sets may differ in length, such as when
+/// short-term weights are added; weights only in one set are not reported. `trainable_decay`
+/// is as for [weight_info], and applies to both sets.
+pub fn compare_weights(
+    old: &Weights,
+    new: &Weights,
+    trainable_decay: bool,
+    min_change: f32,
+) -> Result<Vec<WeightChange>> {
+    let old_info = weight_info(old.len(), trainable_decay)?;
+    let mut changes: Vec<_> = weight_info(new.len(), trainable_decay)?
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, info)| {
+            let old_index = old_info.iter().position(|old| old.name == info.name)?;
+            Some(WeightChange {
+                index,
+                info,
+                old: old[old_index],
+                new: new[index],
+            })
+        })
+        .filter(|change| change.relative_change().abs() >= min_change && change.old != change.new)
+        .collect();
+    changes.sort_by(|a, b| {
+        b.relative_change()
+            .abs()
+            .total_cmp(&a.relative_change().abs())
+    });
+    Ok(changes)
+}
 
+/// A problem found by [check_weights].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WeightIssue {
+    /// No set of weights has this many entries.
+    WrongLength { len: usize },
+    /// The weight is NaN or infinite.
+    NotFinite { index: usize, value: f32 },
+    /// The weight lies outside `min..=max`, and would be clipped.
+    OutOfRange {
+        index: usize,
+        value: f32,
+        min: f32,
+        max: f32,
+    },
+}
+
+impl std::fmt::Display for WeightIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            WeightIssue::WrongLength { len } => {
+                write!(
+                    f,
+                    "expected 17 or 19 weights, or one more with a trainable decay, got {len}"
+                )
+            }
+            WeightIssue::NotFinite { index, value } => write!(f, "w[{index}] is {value}"),
+            WeightIssue::OutOfRange {
+                index,
+                value,
+                min,
+                max,
+            } => write!(f, "w[{index}] is {value}, outside {min}..={max}"),
+        }
+    }
+}
+
+/// Check user-supplied weights before constructing [crate::FSRS] with them, reporting every
+/// problem found. `trainable_decay` is as for [weight_info].
+pub fn check_weights(
+    weights: &[f32],
+    trainable_decay: bool,
+) -> std::result::Result<(), Vec<WeightIssue>> {
+    let info = weight_info(weights.len(), trainable_decay)
+        .map_err(|_| vec![WeightIssue::WrongLength { len: weights.len() }])?;
+    let issues: Vec<_> = weights
+        .iter()
+        .zip(info)
+        .enumerate()
+        .filter_map(|(index, (&value, info))| {
+            if !value.is_finite() {
+                Some(WeightIssue::NotFinite { index, value })
+            } else if !(info.min..=info.max).contains(&value) {
+                Some(WeightIssue::OutOfRange {
+                    index,
+                    value,
+                    min: info.min,
+                    max: info.max,
+                })
+            } else {
+                None
+            }
+        })
+        .collect();
+    if issues.is_empty() {
+        Ok(())
+    } else {
+        Err(issues)
+    }
+}
+
+/// Convert weights stored by an earlier version of FSRS to the current format, so stored
+/// parameters keep working after upgrading. The result is clipped to the current ranges.
+///
+/// - An empty slice gives [crate::DEFAULT_WEIGHTS], as with [crate::FSRS::new].
+/// - 17 or 19 weights are already current, and are only clipped.
+/// - 13 weights from FSRS v3 are mapped onto the 17 of FSRS-4.5. v3 gave the initial
+///   stability as `w0 + w1 * (rating - 1)`, so the four initial stabilities are filled in from
+///   that line. Weights whose sign convention changed are negated: initial difficulty and
+///   difficulty changes now subtract the rating offset, and the stability and difficulty
+///   exponents are now negative powers. v3's `S ^ w11` after a lapse becomes
+///   `(S + 1) ^ w13 - 1`, which is close for the stabilities that lapse. v3 had no hard
+///   penalty or easy bonus, so both are filled in as 1. v3 used an exponential forgetting
+///   curve, so the migrated weights are a starting point, and should be optimized again once
+///   there are enough reviews.
+///
+/// Any other length is an [FSRSError::InvalidWeights].
+pub fn migrate_weights(old: &[f32]) -> Result<Vec<f32>> {
+    match old.len() {
+        0 => Ok(crate::DEFAULT_WEIGHTS.to_vec()),
+        17 | 19 => Ok(clip_weights(old, false)),
+        13 => {
+            let initial_stability = |rating: f32| old[0] + old[1] * (rating - 1.0);
+            Ok(clip_weights(
+                &[
+                    initial_stability(1.0),
+                    initial_stability(2.0),
+                    initial_stability(3.0),
+                    initial_stability(4.0),
+                    old[2],
+                    -old[3],
+                    -old[4],
+                    old[5],
+                    old[6],
+                    -old[7],
+                    old[8],
+                    old[9],
+                    -old[10],
+                    old[11],
+                    old[12],
+                    1.0,
+                    1.0,
+                ],
+                false,
+            ))
+        }
+        _ => Err(FSRSError::InvalidWeights),
+    }
+}
+
+pub(crate) fn clip_weights(weights: &Weights, trainable_decay: bool) -> Vec<f32> {
     let mut weights = weights.to_vec();
+    let decay = if trainable_decay { weights.pop() } else { None };
     weights
         .iter_mut()
-        .zip(CLAMPS)
-        .for_each(|(w, (low, high))| *w = w.clamp(low, high));
+        .zip(&WEIGHT_INFO)
+        .for_each(|(w, info)| *w = w.clamp(info.min, info.max));
+    if let Some(decay) = decay {
+        weights.push(decay.clamp(DECAY_INFO.min, DECAY_INFO.max));
+    }
     weights
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "training"))]
 mod tests {
     use super::*;
     use crate::test_helpers::Tensor;
@@ -46,9 +406,127 @@ mod tests {
         let tensor =
             Tensor::from_floats([0.0, -1000.0, 1000.0, 0.0, 1000.0, -1000.0, 1.0, 0.25, -0.1]);
 
-        let param: Tensor<1> = weight_clipper(tensor);
+        let param: Tensor<1> = weight_clipper(tensor, false);
         let values = &param.to_data().value;
 
         assert_eq!(values, &[0.1, 0.1, 100.0, 0.1, 10.0, 0.1, 1.0, 0.25, 0.0]);
     }
+
+    #[test]
+    fn weight_info_matches_weights() {
+        assert_eq!(weight_info(17, false).unwrap().len(), 17);
+        let info = weight_info(20, true).unwrap();
+        assert_eq!(info[17].name, "short_term_factor");
+        assert_eq!(info[19].name, "decay");
+        assert_eq!(weight_info(18, true).unwrap()[17].name, "decay");
+        // the decay must be asked for, so a stray weight isn't taken for one
+        assert!(weight_info(18, false).is_err());
+        assert!(weight_info(19, true).is_err());
+        assert!(weight_info(16, false).is_err());
+        for (w, info) in crate::DEFAULT_WEIGHTS
+            .iter()
+            .zip(weight_info(17, false).unwrap())
+        {
+            assert!((info.min..=info.max).contains(w), "{}", info.name);
+        }
+    }
+
+    #[test]
+    fn migrate_weights_from_v3() {
+        let v3 = [
+            1.0, 1.0, 5.0, -0.5, -0.5, 0.2, 1.4, -0.12, 0.8, 2.0, -0.2, 0.2, 1.0,
+        ];
+        let migrated = migrate_weights(&v3).unwrap();
+        assert_eq!(migrated.len(), 17);
+        assert_eq!(migrated[..8], [1.0, 2.0, 3.0, 4.0, 5.0, 0.5, 0.5, 0.2]);
+        assert_eq!(migrated[9], 0.12);
+        assert_eq!(migrated[12], 0.2);
+        assert_eq!(check_weights(&migrated, false), Ok(()));
+
+        assert_eq!(
+            migrate_weights(crate::DEFAULT_WEIGHTS).unwrap(),
+            crate::DEFAULT_WEIGHTS
+        );
+        assert_eq!(migrate_weights(&[]).unwrap(), crate::DEFAULT_WEIGHTS);
+        let mut out_of_range = crate::DEFAULT_WEIGHTS.to_vec();
+        out_of_range[7] = 0.9;
+        assert_eq!(migrate_weights(&out_of_range).unwrap()[7], 0.5);
+        assert!(matches!(
+            migrate_weights(&[1.0; 14]),
+            Err(FSRSError::InvalidWeights)
+        ));
+    }
+
+    #[test]
+    fn compare_weights_describes_changes() {
+        let old = crate::DEFAULT_WEIGHTS;
+        let mut new = old.to_vec();
+        new[2] = 3.4;
+        new[16] = 2.6;
+        new.extend([0.5, 0.3]);
+        let changes = compare_weights(old, &new, false, 0.001).unwrap();
+        assert_eq!(
+            changes.len(),
+            2,
+            "the added short-term weights are not compared"
+        );
+        assert_eq!(changes[0].index, 2);
+        assert_eq!(
+            changes[0].to_string(),
+            "initial stability for Good increased from 2.40 to 3.40 days"
+        );
+        assert_eq!(
+            changes[1].to_string(),
+            "easy bonus decreased from 2.61 to 2.60"
+        );
+        assert!(changes[1].relative_change() < 0.0);
+        assert_eq!(compare_weights(old, &new, false, 0.1).unwrap().len(), 0);
+
+        let mut shallow = old.to_vec();
+        shallow.push(0.6);
+        let mut steep = shallow.clone();
+        steep[17] = 0.7;
+        assert_eq!(
+            compare_weights(&shallow, &steep, true, 0.0).unwrap()[0].to_string(),
+            "forgetting decay increased from 0.60 to 0.70, so cards are forgotten slower at first"
+        );
+        assert_eq!(
+            compare_weights(&steep, &shallow, true, 0.0).unwrap()[0].to_string(),
+            "forgetting decay decreased from 0.70 to 0.60, so cards are forgotten faster at first"
+        );
+        assert!(compare_weights(&[1.0], old, false, 0.0).is_err());
+    }
+
+    #[test]
+    fn check_weights_reports_issues() {
+        assert_eq!(check_weights(crate::DEFAULT_WEIGHTS, false), Ok(()));
+        assert_eq!(
+            check_weights(&[1.0; 3], false),
+            Err(vec![WeightIssue::WrongLength { len: 3 }])
+        );
+        let mut weights = crate::DEFAULT_WEIGHTS.to_vec();
+        weights[2] = f32::NAN;
+        weights[7] = 0.9;
+        let issues = check_weights(&weights, false).unwrap_err();
+        assert!(matches!(issues[0], WeightIssue::NotFinite { index: 2, .. }));
+        assert_eq!(
+            issues[1],
+            WeightIssue::OutOfRange {
+                index: 7,
+                value: 0.9,
+                min: 0.0,
+                max: 0.5
+            }
+        );
+        assert_eq!(issues[1].to_string(), "w[7] is 0.9, outside 0..=0.5");
+    }
+
+    #[test]
+    fn trainable_decay_is_clipped() {
+        let mut weights = vec![1.0; 17];
+        weights.push(5.0);
+        let clipped = clip_weights(&weights, true);
+        assert_eq!(clipped.len(), 18);
+        assert_eq!(clipped[17], 1.5);
+    }
 }